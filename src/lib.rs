@@ -10,6 +10,7 @@
 //! - [`handle_message`] (or [`handle_lsp_message`]): Process incoming LSP messages
 //! - [`format_raw`]: Format SPARQL queries directly
 //! - [`format_with_settings`]: Format with custom settings
+//! - [`organize_prefixes_raw`]: Organize the prefix declarations of a query
 //!
 //! # WASM Usage
 //!
@@ -27,7 +28,9 @@ mod sparql;
 
 // Re-export core server types for all targets (used by tests and native builds)
 pub use crate::server::configuration::FormatSettings;
-pub use crate::server::message_handler::formatting::{format_raw, format_with_settings};
+pub use crate::server::message_handler::formatting::{
+    format_raw, format_with_settings, organize_prefixes_raw,
+};
 pub use crate::server::{Server, handle_message};
 
 // Aliases for more descriptive names (for external consumers)
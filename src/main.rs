@@ -38,7 +38,7 @@ use futures::lock::Mutex;
 use tokio::{runtime, task::LocalSet};
 use tracing_subscriber::EnvFilter;
 
-use qlue_ls::{Server, format_raw, handle_message};
+use qlue_ls::{Server, format_raw, handle_message, organize_prefixes_raw};
 
 use crate::stdio_reader::StdioMessages;
 
@@ -62,6 +62,9 @@ enum Command {
         /// Avoid writing formatted file back; instead, exit with a non-zero status code if any files would have been modified, and zero otherwise
         #[arg(short, long)]
         check: bool,
+        /// Remove unused and duplicate prefix declarations, declare missing ones and sort them
+        #[arg(long)]
+        organize_prefixes: bool,
         /// Omit to read from stdin
         path: Option<PathBuf>,
     },
@@ -124,14 +127,22 @@ fn main() {
             path,
             writeback,
             check,
+            organize_prefixes,
         } => {
+            let prepare = |text: String| {
+                if organize_prefixes {
+                    organize_prefixes_raw(text)
+                } else {
+                    text
+                }
+            };
             if let Some(path) = path {
                 match File::open(path.clone()) {
                     Ok(mut file) => {
                         let mut contents = String::new();
                         file.read_to_string(&mut contents)
                             .expect("Could not read file");
-                        match format_raw(contents.clone()) {
+                        match format_raw(prepare(contents.clone())) {
                             Ok(formatted_contents) => {
                                 let unchanged = formatted_contents == contents;
                                 if check {
@@ -179,7 +190,7 @@ fn main() {
                 //     buffer += "\n";
                 // }
                 let input = String::from_utf8(buffer).expect("input should be valid UTF8");
-                match format_raw(input) {
+                match format_raw(prepare(input)) {
                     Ok(res) => {
                         print!("{}", res);
                     }
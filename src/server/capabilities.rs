@@ -49,7 +49,8 @@ pub(super) fn create_capabilities() -> ServerCapabilities {
             workspace_diagnostics: false,
         },
         completion_provider: CompletionOptions {
-            trigger_characters: vec!["?".to_string(), " ".to_string()],
+            trigger_characters: vec!["?".to_string(), " ".to_string(), "@".to_string()],
+            resolve_provider: true,
         },
         document_formatting_provider: DocumentFormattingOptions {},
         document_on_type_formatting_provider: DocumentOnTypeFormattingOptions {
@@ -0,0 +1,262 @@
+//! Cache for the results of completion queries.
+//!
+//! Entries are keyed by backend, template and rendered query. They expire after
+//! `completion.cache_ttl_ms` and the least recently used entry is evicted once
+//! `completion.cache_size` entries are stored.
+//!
+//! A result that was not truncated by `completion.result_size_limit` contains
+//! every match of its search term, and therefore every match of a longer search
+//! term. Such a result answers the narrowed search term without a query: its
+//! bindings are filtered client-side.
+
+use std::rc::Rc;
+
+use super::ttl_cache::TtlCache;
+use crate::sparql::results::Binding;
+
+/// Stands in for the search term when rendering the [`CompletionQuery::family`]
+/// of a completion query.
+pub(crate) const SEARCH_TERM_PLACEHOLDER: &str = "qls_search_term";
+
+/// A rendered completion query.
+#[derive(Debug, Clone)]
+pub(crate) struct CompletionQuery {
+    pub backend: String,
+    pub template: String,
+    pub query: String,
+    /// The query rendered with [`SEARCH_TERM_PLACEHOLDER`] as search term.
+    /// Queries of the same family only differ in their search term.
+    pub family: Option<String>,
+    pub search_term: Option<String>,
+    /// The search term with its prefix expanded, see `search_term_uncompressed`.
+    pub search_term_uncompressed: Option<String>,
+}
+
+impl CompletionQuery {
+    fn key(&self) -> (String, String, String) {
+        (
+            self.backend.clone(),
+            self.template.clone(),
+            self.query.clone(),
+        )
+    }
+}
+
+struct CacheEntry {
+    bindings: Rc<Vec<Binding>>,
+    family: Option<String>,
+    search_term: Option<String>,
+    truncated: bool,
+}
+
+impl CacheEntry {
+    /// Can the bindings of this entry be filtered to answer `query`?
+    fn narrows_to(
+        &self,
+        (backend, template, _): &(String, String, String),
+        query: &CompletionQuery,
+        search_term: &str,
+    ) -> bool {
+        *backend == query.backend
+            && *template == query.template
+            && !self.truncated
+            && self.family.is_some()
+            && self.family == query.family
+            && self
+                .search_term
+                .as_deref()
+                .is_some_and(|previous| search_term.starts_with(&previous.to_lowercase()))
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct CompletionCache {
+    entries: TtlCache<(String, String, String), CacheEntry>,
+}
+
+impl CompletionCache {
+    /// The cached result of `query`, or the filtered result of a query of the
+    /// same family with a shorter search term that was not truncated.
+    pub(crate) fn get(
+        &mut self,
+        query: &CompletionQuery,
+        now_ms: f64,
+        ttl_ms: u32,
+        capacity: u32,
+    ) -> Option<Rc<Vec<Binding>>> {
+        let ttl_ms = ttl_ms as f64;
+        if let Some(entry) = self.entries.get(&query.key(), now_ms, ttl_ms) {
+            return Some(entry.bindings.clone());
+        }
+        let search_term = query.search_term.as_deref()?.to_lowercase();
+        // NOTE: the longest previous search term has the fewest bindings.
+        let (base_key, base) = self
+            .entries
+            .iter(now_ms, ttl_ms)
+            .filter(|(key, entry)| entry.narrows_to(key, query, &search_term))
+            .max_by_key(|(_, entry)| entry.search_term.as_ref().map_or(0, String::len))?;
+        let base_key = base_key.clone();
+        let bindings: Rc<Vec<Binding>> = Rc::new(
+            base.bindings
+                .iter()
+                .filter(|binding| {
+                    matches(
+                        binding,
+                        &search_term,
+                        query.search_term_uncompressed.as_deref(),
+                    )
+                })
+                .cloned()
+                .collect(),
+        );
+        // NOTE: the narrowed result expires together with the result it was filtered from.
+        let created_ms = self.entries.created_ms(&base_key)?;
+        self.entries.get(&base_key, now_ms, ttl_ms);
+        self.entries.insert(
+            query.key(),
+            CacheEntry {
+                bindings: bindings.clone(),
+                family: query.family.clone(),
+                search_term: query.search_term.clone(),
+                truncated: false,
+            },
+            created_ms,
+            capacity as usize,
+        );
+        Some(bindings)
+    }
+
+    /// Stores the result of `query`.
+    /// `truncated` tells if the result was cut off by the result size limit.
+    pub(crate) fn insert(
+        &mut self,
+        query: &CompletionQuery,
+        bindings: Rc<Vec<Binding>>,
+        truncated: bool,
+        now_ms: f64,
+        capacity: u32,
+    ) {
+        self.entries.insert(
+            query.key(),
+            CacheEntry {
+                bindings,
+                family: query.family.clone(),
+                search_term: query.search_term.clone(),
+                truncated,
+            },
+            now_ms,
+            capacity as usize,
+        );
+    }
+
+    /// Drops all results of `backend`.
+    pub(crate) fn invalidate_backend(&mut self, backend: &str) {
+        self.entries
+            .retain(|(entry_backend, _, _), _| entry_backend != backend);
+    }
+}
+
+/// Does the label, alias or entity of `binding` contain the (lowercase) search term?
+fn matches(binding: &Binding, search_term: &str, search_term_uncompressed: Option<&str>) -> bool {
+    ["qls_label", "qls_alias", "qls_entity"]
+        .into_iter()
+        .filter_map(|variable| binding.get(variable))
+        .any(|rdf_term| {
+            let value = rdf_term.value().to_lowercase();
+            value.contains(search_term)
+                || search_term_uncompressed
+                    .is_some_and(|uncompressed| value.contains(&uncompressed.to_lowercase()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, rc::Rc};
+
+    use super::{CompletionCache, CompletionQuery};
+    use crate::sparql::results::{Binding, RDFTerm};
+
+    fn query(query: &str, search_term: Option<&str>) -> CompletionQuery {
+        CompletionQuery {
+            backend: "wikidata".to_string(),
+            template: "wikidata-subjectCompletion".to_string(),
+            query: query.to_string(),
+            family: Some("family".to_string()),
+            search_term: search_term.map(str::to_string),
+            search_term_uncompressed: None,
+        }
+    }
+
+    fn binding(label: &str) -> Binding {
+        HashMap::from([(
+            "qls_label".to_string(),
+            RDFTerm::Literal {
+                value: label.to_string(),
+                lang: None,
+                datatype: None,
+            },
+        )])
+    }
+
+    fn labels(bindings: &[Binding]) -> Vec<&str> {
+        bindings
+            .iter()
+            .map(|binding| binding["qls_label"].value())
+            .collect()
+    }
+
+    #[test]
+    fn cached_result_expires() {
+        let mut cache = CompletionCache::default();
+        let ber = query("ber", Some("Ber"));
+        cache.insert(&ber, Rc::new(vec![binding("Berlin")]), false, 0.0, 10);
+        assert!(cache.get(&ber, 999.0, 1000, 10).is_some());
+        assert!(cache.get(&ber, 1000.0, 1000, 10).is_none());
+    }
+
+    #[test]
+    fn least_recently_used_result_is_evicted() {
+        let mut cache = CompletionCache::default();
+        let (a, b, c) = (query("a", None), query("b", None), query("c", None));
+        cache.insert(&a, Rc::new(vec![]), true, 0.0, 2);
+        cache.insert(&b, Rc::new(vec![]), true, 0.0, 2);
+        assert!(cache.get(&a, 0.0, 1000, 2).is_some());
+        cache.insert(&c, Rc::new(vec![]), true, 0.0, 2);
+        assert!(cache.get(&a, 0.0, 1000, 2).is_some());
+        assert!(cache.get(&b, 0.0, 1000, 2).is_none());
+        assert!(cache.get(&c, 0.0, 1000, 2).is_some());
+    }
+
+    #[test]
+    fn narrowed_search_term_is_filtered_client_side() {
+        let mut cache = CompletionCache::default();
+        cache.insert(
+            &query("ber", Some("Ber")),
+            Rc::new(vec![binding("Berlin"), binding("Bern"), binding("Bergen")]),
+            false,
+            0.0,
+            10,
+        );
+        let narrowed = cache
+            .get(&query("berl", Some("berl")), 0.0, 1000, 10)
+            .unwrap();
+        assert_eq!(labels(&narrowed), vec!["Berlin"]);
+    }
+
+    #[test]
+    fn truncated_result_is_not_narrowed() {
+        let mut cache = CompletionCache::default();
+        cache.insert(
+            &query("ber", Some("Ber")),
+            Rc::new(vec![binding("Berlin")]),
+            true,
+            0.0,
+            10,
+        );
+        assert!(
+            cache
+                .get(&query("berl", Some("Berl")), 0.0, 1000, 10)
+                .is_none()
+        );
+    }
+}
@@ -8,6 +8,7 @@
 //! - [`Settings`]: Top-level configuration container
 //! - [`FormatSettings`]: Formatter options (alignment, capitalization, spacing)
 //! - [`CompletionSettings`]: Timeout and result limits for completions
+//! - [`DiagnosticsSettings`]: Opt-in diagnostics that query a backend
 //! - [`BackendConfiguration`]: SPARQL endpoint with prefix map and custom queries
 //!
 //! # Configuration Loading
@@ -48,6 +49,7 @@ pub struct BackendsSettings {
 #[serde(rename_all = "camelCase")]
 pub struct BackendConfiguration {
     pub name: String,
+    #[serde(default)]
     pub url: String,
     pub health_check_url: Option<String>,
     pub engine: Option<SparqlEngine>,
@@ -58,9 +60,30 @@ pub struct BackendConfiguration {
     pub default: bool,
     #[serde(default)]
     pub queries: HashMap<CompletionTemplate, String>,
+    /// Local N-Triples or Turtle files. If set, completion and hover are answered
+    /// from an in-memory index of these files instead of the SPARQL endpoint.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
     pub additional_data: Option<LSPAny>,
 }
 
+impl BackendConfiguration {
+    /// Checks that the backend has something to answer queries from:
+    /// a SPARQL endpoint, local files or both.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.url.is_empty() && self.files.is_empty() {
+            return Err(format!("backend \"{}\" needs a url or files", self.name));
+        }
+        Ok(())
+    }
+
+    /// Whether the backend has a SPARQL endpoint.
+    /// A backend built only from local files has none.
+    pub fn has_endpoint(&self) -> bool {
+        !self.url.is_empty()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", try_from = "String")]
 pub(crate) enum CompletionTemplate {
@@ -72,8 +95,59 @@ pub(crate) enum CompletionTemplate {
     ObjectCompletionContextInsensitive,
     ValuesCompletionContextSensitive,
     ValuesCompletionContextInsensitive,
+    GraphCompletion,
+    LanguageTagCompletion,
+    UsageCheck,
+    EntityDetails,
 }
 
+impl CompletionTemplate {
+    /// The query of this template for a backend that does not configure one.
+    pub(crate) fn default_query(&self) -> Option<&'static str> {
+        match self {
+            CompletionTemplate::GraphCompletion => Some(DEFAULT_GRAPH_COMPLETION_QUERY),
+            CompletionTemplate::LanguageTagCompletion => {
+                Some(DEFAULT_LANGUAGE_TAG_COMPLETION_QUERY)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Lists the named graphs of the dataset, the biggest first.
+const DEFAULT_GRAPH_COMPLETION_QUERY: &str = r#"{% include "prefix_declarations" %}
+SELECT ?qls_entity ?qls_count WHERE {
+  {
+    SELECT ?qls_entity (COUNT(*) AS ?qls_count) WHERE {
+      GRAPH ?qls_entity { ?s ?p ?o }
+    }
+    GROUP BY ?qls_entity
+  }
+  {% if search_term_uncompressed %}
+  FILTER (STRSTARTS(STR(?qls_entity), "{{ search_term_uncompressed }}"))
+  {% elif search_term %}
+  FILTER (CONTAINS(LCASE(STR(?qls_entity)), LCASE("{{ search_term | trim_start_matches(pat="<") }}")))
+  {% endif %}
+}
+ORDER BY DESC(?qls_count)
+LIMIT {{ limit }}
+OFFSET {{ offset }}"#;
+
+/// Lists the language tags of the literals of a predicate, the most frequent first.
+const DEFAULT_LANGUAGE_TAG_COMPLETION_QUERY: &str = r#"{% include "prefix_declarations" %}
+SELECT ?qls_entity (COUNT(*) AS ?qls_count) WHERE {
+  [] {{ predicate }} ?qls_literal .
+  BIND (LANG(?qls_literal) AS ?qls_entity)
+  FILTER (?qls_entity != "")
+  {% if search_term %}
+  FILTER (STRSTARTS(LCASE(?qls_entity), LCASE("{{ search_term }}")))
+  {% endif %}
+}
+GROUP BY ?qls_entity
+ORDER BY DESC(?qls_count)
+LIMIT {{ limit }}
+OFFSET {{ offset }}"#;
+
 #[derive(Debug)]
 pub struct UnknownTemplateError(String);
 
@@ -108,6 +182,10 @@ impl TryFrom<String> for CompletionTemplate {
             "valuesCompletionContextInsensitive" => {
                 Ok(CompletionTemplate::ValuesCompletionContextInsensitive)
             }
+            "graphCompletion" => Ok(CompletionTemplate::GraphCompletion),
+            "languageTagCompletion" => Ok(CompletionTemplate::LanguageTagCompletion),
+            "usageCheck" => Ok(CompletionTemplate::UsageCheck),
+            "entityDetails" => Ok(CompletionTemplate::EntityDetails),
             _ => Err(UnknownTemplateError(s.to_string())),
         }
     }
@@ -136,6 +214,10 @@ impl fmt::Display for CompletionTemplate {
             CompletionTemplate::ValuesCompletionContextInsensitive => {
                 write!(f, "valuesCompletionContextInsensitive")
             }
+            CompletionTemplate::GraphCompletion => write!(f, "graphCompletion"),
+            CompletionTemplate::LanguageTagCompletion => write!(f, "languageTagCompletion"),
+            CompletionTemplate::UsageCheck => write!(f, "usageCheck"),
+            CompletionTemplate::EntityDetails => write!(f, "entityDetails"),
         }
     }
 }
@@ -160,6 +242,13 @@ pub struct CompletionSettings {
     /// When completing a subject that matches the previous triple's subject,
     /// transform the completion to use semicolon notation instead of starting a new triple.
     pub same_subject_semicolon: bool,
+    /// Time (in ms) a completion query result is reused. 0 disables the cache.
+    pub cache_ttl_ms: u32,
+    /// Maximum number of cached completion query results. 0 disables the cache.
+    pub cache_size: u32,
+    /// User-defined snippets, offered next to the built-in completions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub snippets: Vec<Snippet>,
 }
 
 impl Default for CompletionSettings {
@@ -171,6 +260,142 @@ impl Default for CompletionSettings {
             object_completion_suffix: true,
             variable_completion_limit: None,
             same_subject_semicolon: true,
+            cache_ttl_ms: 60000,
+            cache_size: 200,
+            snippets: Vec::new(),
+        }
+    }
+}
+
+/// A named completion snippet from the user configuration.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    /// The label of the completion item.
+    pub name: String,
+    /// The inserted text, with LSP snippet placeholders like `${1:?item}`.
+    /// Leading tabs are replaced by the indentation of the format settings.
+    pub body: String,
+    /// Markdown shown next to the completion item.
+    pub documentation: Option<String>,
+    /// The locations the snippet is offered at. Empty means everywhere but at
+    /// a datatype, a language tag or a SERVICE URL.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<SnippetLocation>,
+    /// The backends the snippet is offered for. Empty means every backend.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backends: Vec<String>,
+}
+
+/// Where a [`Snippet`] is offered, named after the completion locations.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SnippetLocation {
+    /// At the beginning of the input.
+    Start,
+    /// Inside a group graph pattern, where a triple or a `FILTER` may start.
+    Subject,
+    Predicate,
+    Object,
+    /// After the `WHERE` clause of a query.
+    SolutionModifier,
+    SelectBinding,
+    Graph,
+    BlankNodeProperty,
+    BlankNodeObject,
+    ServiceUrl,
+    OrderCondition,
+    GroupCondition,
+    InlineData,
+    BuiltInCall,
+    PrefixDeclaration,
+    Datatype,
+    LanguageTag,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsSettings {
+    /// Check constant predicates and classes against the backend and report
+    /// the ones that never occur in the dataset.
+    /// Requires a `usageCheck` query for the backend.
+    pub usage_check: bool,
+    /// Minimal time between two usage checks. Terms that are not cached yet
+    /// are only looked up once this interval has passed.
+    pub usage_check_interval_ms: u32,
+    /// Report query shapes that are known to be slow.
+    pub performance_hints: bool,
+    /// The performance hints to report, per engine of the default backend.
+    /// Engines without an entry use [`PerformanceRule::defaults_for`].
+    pub performance_rules: HashMap<SparqlEngine, Vec<PerformanceRule>>,
+}
+
+impl Default for DiagnosticsSettings {
+    fn default() -> Self {
+        Self {
+            usage_check: false,
+            usage_check_interval_ms: 2000,
+            performance_hints: false,
+            performance_rules: HashMap::new(),
+        }
+    }
+}
+
+impl DiagnosticsSettings {
+    /// The performance hints to report for a backend running `engine`.
+    pub fn performance_rules_for(&self, engine: Option<&SparqlEngine>) -> Vec<PerformanceRule> {
+        engine
+            .and_then(|engine| self.performance_rules.get(engine))
+            .cloned()
+            .unwrap_or_else(|| PerformanceRule::defaults_for(engine))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum PerformanceRule {
+    /// `REGEX` with a leading wildcard or a case-insensitive flag.
+    RegexScan,
+    /// `ORDER BY` without `LIMIT` in a sub-select.
+    SubSelectOrderWithoutLimit,
+    /// A `*` property path between two otherwise unbound variables.
+    UnboundStarPath,
+    /// `OPTIONAL` that only contains a `FILTER`.
+    FilterOnlyOptional,
+    /// `SELECT DISTINCT *`.
+    DistinctStar,
+    /// `FILTER(?x = <iri>)` instead of the constant in the pattern.
+    ConstantFilter,
+}
+
+impl PerformanceRule {
+    pub const ALL: [PerformanceRule; 6] = [
+        PerformanceRule::RegexScan,
+        PerformanceRule::SubSelectOrderWithoutLimit,
+        PerformanceRule::UnboundStarPath,
+        PerformanceRule::FilterOnlyOptional,
+        PerformanceRule::DistinctStar,
+        PerformanceRule::ConstantFilter,
+    ];
+
+    /// The rules that are reported for `engine` unless configured otherwise.
+    pub fn defaults_for(engine: Option<&SparqlEngine>) -> Vec<PerformanceRule> {
+        match engine {
+            // NOTE: these optimizers rewrite `FILTER(?x = <iri>)` into the
+            // pattern themselves.
+            Some(
+                SparqlEngine::Virtuoso
+                | SparqlEngine::GraphDB
+                | SparqlEngine::Blazegraph
+                | SparqlEngine::Jena,
+            ) => PerformanceRule::ALL
+                .into_iter()
+                .filter(|rule| *rule != PerformanceRule::ConstantFilter)
+                .collect(),
+            Some(SparqlEngine::QLever | SparqlEngine::MillenniumDB) | None => {
+                PerformanceRule::ALL.to_vec()
+            }
         }
     }
 }
@@ -215,11 +440,24 @@ impl Default for FormatSettings {
     }
 }
 
+impl FormatSettings {
+    /// One level of indentation: `tab_size` spaces, or a tab.
+    pub fn indent_unit(&self) -> String {
+        match self.insert_spaces.unwrap_or(true) {
+            true => " ".repeat(self.tab_size.unwrap_or(2) as usize),
+            false => "\t".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PrefixesSettings {
     pub add_missing: Option<bool>,
     pub remove_unused: Option<bool>,
+    /// Prefixes that "Organize prefixes" puts first, in this order.
+    /// All other prefixes follow alphabetically.
+    pub order: Option<Vec<String>>,
 }
 
 impl Default for PrefixesSettings {
@@ -227,6 +465,7 @@ impl Default for PrefixesSettings {
         Self {
             add_missing: Some(true),
             remove_unused: Some(false),
+            order: None,
         }
     }
 }
@@ -360,6 +599,9 @@ pub struct Settings {
     /// Completion Settings
     #[serde(default)]
     pub completion: CompletionSettings,
+    /// Diagnostic Settings
+    #[serde(default)]
+    pub diagnostics: DiagnosticsSettings,
     /// Backend configurations
     pub backends: Option<BackendsSettings>,
     /// Automatically add and remove prefix declarations
@@ -376,6 +618,7 @@ impl Default for Settings {
         Self {
             format: FormatSettings::default(),
             completion: CompletionSettings::default(),
+            diagnostics: DiagnosticsSettings::default(),
             backends: None,
             prefixes: Some(PrefixesSettings::default()),
             replacements: Some(Replacements::default()),
@@ -396,7 +639,7 @@ impl Settings {
     pub fn new() -> Self {
         #[cfg(not(target_arch = "wasm32"))]
         match load_user_configuration() {
-            Ok(settings) => {
+            Ok(mut settings) => {
                 tracing::info!("Loaded user configuration!!");
                 // NOTE: an invalid pattern does not invalidate the whole
                 // configuration file, it is skipped when the replacements are
@@ -406,6 +649,19 @@ impl Settings {
                 {
                     tracing::warn!("Ignoring a replacement from the user configuration: {error}");
                 }
+                if let Some(backends) = settings.backends.as_mut() {
+                    backends
+                        .backends
+                        .retain(|_, backend| match backend.validate() {
+                            Ok(()) => true,
+                            Err(error) => {
+                                tracing::warn!(
+                                    "Ignoring a backend from the user configuration: {error}"
+                                );
+                                false
+                            }
+                        });
+                }
                 settings
             }
             Err(error) => {
@@ -454,6 +710,8 @@ mod tests {
               objectCompletionContextInsensitive: SELECT ?qls_entity WHERE { [] [] ?qls_entity }
               valuesCompletionContextSensitive: SELECT ?qls_entity WHERE { ?qls_entity ?p ?o }
               valuesCompletionContextInsensitive: SELECT ?qls_entity WHERE { ?qls_entity ?p ?o }
+              usageCheck: SELECT ?qls_entity WHERE { VALUES ?qls_entity { } [] ?qls_entity [] }
+              entityDetails: SELECT ?qls_description WHERE { {{ entity }} rdfs:comment ?qls_description }
         "#;
 
         let config: BackendConfiguration = parse_yaml(yaml);
@@ -461,7 +719,7 @@ mod tests {
         assert_eq!(config.name, "TestBackend");
         assert_eq!(config.url, "https://example.com/sparql");
         assert!(!config.default);
-        assert_eq!(config.queries.len(), 7);
+        assert_eq!(config.queries.len(), 9);
         assert!(
             config
                 .queries
@@ -497,6 +755,12 @@ mod tests {
                 .queries
                 .contains_key(&CompletionTemplate::ValuesCompletionContextInsensitive)
         );
+        assert!(config.queries.contains_key(&CompletionTemplate::UsageCheck));
+        assert!(
+            config
+                .queries
+                .contains_key(&CompletionTemplate::EntityDetails)
+        );
     }
 
     #[test]
@@ -550,6 +814,45 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_backend_configuration_with_local_files() {
+        let yaml = r#"
+            name: Ontology
+            files:
+              - ontology.ttl
+              - sample-data.nt
+        "#;
+
+        let backend: BackendConfiguration = parse_yaml(yaml);
+        assert_eq!(backend.url, "");
+        assert_eq!(backend.files, vec!["ontology.ttl", "sample-data.nt"]);
+    }
+
+    #[test]
+    fn test_completion_settings_with_snippets() {
+        let yaml = r##"
+            snippets:
+              - name: label service
+                body: "SERVICE wikibase:label {\n\tbd:serviceParam wikibase:language \"${1:en}\" .\n}"
+                documentation: Fetch labels with the Wikidata label service.
+                locations: [subject]
+                backends: [wikidata]
+              - name: todo
+                body: "# TODO: $0"
+        "##;
+
+        let settings: CompletionSettings = parse_yaml(yaml);
+        assert_eq!(settings.snippets.len(), 2);
+        assert_eq!(
+            settings.snippets[0].locations,
+            vec![SnippetLocation::Subject]
+        );
+        assert_eq!(settings.snippets[0].backends, vec!["wikidata"]);
+        assert!(settings.snippets[0].body.contains("\n\tbd:serviceParam"));
+        assert!(settings.snippets[1].locations.is_empty());
+        assert_eq!(settings.snippets[1].documentation, None);
+    }
+
     #[test]
     fn test_backend_configuration_with_multiline_queries() {
         let yaml = r#"
@@ -678,6 +981,27 @@ mod tests {
         assert_eq!(wikidata.queries.len(), 2);
     }
 
+    #[test]
+    fn test_backend_needs_url_or_files() {
+        let backend: BackendConfiguration = parse_yaml(
+            r#"
+            name: ontology
+            files:
+              - ./ontology.ttl
+        "#,
+        );
+        assert_eq!(backend.validate(), Ok(()));
+
+        let backend = BackendConfiguration {
+            files: Vec::new(),
+            ..backend
+        };
+        assert_eq!(
+            backend.validate(),
+            Err("backend \"ontology\" needs a url or files".to_string())
+        );
+    }
+
     // NOTE: object variable replacements
 
     #[test]
@@ -957,4 +1281,30 @@ mod tests {
         // it does NOT fall back to `Replacements::default()`.
         assert_eq!(settings.replacements, None);
     }
+
+    #[test]
+    fn test_performance_rules_deserialize_per_engine() {
+        let yaml = r#"
+            performanceHints: true
+            performanceRules:
+              QLever: [regexScan, distinctStar]
+        "#;
+
+        let settings: DiagnosticsSettings = parse_yaml(yaml);
+
+        assert!(settings.performance_hints);
+        assert_eq!(
+            settings.performance_rules_for(Some(&SparqlEngine::QLever)),
+            vec![PerformanceRule::RegexScan, PerformanceRule::DistinctStar]
+        );
+        // NOTE: engines without an entry fall back to their defaults
+        assert_eq!(
+            settings.performance_rules_for(Some(&SparqlEngine::Virtuoso)),
+            PerformanceRule::defaults_for(Some(&SparqlEngine::Virtuoso))
+        );
+        assert_eq!(
+            settings.performance_rules_for(None),
+            PerformanceRule::ALL.to_vec()
+        );
+    }
 }
@@ -0,0 +1,512 @@
+//! Catalogs of the extension functions and magic predicates of SPARQL engines.
+//!
+//! Most engines extend SPARQL with functions and predicates of their own
+//! namespace, e.g. `geof:distance` or Virtuoso's `bif:contains`. The catalog of
+//! the engine of a backend feeds completion, hover and the diagnostic for
+//! extensions of other engines.
+
+use crate::server::lsp::SparqlEngine;
+
+/// The namespace of a group of extensions, with its conventional prefix.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Namespace {
+    pub(crate) prefix: &'static str,
+    pub(crate) uri: &'static str,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ExtensionKind {
+    /// Called like a built-in function, e.g. in a `FILTER` or `BIND`.
+    Function,
+    /// Used as the predicate of a triple pattern, the engine computes its matches.
+    MagicPredicate,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Extension {
+    pub(crate) namespace: &'static Namespace,
+    pub(crate) name: &'static str,
+    pub(crate) kind: ExtensionKind,
+    /// The arguments of a function, the subject and object of a magic predicate.
+    pub(crate) signature: &'static str,
+    pub(crate) documentation: &'static str,
+}
+
+impl Extension {
+    pub(crate) fn iri(&self) -> String {
+        format!("{}{}", self.namespace.uri, self.name)
+    }
+
+    pub(crate) fn curie(&self) -> String {
+        format!("{}:{}", self.namespace.prefix, self.name)
+    }
+
+    /// The hover documentation of this extension.
+    pub(crate) fn markdown(&self, engine: &SparqlEngine) -> String {
+        let usage = match self.kind {
+            ExtensionKind::Function => format!("{}{}", self.curie(), self.signature),
+            ExtensionKind::MagicPredicate => self.signature.replace("$", &self.curie()),
+        };
+        format!(
+            "### **{}**\n{} extension of {:?}\n\n```sparql\n{}\n```\n\n{}",
+            self.curie(),
+            match self.kind {
+                ExtensionKind::Function => "Function",
+                ExtensionKind::MagicPredicate => "Magic predicate",
+            },
+            engine,
+            usage,
+            self.documentation
+        )
+    }
+}
+
+const QL: Namespace = Namespace {
+    prefix: "ql",
+    uri: "http://qlever.cs.uni-freiburg.de/builtin-functions/",
+};
+const GEOF: Namespace = Namespace {
+    prefix: "geof",
+    uri: "http://www.opengis.net/def/function/geosparql/",
+};
+const BIF: Namespace = Namespace {
+    prefix: "bif",
+    uri: "http://www.openlinksw.com/schemas/bif#",
+};
+const APF: Namespace = Namespace {
+    prefix: "apf",
+    uri: "http://jena.apache.org/ARQ/property#",
+};
+const AFN: Namespace = Namespace {
+    prefix: "afn",
+    uri: "http://jena.apache.org/ARQ/function#",
+};
+const TEXT: Namespace = Namespace {
+    prefix: "text",
+    uri: "http://jena.apache.org/text#",
+};
+const LUC: Namespace = Namespace {
+    prefix: "luc",
+    uri: "http://www.ontotext.com/owlim/lucene#",
+};
+const BDS: Namespace = Namespace {
+    prefix: "bds",
+    uri: "http://www.bigdata.com/rdf/search#",
+};
+
+/// Every namespace that holds extensions of some engine.
+const NAMESPACES: &[&Namespace] = &[&QL, &GEOF, &BIF, &APF, &AFN, &TEXT, &LUC, &BDS];
+
+const fn function(
+    namespace: &'static Namespace,
+    name: &'static str,
+    signature: &'static str,
+    documentation: &'static str,
+) -> Extension {
+    Extension {
+        namespace,
+        name,
+        kind: ExtensionKind::Function,
+        signature,
+        documentation,
+    }
+}
+
+/// A magic predicate, `$` in the signature stands for the predicate itself.
+const fn magic_predicate(
+    namespace: &'static Namespace,
+    name: &'static str,
+    signature: &'static str,
+    documentation: &'static str,
+) -> Extension {
+    Extension {
+        namespace,
+        name,
+        kind: ExtensionKind::MagicPredicate,
+        signature,
+        documentation,
+    }
+}
+
+const GEOSPARQL: &[Extension] = &[
+    function(
+        &GEOF,
+        "distance",
+        "(geometry1, geometry2, unit)",
+        "Returns the shortest distance between the two geometries in the given unit, e.g. `uom:metre`.",
+    ),
+    function(
+        &GEOF,
+        "sfIntersects",
+        "(geometry1, geometry2)",
+        "Returns true if the two geometries share at least one point.",
+    ),
+    function(
+        &GEOF,
+        "sfContains",
+        "(geometry1, geometry2)",
+        "Returns true if the first geometry contains the second one.",
+    ),
+    function(
+        &GEOF,
+        "sfWithin",
+        "(geometry1, geometry2)",
+        "Returns true if the first geometry lies within the second one.",
+    ),
+    function(
+        &GEOF,
+        "sfTouches",
+        "(geometry1, geometry2)",
+        "Returns true if the boundaries of the two geometries meet but their interiors do not.",
+    ),
+    function(
+        &GEOF,
+        "sfOverlaps",
+        "(geometry1, geometry2)",
+        "Returns true if the two geometries overlap without one containing the other.",
+    ),
+    function(
+        &GEOF,
+        "buffer",
+        "(geometry, radius, unit)",
+        "Returns the geometry of all points within the radius around the geometry.",
+    ),
+    function(
+        &GEOF,
+        "envelope",
+        "(geometry)",
+        "Returns the minimum bounding box of the geometry.",
+    ),
+];
+
+const QLEVER: &[Extension] = &[
+    magic_predicate(
+        &QL,
+        "contains-word",
+        "?text $ \"prefix* words\"",
+        "Text search: binds `?text` to the text records that contain all the words. A word ending in `*` matches as prefix.",
+    ),
+    magic_predicate(
+        &QL,
+        "contains-entity",
+        "?text $ ?entity",
+        "Text search: binds `?text` to the text records that mention `?entity`.",
+    ),
+    magic_predicate(
+        &QL,
+        "has-predicate",
+        "?subject $ ?predicate",
+        "Binds `?predicate` to the predicates that `?subject` has, computed from precomputed patterns.",
+    ),
+    function(
+        &GEOF,
+        "metricDistance",
+        "(geometry1, geometry2)",
+        "Returns the distance between the two geometries in meters.",
+    ),
+    function(
+        &GEOF,
+        "latitude",
+        "(point)",
+        "Returns the latitude of a WKT point.",
+    ),
+    function(
+        &GEOF,
+        "longitude",
+        "(point)",
+        "Returns the longitude of a WKT point.",
+    ),
+    function(
+        &GEOF,
+        "centroid",
+        "(geometry)",
+        "Returns the centroid of the geometry as WKT point.",
+    ),
+];
+
+const VIRTUOSO: &[Extension] = &[
+    magic_predicate(
+        &BIF,
+        "contains",
+        "?literal $ \"'word' AND 'prefix*'\"",
+        "Free text search over literals indexed by the Virtuoso text index.",
+    ),
+    function(
+        &BIF,
+        "st_point",
+        "(x, y)",
+        "Returns a point geometry from its longitude and latitude.",
+    ),
+    function(
+        &BIF,
+        "st_distance",
+        "(geometry1, geometry2)",
+        "Returns the distance between the two geometries in kilometers.",
+    ),
+    function(
+        &BIF,
+        "st_intersects",
+        "(geometry1, geometry2 [, precision])",
+        "Returns true if the two geometries intersect, within the optional precision in kilometers.",
+    ),
+    function(
+        &BIF,
+        "st_within",
+        "(geometry1, geometry2 [, precision])",
+        "Returns true if the first geometry lies within the second one.",
+    ),
+    function(&BIF, "st_x", "(point)", "Returns the longitude of a point."),
+    function(&BIF, "st_y", "(point)", "Returns the latitude of a point."),
+    function(
+        &BIF,
+        "datediff",
+        "(unit, date1, date2)",
+        "Returns the difference between the two dates in the given unit, e.g. `'day'`.",
+    ),
+    function(
+        &BIF,
+        "dateadd",
+        "(unit, amount, date)",
+        "Returns the date shifted by the amount of the given unit.",
+    ),
+];
+
+const JENA: &[Extension] = &[
+    magic_predicate(
+        &TEXT,
+        "query",
+        "?subject $ (property \"query\" [limit])",
+        "Full text search with the Lucene index of Jena text: binds `?subject` to the resources whose property matches the query.",
+    ),
+    magic_predicate(
+        &APF,
+        "strSplit",
+        "?part $ (string separator)",
+        "Binds `?part` to each part of the string split by the separator, a regular expression.",
+    ),
+    magic_predicate(
+        &APF,
+        "splitIRI",
+        "iri $ (?namespace ?localname)",
+        "Splits an IRI into namespace and local name.",
+    ),
+    magic_predicate(
+        &APF,
+        "concat",
+        "?string $ (string1 string2 ...)",
+        "Binds `?string` to the concatenation of the arguments.",
+    ),
+    function(
+        &AFN,
+        "localname",
+        "(iri)",
+        "Returns the local name of an IRI, the part after the last `/` or `#`.",
+    ),
+    function(
+        &AFN,
+        "namespace",
+        "(iri)",
+        "Returns the namespace of an IRI, the part up to the last `/` or `#`.",
+    ),
+    function(
+        &AFN,
+        "strjoin",
+        "(separator, string1, string2, ...)",
+        "Returns the strings joined with the separator.",
+    ),
+    function(
+        &AFN,
+        "sqrt",
+        "(number)",
+        "Returns the square root of a number.",
+    ),
+    function(&AFN, "pi", "()", "Returns the value of pi."),
+    function(
+        &AFN,
+        "max",
+        "(number1, number2)",
+        "Returns the larger of two numbers.",
+    ),
+    function(
+        &AFN,
+        "min",
+        "(number1, number2)",
+        "Returns the smaller of two numbers.",
+    ),
+];
+
+const GRAPHDB: &[Extension] = &[
+    magic_predicate(
+        &LUC,
+        "score",
+        "?node $ ?score",
+        "Binds `?score` to the relevance of `?node` in the preceding Lucene full text search.",
+    ),
+    magic_predicate(
+        &LUC,
+        "createIndex",
+        "$ luc:myIndex",
+        "Creates the Lucene index with the given name, configured with the other `luc:` predicates.",
+    ),
+    magic_predicate(
+        &LUC,
+        "updateIndex",
+        "$ luc:myIndex",
+        "Indexes the resources that were added since the Lucene index was created.",
+    ),
+];
+
+const BLAZEGRAPH: &[Extension] = &[
+    magic_predicate(
+        &BDS,
+        "search",
+        "?literal $ \"words\"",
+        "Full text search: binds `?literal` to the literals that match the words.",
+    ),
+    magic_predicate(
+        &BDS,
+        "relevance",
+        "?literal $ ?relevance",
+        "Binds `?relevance` to the score of `?literal` in the full text search.",
+    ),
+    magic_predicate(
+        &BDS,
+        "rank",
+        "?literal $ ?rank",
+        "Binds `?rank` to the position of `?literal` in the full text search results.",
+    ),
+    magic_predicate(
+        &BDS,
+        "matchAllTerms",
+        "?literal $ true",
+        "Only matches literals that contain all the words of the full text search.",
+    ),
+    magic_predicate(
+        &BDS,
+        "minRelevance",
+        "?literal $ 0.5",
+        "Only matches literals with at least the given relevance.",
+    ),
+];
+
+/// The extensions that `engine` supports.
+pub(crate) fn extensions(engine: &SparqlEngine) -> impl Iterator<Item = &'static Extension> {
+    let catalogs: &[&'static [Extension]] = match engine {
+        SparqlEngine::QLever => &[QLEVER, GEOSPARQL],
+        SparqlEngine::GraphDB => &[GRAPHDB, GEOSPARQL],
+        SparqlEngine::Virtuoso => &[VIRTUOSO],
+        SparqlEngine::Jena => &[JENA, GEOSPARQL],
+        SparqlEngine::Blazegraph => &[BLAZEGRAPH],
+        SparqlEngine::MillenniumDB => &[],
+    };
+    catalogs.iter().flat_map(|catalog| catalog.iter())
+}
+
+/// The extension of `engine` with the full IRI `iri`.
+pub(crate) fn find_extension(engine: &SparqlEngine, iri: &str) -> Option<&'static Extension> {
+    extensions(engine).find(|extension| extension.iri() == iri)
+}
+
+/// The extension namespace of `iri` if it belongs to another engine than
+/// `engine`, with that engine.
+///
+/// Only namespaces of a single engine are foreign: a namespace of several
+/// engines, like GeoSPARQL, is a standard that the catalog of `engine` may just
+/// lack. Engines without a catalog are never checked.
+pub(crate) fn foreign_namespace(
+    engine: &SparqlEngine,
+    iri: &str,
+) -> Option<(&'static Namespace, Vec<SparqlEngine>)> {
+    let namespace = NAMESPACES
+        .iter()
+        .find(|namespace| iri.starts_with(namespace.uri))?;
+    let supported_by = |engine: &SparqlEngine| {
+        extensions(engine).any(|extension| extension.namespace == *namespace)
+    };
+    if extensions(engine).next().is_none() || supported_by(engine) {
+        return None;
+    }
+    let engines: Vec<SparqlEngine> = [
+        SparqlEngine::QLever,
+        SparqlEngine::GraphDB,
+        SparqlEngine::Virtuoso,
+        SparqlEngine::MillenniumDB,
+        SparqlEngine::Blazegraph,
+        SparqlEngine::Jena,
+    ]
+    .into_iter()
+    .filter(supported_by)
+    .collect();
+    (engines.len() == 1).then_some((namespace, engines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExtensionKind, find_extension, foreign_namespace};
+    use crate::server::lsp::SparqlEngine;
+
+    #[test]
+    fn find_extension_of_engine() {
+        let distance = find_extension(
+            &SparqlEngine::QLever,
+            "http://www.opengis.net/def/function/geosparql/distance",
+        )
+        .unwrap();
+        assert_eq!(distance.curie(), "geof:distance");
+        assert_eq!(distance.kind, ExtensionKind::Function);
+        assert!(
+            find_extension(
+                &SparqlEngine::Virtuoso,
+                "http://www.opengis.net/def/function/geosparql/distance"
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn foreign_namespace_names_supporting_engines() {
+        let (namespace, engines) = foreign_namespace(
+            &SparqlEngine::QLever,
+            "http://www.openlinksw.com/schemas/bif#contains",
+        )
+        .unwrap();
+        assert_eq!(namespace.prefix, "bif");
+        assert_eq!(engines, vec![SparqlEngine::Virtuoso]);
+        assert_eq!(
+            foreign_namespace(
+                &SparqlEngine::Jena,
+                "http://www.opengis.net/def/function/geosparql/sfWithin"
+            ),
+            None
+        );
+        assert_eq!(
+            foreign_namespace(&SparqlEngine::QLever, "http://example.org/f"),
+            None
+        );
+    }
+
+    #[test]
+    fn foreign_namespace_only_of_a_single_engine() {
+        assert_eq!(
+            foreign_namespace(
+                &SparqlEngine::Virtuoso,
+                "http://www.opengis.net/def/function/geosparql/distance"
+            ),
+            None
+        );
+        assert!(
+            foreign_namespace(
+                &SparqlEngine::Virtuoso,
+                "http://qlever.cs.uni-freiburg.de/builtin-functions/contains-word"
+            )
+            .is_some()
+        );
+        assert_eq!(
+            foreign_namespace(
+                &SparqlEngine::MillenniumDB,
+                "http://qlever.cs.uni-freiburg.de/builtin-functions/contains-word"
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,560 @@
+//! In-memory index of local N-Triples and Turtle files.
+//!
+//! A backend with `files` is answered from this index instead of a SPARQL
+//! endpoint, e.g. for an unpublished ontology or on an air-gapped machine.
+//! The index keeps what completion and hover need: how often each IRI occurs,
+//! the predicates of each subject, the objects of each predicate, labels,
+//! comments, types, and the domains and ranges of properties.
+
+use std::collections::{HashMap, HashSet};
+
+use rio_api::{
+    model::{Literal, Subject, Term},
+    parser::TriplesParser,
+};
+use rio_turtle::{TurtleError, TurtleParser};
+
+use crate::sparql::results::RDFTerm;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_PROPERTY: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+const RDFS_COMMENT: &str = "http://www.w3.org/2000/01/rdf-schema#comment";
+const RDFS_DOMAIN: &str = "http://www.w3.org/2000/01/rdf-schema#domain";
+const RDFS_RANGE: &str = "http://www.w3.org/2000/01/rdf-schema#range";
+const RDFS_CLASS: &str = "http://www.w3.org/2000/01/rdf-schema#Class";
+const OWL_CLASS: &str = "http://www.w3.org/2002/07/owl#Class";
+const OWL_PROPERTIES: [&str; 4] = [
+    "http://www.w3.org/2002/07/owl#ObjectProperty",
+    "http://www.w3.org/2002/07/owl#DatatypeProperty",
+    "http://www.w3.org/2002/07/owl#AnnotationProperty",
+    "http://www.w3.org/2002/07/owl#TransitiveProperty",
+];
+const SKOS_PREF_LABEL: &str = "http://www.w3.org/2004/02/skos/core#prefLabel";
+const SKOS_ALT_LABEL: &str = "http://www.w3.org/2004/02/skos/core#altLabel";
+const SKOS_DEFINITION: &str = "http://www.w3.org/2004/02/skos/core#definition";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+#[derive(Debug, Default)]
+pub(crate) struct LocalIndex {
+    /// How often each IRI occurs, in any position.
+    occurrences: HashMap<String, usize>,
+    subjects: HashSet<String>,
+    /// The predicates of each subject.
+    predicates: HashMap<String, HashSet<String>>,
+    /// Predicates used in a triple or declared as property.
+    properties: HashSet<String>,
+    /// The objects of each predicate, keyed by their N-Triples form.
+    objects: HashMap<String, HashMap<String, (RDFTerm, usize)>>,
+    types: HashMap<String, Vec<String>>,
+    instances: HashMap<String, Vec<String>>,
+    classes: HashSet<String>,
+    /// Labels of each IRI, with their language tag.
+    labels: HashMap<String, Vec<(String, Option<String>)>>,
+    aliases: HashMap<String, String>,
+    comments: HashMap<String, String>,
+    domains: HashMap<String, Vec<String>>,
+    ranges: HashMap<String, Vec<String>>,
+}
+
+impl LocalIndex {
+    /// Parses the N-Triples (`.nt`) and Turtle (`.ttl`) files at `paths`.
+    pub(crate) fn load(paths: &[String]) -> Result<Self, String> {
+        let mut index = LocalIndex::default();
+        for path in paths {
+            let text = read_file(path)?;
+            match path.rsplit_once('.').map(|(_, extension)| extension) {
+                Some("nt") => index.add_ntriples(&text),
+                Some("ttl" | "turtle") => index.add_turtle(&text),
+                _ => Err("only N-Triples (.nt) and Turtle (.ttl) files are supported".to_string()),
+            }
+            .map_err(|err| format!("Could not load \"{path}\": {err}"))?;
+        }
+        Ok(index)
+    }
+
+    pub(crate) fn add_ntriples(&mut self, text: &str) -> Result<(), String> {
+        let (triples, errors) = ntriples_parser::parse(text.as_bytes())
+            .map_err(|_err| "invalid N-Triples".to_string())?;
+        if let Some(error) = errors.first() {
+            return Err(format!("{} at byte {}", error.message, error.span.start));
+        }
+        for triple in triples {
+            let subject = ntriples_term(triple.0);
+            let predicate = ntriples_term(triple.1);
+            let object = ntriples_term(triple.2);
+            self.add(subject, predicate.value().to_string(), object);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_turtle(&mut self, text: &str) -> Result<(), String> {
+        TurtleParser::new(text.as_bytes(), None)
+            .parse_all(&mut |triple| {
+                let subject = match triple.subject {
+                    Subject::NamedNode(node) => uri(node.iri),
+                    Subject::BlankNode(node) => RDFTerm::Bnode {
+                        value: node.id.to_string(),
+                    },
+                    // NOTE: RDF-star triples as subject are not indexed.
+                    Subject::Triple(_) => return Ok(()),
+                };
+                let object = match triple.object {
+                    Term::NamedNode(node) => uri(node.iri),
+                    Term::BlankNode(node) => RDFTerm::Bnode {
+                        value: node.id.to_string(),
+                    },
+                    Term::Literal(literal) => turtle_literal(literal),
+                    Term::Triple(_) => return Ok(()),
+                };
+                self.add(subject, triple.predicate.iri.to_string(), object);
+                Ok(()) as Result<(), TurtleError>
+            })
+            .map_err(|err| err.to_string())
+    }
+
+    fn add(&mut self, subject: RDFTerm, predicate: String, object: RDFTerm) {
+        *self.occurrences.entry(predicate.clone()).or_default() += 1;
+        self.properties.insert(predicate.clone());
+        if let RDFTerm::Uri { value, .. } = &object {
+            *self.occurrences.entry(value.clone()).or_default() += 1;
+        }
+        let RDFTerm::Uri { value: subject, .. } = subject else {
+            self.add_object(&predicate, object);
+            return;
+        };
+        *self.occurrences.entry(subject.clone()).or_default() += 1;
+        self.subjects.insert(subject.clone());
+        self.predicates
+            .entry(subject.clone())
+            .or_default()
+            .insert(predicate.clone());
+        match (predicate.as_str(), &object) {
+            (RDF_TYPE, RDFTerm::Uri { value: class, .. }) => {
+                if class == RDF_PROPERTY || OWL_PROPERTIES.contains(&class.as_str()) {
+                    self.properties.insert(subject.clone());
+                }
+                if class == RDFS_CLASS || class == OWL_CLASS {
+                    self.classes.insert(subject.clone());
+                }
+                self.classes.insert(class.clone());
+                self.types
+                    .entry(subject.clone())
+                    .or_default()
+                    .push(class.clone());
+                self.instances
+                    .entry(class.clone())
+                    .or_default()
+                    .push(subject.clone());
+            }
+            (RDFS_LABEL | SKOS_PREF_LABEL, RDFTerm::Literal { value, lang, .. }) => {
+                self.labels
+                    .entry(subject.clone())
+                    .or_default()
+                    .push((value.clone(), lang.clone()));
+            }
+            (SKOS_ALT_LABEL, RDFTerm::Literal { value, .. }) => {
+                self.aliases.entry(subject.clone()).or_insert(value.clone());
+            }
+            (RDFS_COMMENT | SKOS_DEFINITION, RDFTerm::Literal { value, .. }) => {
+                self.comments
+                    .entry(subject.clone())
+                    .or_insert(value.clone());
+            }
+            (RDFS_DOMAIN, RDFTerm::Uri { value: class, .. }) => {
+                self.properties.insert(subject.clone());
+                self.domains
+                    .entry(subject.clone())
+                    .or_default()
+                    .push(class.clone());
+            }
+            (RDFS_RANGE, RDFTerm::Uri { value: class, .. }) => {
+                self.properties.insert(subject.clone());
+                self.ranges
+                    .entry(subject.clone())
+                    .or_default()
+                    .push(class.clone());
+            }
+            _ => {}
+        }
+        self.add_object(&predicate, object);
+    }
+
+    fn add_object(&mut self, predicate: &str, object: RDFTerm) {
+        self.objects
+            .entry(predicate.to_string())
+            .or_default()
+            .entry(object.to_string())
+            .or_insert((object, 0))
+            .1 += 1;
+    }
+
+    /// How often `iri` occurs in the indexed triples.
+    pub(crate) fn count(&self, iri: &str) -> usize {
+        self.occurrences.get(iri).copied().unwrap_or_default()
+    }
+
+    /// The label of `iri`, English or without language tag if there is one.
+    pub(crate) fn label(&self, iri: &str) -> Option<&str> {
+        let labels = self.labels.get(iri)?;
+        labels
+            .iter()
+            .find(|(_, lang)| lang.as_deref().is_none_or(|lang| lang.starts_with("en")))
+            .or(labels.first())
+            .map(|(label, _)| label.as_str())
+    }
+
+    pub(crate) fn alias(&self, iri: &str) -> Option<&str> {
+        self.aliases.get(iri).map(String::as_str)
+    }
+
+    /// All IRIs that are the subject of a triple.
+    pub(crate) fn subjects(&self) -> impl Iterator<Item = &str> {
+        self.subjects.iter().map(String::as_str)
+    }
+
+    /// The predicates that fit `subject`: the ones used with it and the ones
+    /// whose domain is one of its types.
+    /// All properties if `subject` is unknown.
+    pub(crate) fn predicates(&self, subject: Option<&str>) -> Vec<&str> {
+        let Some(subject) = subject.filter(|subject| self.subjects.contains(*subject)) else {
+            return self.properties.iter().map(String::as_str).collect();
+        };
+        let types = self.types.get(subject).cloned().unwrap_or_default();
+        let mut predicates: HashSet<&str> = self
+            .predicates
+            .get(subject)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        predicates.extend(
+            self.domains
+                .iter()
+                .filter(|(_, domains)| domains.iter().any(|domain| types.contains(domain)))
+                .map(|(property, _)| property.as_str()),
+        );
+        predicates.into_iter().collect()
+    }
+
+    /// The objects that fit `predicate`, with their count: the ones used with
+    /// it and the instances of its range. Classes are the objects of `rdf:type`.
+    /// All subjects and objects if `predicate` is unknown.
+    pub(crate) fn objects(&self, predicate: Option<&str>) -> Vec<(RDFTerm, usize)> {
+        let Some(predicate) = predicate.filter(|predicate| self.properties.contains(*predicate))
+        else {
+            let mut iris: HashSet<&str> = self.subjects().collect();
+            iris.extend(self.objects.values().flat_map(|objects| {
+                objects.values().filter_map(|(object, _)| match object {
+                    RDFTerm::Uri { value, .. } => Some(value.as_str()),
+                    _ => None,
+                })
+            }));
+            return iris
+                .into_iter()
+                .map(|iri| (uri(iri), self.count(iri)))
+                .collect();
+        };
+        let mut objects: HashMap<String, (RDFTerm, usize)> =
+            self.objects.get(predicate).cloned().unwrap_or_default();
+        let candidates = self
+            .ranges
+            .get(predicate)
+            .into_iter()
+            .flatten()
+            .filter_map(|range| self.instances.get(range))
+            .flatten()
+            .chain(
+                (predicate == RDF_TYPE)
+                    .then_some(&self.classes)
+                    .into_iter()
+                    .flatten(),
+            );
+        for iri in candidates {
+            let object = uri(iri);
+            objects
+                .entry(object.to_string())
+                .or_insert((object, self.count(iri)));
+        }
+        objects.into_values().collect()
+    }
+
+    /// The language tags of the literals used with `predicate`, with their count.
+    pub(crate) fn language_tags(&self, predicate: &str) -> Vec<(String, usize)> {
+        let mut tags: HashMap<String, usize> = HashMap::new();
+        for (object, count) in self
+            .objects
+            .get(predicate)
+            .into_iter()
+            .flatten()
+            .map(|(_, o)| o)
+        {
+            if let RDFTerm::Literal {
+                lang: Some(lang), ..
+            } = object
+            {
+                *tags.entry(lang.clone()).or_default() += count;
+            }
+        }
+        tags.into_iter().collect()
+    }
+
+    /// The hover documentation of `iri`, with IRIs shortened by `compact`.
+    pub(crate) fn markdown(&self, iri: &str, compact: impl Fn(&str) -> String) -> Option<String> {
+        let count = self.count(iri);
+        if count == 0 && !self.properties.contains(iri) {
+            return None;
+        }
+        let join = |iris: Option<&Vec<String>>| {
+            iris.map(|iris| {
+                iris.iter()
+                    .map(|iri| compact(iri))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+        };
+        let mut sections = vec![format!(
+            "### **{}**\n{}",
+            self.label(iri).unwrap_or(&compact(iri)),
+            compact(iri)
+        )];
+        if let Some(comment) = self.comments.get(iri) {
+            sections.push(comment.clone());
+        }
+        for (name, iris) in [
+            ("Type", join(self.types.get(iri))),
+            ("Domain", join(self.domains.get(iri))),
+            ("Range", join(self.ranges.get(iri))),
+        ] {
+            if let Some(iris) = iris {
+                sections.push(format!("{name}: {iris}"));
+            }
+        }
+        sections.push(format!("Occurs {count} times in the local files"));
+        Some(sections.join("\n\n"))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|err| format!("Could not read \"{path}\": {err}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_file(path: &str) -> Result<String, String> {
+    Err(format!(
+        "Could not read \"{path}\": local files are not supported in the browser"
+    ))
+}
+
+fn uri(iri: &str) -> RDFTerm {
+    RDFTerm::Uri {
+        value: iri.to_string(),
+        curie: None,
+    }
+}
+
+fn turtle_literal(literal: Literal) -> RDFTerm {
+    match literal {
+        Literal::Simple { value } => RDFTerm::Literal {
+            value: value.to_string(),
+            lang: None,
+            datatype: None,
+        },
+        Literal::LanguageTaggedString { value, language } => RDFTerm::Literal {
+            value: value.to_string(),
+            lang: Some(language.to_string()),
+            datatype: None,
+        },
+        Literal::Typed { value, datatype } => RDFTerm::Literal {
+            value: value.to_string(),
+            lang: None,
+            datatype: Some(datatype.iri.to_string()),
+        },
+    }
+}
+
+/// Reads a term of the `ntriples_parser`, e.g. `<iri>`, `_:b0` or `"a"@en`.
+fn ntriples_term(bytes: &[u8]) -> RDFTerm {
+    let text = String::from_utf8_lossy(bytes);
+    if let Some(iri) = text.strip_prefix('<').and_then(|iri| iri.strip_suffix('>')) {
+        return uri(iri);
+    }
+    if let Some(id) = text.strip_prefix("_:") {
+        return RDFTerm::Bnode {
+            value: id.to_string(),
+        };
+    }
+    let Some((value, suffix)) = text
+        .strip_prefix('"')
+        .and_then(|literal| literal.rsplit_once('"'))
+    else {
+        // NOTE: the parser accepts bare numbers.
+        let datatype = if text.contains(['e', 'E']) {
+            "double"
+        } else if text.contains('.') {
+            "decimal"
+        } else {
+            "integer"
+        };
+        return RDFTerm::Literal {
+            value: text.to_string(),
+            lang: None,
+            datatype: Some(format!("{XSD}{datatype}")),
+        };
+    };
+    RDFTerm::Literal {
+        value: unescape(value),
+        lang: suffix.strip_prefix('@').map(str::to_string),
+        datatype: suffix
+            .strip_prefix("^^<")
+            .and_then(|datatype| datatype.strip_suffix('>'))
+            .map(str::to_string),
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('b') => unescaped.push('\u{8}'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('f') => unescaped.push('\u{c}'),
+            Some(escape @ ('u' | 'U')) => {
+                let digits: String = chars
+                    .by_ref()
+                    .take(if escape == 'u' { 4 } else { 8 })
+                    .collect();
+                if let Some(char) = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                {
+                    unescaped.push(char);
+                }
+            }
+            Some(char) => unescaped.push(char),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::LocalIndex;
+    use crate::sparql::results::RDFTerm;
+
+    const ONTOLOGY: &str = indoc! {r#"
+        @prefix ex: <http://ex.org/> .
+        @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+        ex:City a rdfs:Class ; rdfs:label "City"@en .
+        ex:Person a rdfs:Class .
+        ex:bornIn rdfs:label "born in" ;
+            rdfs:comment "Where a person was born." ;
+            rdfs:domain ex:Person ;
+            rdfs:range ex:City .
+        ex:Berlin a ex:City ; rdfs:label "Berlin"@en, "Berlin"@de .
+        ex:Bern a ex:City .
+        ex:alice a ex:Person ; ex:name "Alice" .
+    "#};
+
+    fn index() -> LocalIndex {
+        let mut index = LocalIndex::default();
+        index.add_turtle(ONTOLOGY).unwrap();
+        index
+    }
+
+    fn sorted(mut items: Vec<&str>) -> Vec<&str> {
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn predicates_of_a_subject_include_its_domains() {
+        assert_eq!(
+            sorted(index().predicates(Some("http://ex.org/alice"))),
+            vec![
+                "http://ex.org/bornIn",
+                "http://ex.org/name",
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type"
+            ]
+        );
+    }
+
+    #[test]
+    fn objects_of_a_predicate_include_its_range() {
+        let index = index();
+        let objects = index.objects(Some("http://ex.org/bornIn"));
+        assert_eq!(
+            sorted(objects.iter().map(|(object, _)| object.value()).collect()),
+            vec!["http://ex.org/Berlin", "http://ex.org/Bern"]
+        );
+    }
+
+    #[test]
+    fn labels_prefer_english() {
+        let index = index();
+        assert_eq!(index.label("http://ex.org/Berlin"), Some("Berlin"));
+        assert_eq!(index.label("http://ex.org/City"), Some("City"));
+        assert_eq!(index.count("http://ex.org/City"), 5);
+    }
+
+    #[test]
+    fn markdown_of_a_property() {
+        let markdown = index()
+            .markdown("http://ex.org/bornIn", |iri| {
+                iri.replace("http://ex.org/", "ex:")
+            })
+            .unwrap();
+        assert_eq!(
+            markdown,
+            indoc! {"
+                ### **born in**
+                ex:bornIn
+
+                Where a person was born.
+
+                Domain: ex:Person
+
+                Range: ex:City
+
+                Occurs 4 times in the local files"
+            }
+        );
+        assert!(
+            index()
+                .markdown("http://ex.org/unknown", str::to_string)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn ntriples_terms() {
+        let mut index = LocalIndex::default();
+        index
+            .add_ntriples(indoc! {r#"
+                <http://ex.org/a> <http://ex.org/p> "say \"hi\""@en .
+                <http://ex.org/a> <http://ex.org/q> "3"^^<http://www.w3.org/2001/XMLSchema#integer> .
+            "#})
+            .unwrap();
+        let objects = index.objects(Some("http://ex.org/p"));
+        assert!(matches!(
+            &objects[0].0,
+            RDFTerm::Literal { value, lang: Some(lang), .. } if value == "say \"hi\"" && lang == "en"
+        ));
+        assert_eq!(
+            index.language_tags("http://ex.org/p"),
+            vec![("en".to_string(), 1)]
+        );
+    }
+}
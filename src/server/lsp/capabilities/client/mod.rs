@@ -20,6 +20,17 @@ pub struct ClientCapabilities {
 pub struct WorkspaceCapabilities {
     pub apply_edit: Option<bool>,
     pub workspace_edit: Option<WorkspaceEditClientCapabilities>,
+    /// Client workspace capabilities specific to diagnostics.
+    pub diagnostics: Option<DiagnosticWorkspaceClientCapabilities>,
+}
+
+/// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnosticWorkspaceClientCapabilities
+#[derive(Debug, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticWorkspaceClientCapabilities {
+    /// Whether the client implementation supports a refresh request sent from
+    /// the server to the client.
+    pub refresh_support: Option<bool>,
 }
 
 /// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#workspaceEditClientCapabilities
@@ -65,6 +65,9 @@ pub struct CompletionOptions {
     // WARNING: This is not to spec, there are more optional options:
     // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#completionOptions
     pub trigger_characters: Vec<String>,
+    /// The server provides support to resolve additional
+    /// information for a completion item.
+    pub resolve_provider: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -189,6 +192,7 @@ mod tests {
             hover_provider: true,
             completion_provider: CompletionOptions {
                 trigger_characters: vec!["?".to_string()],
+                resolve_provider: true,
             },
             document_formatting_provider: DocumentFormattingOptions {},
             document_on_type_formatting_provider: DocumentOnTypeFormattingOptions {
@@ -228,7 +232,7 @@ mod tests {
 
         pretty_assertions::assert_eq!(
             serialized,
-            r#"{"textDocumentSync":1,"hoverProvider":true,"completionProvider":{"triggerCharacters":["?"]},"documentFormattingProvider":{},"documentOnTypeFormattingProvider":{"firstTriggerCharacter":"\n"},"diagnosticProvider":{"identifier":"my-ls","interFileDependencies":false,"workspaceDiagnostics":false},"codeActionProvider":true,"executeCommandProvider":{"workDoneProgress":true,"commands":["foo"]},"foldingRangeProvider":true,"semanticTokensProvider":{"workDoneProgress":true,"legend":{"tokenTypes":["function","string"],"tokenModifiers":["async"]},"range":true,"full":true},"renameProvider":true,"referencesProvider":true,"documentHighlightProvider":true}"#
+            r#"{"textDocumentSync":1,"hoverProvider":true,"completionProvider":{"triggerCharacters":["?"],"resolveProvider":true},"documentFormattingProvider":{},"documentOnTypeFormattingProvider":{"firstTriggerCharacter":"\n"},"diagnosticProvider":{"identifier":"my-ls","interFileDependencies":false,"workspaceDiagnostics":false},"codeActionProvider":true,"executeCommandProvider":{"workDoneProgress":true,"commands":["foo"]},"foldingRangeProvider":true,"semanticTokensProvider":{"workDoneProgress":true,"legend":{"tokenTypes":["function","string"],"tokenModifiers":["async"]},"range":true,"full":true},"renameProvider":true,"referencesProvider":true,"documentHighlightProvider":true}"#
         );
     }
 }
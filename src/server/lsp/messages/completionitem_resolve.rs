@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::server::lsp::{
+    LspMessage,
+    rpc::{RequestId, RequestMessageBase, ResponseMessageBase},
+};
+
+use super::CompletionItem;
+
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct CompletionResolveRequest {
+    #[serde(flatten)]
+    base: RequestMessageBase,
+    pub params: CompletionItem,
+}
+
+impl LspMessage for CompletionResolveRequest {}
+
+impl CompletionResolveRequest {
+    pub(crate) fn get_id(&self) -> &RequestId {
+        &self.base.id
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CompletionResolveResponse {
+    #[serde(flatten)]
+    base: ResponseMessageBase,
+    result: CompletionItem,
+}
+
+impl LspMessage for CompletionResolveResponse {}
+
+impl CompletionResolveResponse {
+    pub fn new(id: &RequestId, completion_item: CompletionItem) -> Self {
+        CompletionResolveResponse {
+            base: ResponseMessageBase::success(id),
+            result: completion_item,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::server::lsp::{
+        CompletionItem, CompletionItemKind, Documentation,
+        base_types::LSPAny,
+        rpc::{Message, RequestId, RequestMessageBase},
+    };
+
+    use super::{CompletionResolveRequest, CompletionResolveResponse};
+
+    fn item(documentation: Option<Documentation>) -> CompletionItem {
+        CompletionItem {
+            label: "wd:Q42".to_string(),
+            label_details: None,
+            kind: Some(CompletionItemKind::Value),
+            detail: None,
+            documentation,
+            sort_text: None,
+            filter_text: None,
+            insert_text: None,
+            text_edit: None,
+            insert_text_format: None,
+            additional_text_edits: None,
+            command: None,
+            data: Some(LSPAny::LSPObject(HashMap::from([(
+                "backend".to_string(),
+                LSPAny::String("wikidata".to_string()),
+            )]))),
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        let message = br#"{"id":7,"params":{"label":"wd:Q42","kind":12,"documentation":"Label: Douglas Adams","data":{"backend":"wikidata"}},"jsonrpc":"2.0","method":"completionItem/resolve"}"#;
+        let resolve_request: CompletionResolveRequest = serde_json::from_slice(message).unwrap();
+        assert_eq!(
+            resolve_request,
+            CompletionResolveRequest {
+                base: RequestMessageBase {
+                    base: Message {
+                        jsonrpc: "2.0".to_string()
+                    },
+                    method: "completionItem/resolve".to_string(),
+                    id: RequestId::Integer(7)
+                },
+                params: item(Some(Documentation::String(
+                    "Label: Douglas Adams".to_string()
+                )))
+            }
+        )
+    }
+
+    #[test]
+    fn serialize() {
+        let resolve_response = CompletionResolveResponse::new(
+            &RequestId::Integer(7),
+            item(Some(Documentation::markdown(
+                "**Douglas Adams**".to_string(),
+            ))),
+        );
+        let expected_message = r#"{"jsonrpc":"2.0","id":7,"result":{"label":"wd:Q42","kind":12,"documentation":{"kind":"markdown","value":"**Douglas Adams**"},"data":{"backend":"wikidata"}}}"#;
+        assert_eq!(
+            serde_json::to_string(&resolve_response).unwrap(),
+            expected_message
+        );
+    }
+}
@@ -1,4 +1,5 @@
 mod command;
+mod completionitem_resolve;
 pub mod diagnostic;
 mod initialize;
 mod progress;
@@ -32,8 +33,10 @@ mod utils;
 mod window_showmessage;
 mod workspace;
 mod workspace_applyedit;
+mod workspace_diagnostic_refresh;
 
 pub use command::*;
+pub use completionitem_resolve::*;
 pub use initialize::*;
 pub use progress::*;
 pub use qlue_ls_execute::*;
@@ -64,5 +67,6 @@ pub use textdocument_rename::*;
 pub use textdocument_semantic_tokens::*;
 pub use workspace::*;
 pub use workspace_applyedit::*;
+pub use workspace_diagnostic_refresh::*;
 
 pub trait LspMessage {}
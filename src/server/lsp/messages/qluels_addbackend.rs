@@ -13,7 +13,7 @@ pub struct AddBackendNotification {
 
 impl LspMessage for AddBackendNotification {}
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub enum SparqlEngine {
     QLever,
     GraphDB,
@@ -54,13 +54,49 @@ pub enum CodeActionKind {
     SourceFixAll,
 }
 
+impl CodeActionKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            CodeActionKind::Empty => "",
+            CodeActionKind::QuickFix => "quickfix",
+            CodeActionKind::Refactor => "refactor",
+            CodeActionKind::RefactorExtract => "refactor.extract",
+            CodeActionKind::RefactorInline => "refactor.inline",
+            CodeActionKind::RefactorRewrite => "refactor.rewrite",
+            CodeActionKind::Source => "source",
+            CodeActionKind::SourceShortenURIs => "source.shortenUris",
+            CodeActionKind::SourceOrganizeImports => "source.organizeImports",
+            CodeActionKind::SourceFixAll => "source.fixAll",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CodeActionContext {
     pub diagnostics: Vec<Diagnostic>,
-    // pub only: Option<Vec<CodeActionKind>>,
+    /// Requested kinds, e.g. `source.organizeImports` for `codeActionsOnSave`.
+    ///
+    /// INFO: kept as strings, clients may request kinds this server does not know.
+    pub only: Option<Vec<String>>,
     // trigger_kind: Option<CodeActionTriggerKind>,
 }
 
+impl CodeActionContext {
+    /// Whether a code action of `kind` was requested.
+    /// A requested kind also covers its sub-kinds, `source` covers `source.fixAll`.
+    pub(crate) fn requests(&self, kind: Option<&CodeActionKind>) -> bool {
+        match (&self.only, kind) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(only), Some(kind)) => only.iter().any(|requested| {
+                kind.as_str()
+                    .strip_prefix(requested.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct CodeActionResponse {
     #[serde(flatten)]
@@ -128,7 +164,7 @@ impl CodeAction {
 #[cfg(test)]
 mod test {
     use crate::server::lsp::{
-        CodeAction, CodeActionResponse, WorkspaceEdit,
+        CodeAction, CodeActionContext, CodeActionKind, CodeActionResponse, WorkspaceEdit,
         rpc::RequestId,
         textdocument::{Range, TextEdit},
     };
@@ -154,4 +190,33 @@ mod test {
             r#"{"jsonrpc":"2.0","id":42,"result":[{"title":"test-action","edit":{"changes":{"file:///test.rq":[{"range":{"start":{"line":0,"character":0},"end":{"line":0,"character":0}},"newText":"test"}]}}}]}"#
         )
     }
+
+    #[test]
+    fn only_matches_sub_kinds() {
+        let context = CodeActionContext {
+            diagnostics: vec![],
+            only: Some(vec!["source".to_string(), "quickfix".to_string()]),
+        };
+        assert!(context.requests(Some(&CodeActionKind::SourceOrganizeImports)));
+        assert!(context.requests(Some(&CodeActionKind::QuickFix)));
+        assert!(!context.requests(Some(&CodeActionKind::RefactorRewrite)));
+        assert!(!context.requests(None));
+        let context = CodeActionContext {
+            diagnostics: vec![],
+            only: Some(vec!["source.organize".to_string()]),
+        };
+        assert!(!context.requests(Some(&CodeActionKind::SourceOrganizeImports)));
+    }
+
+    #[test]
+    fn kind_as_str_matches_serialization() {
+        for kind in [
+            CodeActionKind::QuickFix,
+            CodeActionKind::RefactorRewrite,
+            CodeActionKind::SourceShortenURIs,
+            CodeActionKind::SourceOrganizeImports,
+        ] {
+            assert_eq!(serde_json::to_value(&kind).unwrap(), kind.as_str());
+        }
+    }
 }
@@ -8,7 +8,11 @@ use crate::server::lsp::{
     textdocument::{Range, TextEdit},
 };
 
-use super::{command::Command, utils::TextDocumentPositionParams};
+use super::{
+    command::Command,
+    textdocument_hover::{MarkupContent, Markupkind},
+    utils::TextDocumentPositionParams,
+};
 
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct CompletionRequest {
@@ -107,7 +111,7 @@ pub struct ItemDefaults {
 }
 
 /// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#completionItem
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CompletionItem {
     pub label: String,
@@ -118,7 +122,7 @@ pub struct CompletionItem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub detail: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub documentation: Option<String>,
+    pub documentation: Option<Documentation>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_text: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -133,6 +137,10 @@ pub struct CompletionItem {
     pub additional_text_edits: Option<Vec<TextEdit>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub command: Option<Command>,
+    /// A data entry field that is preserved on a completion item between
+    /// a completion and a completion resolve request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<LSPAny>,
 }
 
 impl CompletionItem {
@@ -157,16 +165,34 @@ impl CompletionItem {
             insert_text_format: None,
             additional_text_edits,
             command: None,
+            data: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, PartialEq)]
+/// The documentation of a completion item, either plain text or markup.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Documentation {
+    String(String),
+    MarkupContent(MarkupContent),
+}
+
+impl Documentation {
+    pub fn markdown(value: String) -> Self {
+        Documentation::MarkupContent(MarkupContent::Content {
+            kind: Markupkind::Markdown,
+            value,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct CompletionItemLabelDetails {
     pub detail: String,
 }
 
-#[derive(Debug, Serialize_repr, PartialEq)]
+#[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq)]
 #[repr(u8)]
 #[allow(dead_code)]
 pub enum CompletionItemKind {
@@ -394,7 +420,7 @@ impl CompletionItemBuilder {
             label_details: self.label_details,
             kind: self.kind,
             detail: self.detail,
-            documentation: self.documentation,
+            documentation: self.documentation.map(Documentation::String),
             sort_text: self.sort_text,
             filter_text: self.filter_text,
             insert_text: self.insert_text,
@@ -402,6 +428,7 @@ impl CompletionItemBuilder {
             insert_text_format: self.insert_text_format,
             additional_text_edits: self.additional_text_edits,
             command: self.command,
+            data: None,
         }
     }
 }
@@ -464,6 +491,7 @@ mod tests {
             kind: Some(CompletionItemKind::Snippet),
             insert_text_format: Some(InsertTextFormat::Snippet),
             additional_text_edits: None,
+            data: None,
         };
         let completion_list = CompletionList {
             is_incomplete: true,
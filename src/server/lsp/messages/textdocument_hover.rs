@@ -87,13 +87,13 @@ enum MarkedString {
     Content { language: String, value: String },
 }
 
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(untagged)]
-enum MarkupContent {
+pub enum MarkupContent {
     Content { kind: Markupkind, value: String },
 }
 
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 #[allow(dead_code)]
 pub enum Markupkind {
@@ -0,0 +1,33 @@
+use serde::Serialize;
+
+use crate::server::lsp::{LspMessage, rpc::RequestMessageBase};
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#diagnostic_refresh
+#[derive(Debug, Serialize, PartialEq)]
+pub struct DiagnosticRefreshRequest {
+    #[serde(flatten)]
+    pub base: RequestMessageBase,
+}
+
+impl LspMessage for DiagnosticRefreshRequest {}
+
+impl DiagnosticRefreshRequest {
+    pub fn new(id: u32) -> Self {
+        Self {
+            base: RequestMessageBase::new("workspace/diagnostic/refresh", id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiagnosticRefreshRequest;
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            serde_json::to_string(&DiagnosticRefreshRequest::new(3)).unwrap(),
+            r#"{"jsonrpc":"2.0","id":3,"method":"workspace/diagnostic/refresh"}"#
+        );
+    }
+}
@@ -73,10 +73,17 @@ pub(super) async fn handle_add_backend_notification(
 ) -> Result<(), LSPError> {
     let mut server = server_rc.lock().await;
 
+    request
+        .params
+        .validate()
+        .map_err(|error| LSPError::new(ErrorCode::InvalidParams, &error))?;
     server
         .state
         .load_prefix_map(request.params.name.clone(), &request.params.prefix_map)?;
     server.load_templates(&request.params.name, request.params.queries.clone())?;
+    server
+        .state
+        .load_local_index(&request.params.name, &request.params.files)?;
     let backend_name = request.params.name.clone();
     let default = request.params.default;
     server.state.add_backend(request.params);
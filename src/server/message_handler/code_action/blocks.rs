@@ -0,0 +1,545 @@
+//! Split and merge blocks of a group: split a group into two `UNION` branches,
+//! merge adjacent `OPTIONAL`s on the same subject and merge or split `FILTER`s.
+//! Unlike the other rewrites, merging `OPTIONAL`s can change the results: the
+//! merged `OPTIONAL` only matches where both did, which its title says.
+//!
+//! Comments in the rewritten text are kept. A comment that ends up in between
+//! the rewritten parts is put on a line of its own, the formatter takes care of
+//! its indentation like for every other comment.
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode, SyntaxToken,
+    ast::{AstNode, Triple},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use super::{sub_select::line_indentation, term::next_non_trivia};
+use crate::server::lsp::{
+    CodeAction, CodeActionKind,
+    textdocument::{Range, TextDocumentItem, TextEdit},
+};
+
+pub(super) fn code_actions(
+    element: &SyntaxElement,
+    document: &TextDocumentItem,
+    indent: &str,
+) -> Vec<CodeAction> {
+    let ancestors: Vec<SyntaxNode> = match element {
+        SyntaxElement::Node(node) => node.ancestors().collect(),
+        SyntaxElement::Token(token) => token.parent_ancestors().collect(),
+    };
+    [
+        split_into_union(&ancestors, document, indent),
+        merge_optionals(element, document),
+        merge_filters(&ancestors),
+        split_filter(&ancestors, document),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|(title, range, new_text)| {
+        let mut code_action = CodeAction::new(title, Some(CodeActionKind::RefactorRewrite));
+        code_action.add_edit(
+            &document.uri,
+            TextEdit::new(
+                Range::from_byte_offset_range(range, &document.text)?,
+                &new_text,
+            ),
+        );
+        Some(code_action)
+    })
+    .collect()
+}
+
+type Rewrite = (&'static str, TextRange, String);
+
+/// The comments inside of `node` within `range`.
+fn comments(node: &SyntaxNode, range: TextRange) -> impl Iterator<Item = SyntaxToken> {
+    node.descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(move |token| {
+            token.kind() == SyntaxKind::Comment && range.contains_range(token.text_range())
+        })
+}
+
+/// Splits the group around the selected pattern into two `UNION` branches,
+/// the second one starting with the selected pattern.
+fn split_into_union(
+    ancestors: &[SyntaxNode],
+    document: &TextDocumentItem,
+    indent: &str,
+) -> Option<Rewrite> {
+    let group = ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::GroupGraphPatternSub)?;
+    let patterns: Vec<SyntaxNode> = group
+        .children()
+        .flat_map(|child| match child.kind() {
+            SyntaxKind::TriplesBlock => child
+                .descendants()
+                .filter(|node| Triple::can_cast(node.kind()))
+                .collect(),
+            SyntaxKind::GraphPatternNotTriples => vec![child],
+            _ => vec![],
+        })
+        .collect();
+    let split = patterns
+        .iter()
+        .position(|pattern| ancestors.contains(pattern))
+        .filter(|split| *split > 0)?;
+    let (first, last) = (patterns.first()?, patterns.last()?);
+    let (before, after) = (&patterns[split - 1], &patterns[split]);
+
+    // NOTE: a comment on the line of the last pattern of the first branch stays
+    // there, every other comment in between moves into the second branch.
+    let gap = TextRange::new(before.text_range().end(), after.text_range().start());
+    let (trailing, leading): (Vec<SyntaxToken>, Vec<SyntaxToken>) =
+        comments(group, gap).partition(|comment| {
+            !document.text[TextRange::new(gap.start(), comment.text_range().start())].contains('\n')
+        });
+    let mut first_branch =
+        document.text[TextRange::new(first.text_range().start(), gap.start())].to_string();
+    for comment in trailing {
+        first_branch.push(' ');
+        first_branch.push_str(comment.text());
+    }
+    let base = line_indentation(&document.text, first.text_range().start());
+    let second_branch = leading
+        .iter()
+        .map(|comment| comment.text().to_string())
+        .chain(std::iter::once(
+            document.text[TextRange::new(gap.end(), last.text_range().end())].to_string(),
+        ))
+        .collect::<Vec<_>>()
+        .join(&format!("\n{base}"));
+
+    let branch = |text: &str| {
+        format!(
+            "{{\n{base}{indent}{}\n{base}}}",
+            text.replace('\n', &format!("\n{indent}"))
+        )
+    };
+    Some((
+        "Split into UNION",
+        TextRange::new(first.text_range().start(), last.text_range().end()),
+        format!("{} UNION {}", branch(&first_branch), branch(&second_branch)),
+    ))
+}
+
+/// The graph patterns right before and after `pattern` in its group,
+/// separated by nothing but trivia and possibly a dot.
+fn adjacent_patterns(pattern: &SyntaxNode) -> [Option<SyntaxNode>; 2] {
+    [false, true].map(|forward| {
+        let token = match forward {
+            true => pattern.last_token()?.next_token(),
+            false => pattern.first_token()?.prev_token(),
+        };
+        let mut token = next_non_trivia(token, forward)?;
+        if token.kind() == SyntaxKind::Dot {
+            let next = match forward {
+                true => token.next_token(),
+                false => token.prev_token(),
+            };
+            token = next_non_trivia(next, forward)?;
+        }
+        token
+            .parent_ancestors()
+            .find(|node| node.kind() == SyntaxKind::GraphPatternNotTriples)
+            .filter(|other| other.parent() == pattern.parent())
+    })
+}
+
+/// Merges the `OPTIONAL` under the cursor with an adjacent one, if the first
+/// triples of both have the same subject.
+///
+/// The bindings of one `OPTIONAL` are lost where the other one does not match.
+/// There is no telling whether that ever happens, so the title says it.
+fn merge_optionals(element: &SyntaxElement, document: &TextDocumentItem) -> Option<Rewrite> {
+    // NOTE: only offered on the keyword, inside of the group other actions apply.
+    let keyword = element
+        .as_token()
+        .filter(|token| token.kind() == SyntaxKind::OPTIONAL)?;
+    let pattern = keyword.parent()?.parent()?;
+    let [previous, next] = adjacent_patterns(&pattern);
+    let (first, second) = match (previous, next) {
+        (_, Some(next)) if same_subject_optionals(&pattern, &next) => (pattern, next),
+        (Some(previous), _) if same_subject_optionals(&previous, &pattern) => (previous, pattern),
+        _ => return None,
+    };
+    let (first_body, second_body) = (optional_body(&first)?, optional_body(&second)?);
+    let first_end = next_non_trivia(first_body.last_token(), false)?;
+    let second_start = next_non_trivia(second_body.first_token(), true)?;
+    let gap = TextRange::new(
+        first_end.text_range().end(),
+        second_start.text_range().start(),
+    );
+
+    let mut new_text =
+        document.text[TextRange::new(first.text_range().start(), gap.start())].to_string();
+    if first_end.kind() != SyntaxKind::Dot {
+        new_text.push_str(" .");
+    }
+    let multiline = document.text
+        [TextRange::new(first.text_range().start(), first_body.text_range().start())]
+    .contains('\n');
+    let separator = format!(
+        "\n{}",
+        line_indentation(&document.text, first_body.text_range().start())
+    );
+    let parent = first.parent()?;
+    let gap_comments: Vec<String> = comments(&parent, gap)
+        .map(|comment| comment.text().to_string())
+        .collect();
+    if !multiline && gap_comments.is_empty() {
+        new_text.push(' ');
+    } else {
+        for comment in gap_comments {
+            new_text.push_str(&separator);
+            new_text.push_str(&comment);
+        }
+        new_text.push_str(&separator);
+    }
+    new_text.push_str(&document.text[TextRange::new(gap.end(), second.text_range().end())]);
+    Some((
+        "Merge OPTIONALs (match only together)",
+        TextRange::new(first.text_range().start(), second.text_range().end()),
+        new_text,
+    ))
+}
+
+/// The group of the `OPTIONAL` in `pattern`.
+fn optional_body(pattern: &SyntaxNode) -> Option<SyntaxNode> {
+    pattern
+        .first_child()
+        .filter(|optional| optional.kind() == SyntaxKind::OptionalGraphPattern)?
+        .children()
+        .find(|child| child.kind() == SyntaxKind::GroupGraphPattern)?
+        .children()
+        .find(|child| child.kind() == SyntaxKind::GroupGraphPatternSub)
+}
+
+fn same_subject_optionals(first: &SyntaxNode, second: &SyntaxNode) -> bool {
+    subject(first).is_some_and(|subject| self::subject(second) == Some(subject))
+}
+
+/// The subject of the first triple in the `OPTIONAL` of `pattern`.
+fn subject(pattern: &SyntaxNode) -> Option<String> {
+    optional_body(pattern)?
+        .first_child()
+        .filter(|child| child.kind() == SyntaxKind::TriplesBlock)?
+        .first_child()?
+        .first_child()
+        .map(|subject| subject.text().to_string())
+}
+
+/// The `FILTER` around the cursor, as its graph pattern.
+fn selected_filter(ancestors: &[SyntaxNode]) -> Option<SyntaxNode> {
+    ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::Filter)?
+        .parent()
+        .filter(|parent| parent.kind() == SyntaxKind::GraphPatternNotTriples)
+}
+
+/// The constraint of the `FILTER` in `pattern` as an operand of `&&`.
+///
+/// Returns the node to take the text from and whether it needs parentheses.
+fn conjunct(pattern: &SyntaxNode) -> Option<(SyntaxNode, bool)> {
+    let constraint = pattern
+        .first_child()
+        .filter(|filter| filter.kind() == SyntaxKind::Filter)?
+        .children()
+        .find(|child| child.kind() == SyntaxKind::Constraint)?;
+    match constraint
+        .first_child()
+        .filter(|child| child.kind() == SyntaxKind::BrackettedExpression)
+        .and_then(|bracketted| bracketted.first_child())
+    {
+        // NOTE: `||` binds weaker than `&&`.
+        Some(expression) => {
+            let disjunction = expression
+                .first_child()?
+                .children()
+                .filter(|child| child.kind() == SyntaxKind::ConditionalAndExpression)
+                .count()
+                > 1;
+            Some((expression, disjunction))
+        }
+        None => Some((constraint, false)),
+    }
+}
+
+/// Merges the `FILTER` under the cursor with the next one, or else the previous one.
+fn merge_filters(ancestors: &[SyntaxNode]) -> Option<Rewrite> {
+    let pattern = selected_filter(ancestors)?;
+    let is_filter = |node: &SyntaxNode| {
+        node.first_child()
+            .is_some_and(|child| child.kind() == SyntaxKind::Filter)
+    };
+    let [previous, next] = adjacent_patterns(&pattern);
+    let (first, second) = match (previous, next) {
+        (_, Some(next)) if is_filter(&next) => (pattern, next),
+        (Some(previous), _) if is_filter(&previous) => (previous, pattern),
+        _ => return None,
+    };
+    let range = TextRange::new(first.text_range().start(), second.text_range().end());
+    let operands = [conjunct(&first)?, conjunct(&second)?];
+    let [left, right] = operands.each_ref().map(|(node, parenthesize)| {
+        if *parenthesize {
+            format!("({})", node.text())
+        } else {
+            node.text().to_string()
+        }
+    });
+    let outside = |comment: &SyntaxToken| {
+        !operands
+            .iter()
+            .any(|(node, _)| node.text_range().contains_range(comment.text_range()))
+    };
+    let between: Vec<String> = comments(&first.parent()?, range)
+        .filter(outside)
+        .map(|comment| comment.text().to_string())
+        .collect();
+    let new_text = match between.is_empty() {
+        true => format!("FILTER ({left} && {right})"),
+        false => format!("FILTER ({left} && {}\n{right})", between.join("\n")),
+    };
+    Some(("Merge FILTERs", range, new_text))
+}
+
+/// Splits the conjunction `a && b && ...` of the `FILTER` under the cursor
+/// into one `FILTER` per operand.
+fn split_filter(ancestors: &[SyntaxNode], document: &TextDocumentItem) -> Option<Rewrite> {
+    let pattern = selected_filter(ancestors)?;
+    let (expression, disjunction) = conjunct(&pattern)?;
+    if disjunction || expression.kind() != SyntaxKind::Expression {
+        return None;
+    }
+    let operands: Vec<SyntaxNode> = expression
+        .first_child()?
+        .first_child()?
+        .children()
+        .filter(|child| child.kind() == SyntaxKind::ValueLogical)
+        .collect();
+    if operands.len() < 2 {
+        return None;
+    }
+    let mut filters: Vec<String> = operands
+        .iter()
+        .map(|operand| format!("FILTER ({})", operand.text()))
+        .collect();
+    let separator = format!(
+        "\n{}",
+        line_indentation(&document.text, pattern.text_range().start())
+    );
+    // NOTE: comments in between the operands trail the filter of the operand before.
+    for comment in comments(&pattern, pattern.text_range()).filter(|comment| {
+        !operands
+            .iter()
+            .any(|operand| operand.text_range().contains_range(comment.text_range()))
+    }) {
+        let idx = operands
+            .iter()
+            .filter(|operand| operand.text_range().end() <= comment.text_range().start())
+            .count()
+            .saturating_sub(1);
+        // NOTE: a second comment can not trail the first one.
+        if filters[idx].ends_with(')') {
+            filters[idx].push(' ');
+        } else {
+            filters[idx].push_str(&separator);
+        }
+        filters[idx].push_str(comment.text());
+    }
+    Some((
+        "Split FILTER",
+        pattern.text_range(),
+        filters.join(&separator),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::code_actions;
+    use crate::server::{
+        lsp::textdocument::TextDocumentItem, message_handler::formatting::format_raw,
+    };
+
+    /// Applies the code action titled `title` on the first occurrence of `needle`.
+    fn apply(input: &str, needle: &str, title: &str) -> Option<String> {
+        apply_with_indent(input, needle, title, "  ")
+    }
+
+    fn apply_with_indent(input: &str, needle: &str, title: &str, indent: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (root, _) = parse(&document.text);
+        let offset = TextSize::new(input.find(needle)? as u32);
+        let element = root.covering_element(TextRange::at(offset, 1.into()));
+        let code_action = code_actions(&element, &document, indent)
+            .into_iter()
+            .find(|code_action| code_action.title == title)?;
+        let edits = code_action.edit.changes?.remove("query.rq")?;
+        document.apply_text_edits(edits);
+        Some(document.text)
+    }
+
+    #[test]
+    fn split_into_union() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?s <p> ?o . # first
+               # second
+               ?s <q> ?x .
+               FILTER (?x > 1)
+             }"
+        };
+        assert_eq!(
+            apply(input, "?s <q>", "Split into UNION").unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   {
+                     ?s <p> ?o # first
+                   } UNION {
+                     # second
+                     ?s <q> ?x .
+                     FILTER (?x > 1)
+                   }
+                 }
+                 "
+            }
+        );
+        assert_eq!(apply(input, "?s <p>", "Split into UNION"), None);
+    }
+
+    #[test]
+    fn split_into_union_with_tabs() {
+        assert_eq!(
+            apply_with_indent(
+                "SELECT * WHERE {\n\t?s <p> ?o .\n\t?s <q> ?x\n}",
+                "?s <q>",
+                "Split into UNION",
+                "\t"
+            )
+            .unwrap(),
+            "SELECT * WHERE {\n\t{\n\t\t?s <p> ?o\n\t} UNION {\n\t\t?s <q> ?x\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn merge_optionals() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               OPTIONAL { ?s <p> ?a } # a
+               OPTIONAL { ?s <q> ?b }
+               OPTIONAL { ?o <r> ?c }
+             }"
+        };
+        let merged = apply(input, "OPTIONAL", "Merge OPTIONALs (match only together)").unwrap();
+        assert_eq!(
+            merged,
+            indoc! {
+                "SELECT * WHERE {
+                   OPTIONAL { ?s <p> ?a .
+                   # a
+                   ?s <q> ?b }
+                   OPTIONAL { ?o <r> ?c }
+                 }
+                 "
+            }
+        );
+        assert_eq!(
+            format_raw(merged).unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   OPTIONAL {
+                     ?s <p> ?a .
+                     # a
+                     ?s <q> ?b
+                   }
+                   OPTIONAL {
+                     ?o <r> ?c
+                   }
+                 }
+                 "
+            }
+        );
+        // NOTE: the last one has a different subject.
+        assert_eq!(
+            apply(
+                input,
+                "OPTIONAL { ?o",
+                "Merge OPTIONALs (match only together)"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn merge_filters() {
+        let input = indoc! {
+            r#"SELECT * WHERE {
+                 ?s <p> ?x
+                 FILTER (?x > 1 || ?x < -1) # range
+                 FILTER BOUND(?s)
+               }"#
+        };
+        let merged = apply(input, "FILTER BOUND", "Merge FILTERs").unwrap();
+        assert_eq!(
+            merged,
+            indoc! {
+                r#"SELECT * WHERE {
+                     ?s <p> ?x
+                     FILTER ((?x > 1 || ?x < -1) && # range
+                   BOUND(?s))
+                   }
+                   "#
+            }
+        );
+        assert_eq!(
+            format_raw(merged).unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   ?s <p> ?x
+                   FILTER ((?x > 1 || ?x < -1) && # range
+                     BOUND(?s))
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn split_filter() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?s <p> ?x
+               FILTER (?x > 1 # lower
+                       && ?x < 5 && BOUND(?s))
+             }"
+        };
+        assert_eq!(
+            apply(input, "&&", "Split FILTER").unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   ?s <p> ?x
+                   FILTER (?x > 1) # lower
+                   FILTER (?x < 5)
+                   FILTER (BOUND(?s))
+                 }
+                 "
+            }
+        );
+        assert_eq!(
+            apply(
+                "SELECT * WHERE { FILTER (?a && ?b || ?c) }",
+                "FILTER",
+                "Split FILTER"
+            ),
+            None
+        );
+    }
+}
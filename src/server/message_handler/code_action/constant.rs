@@ -0,0 +1,335 @@
+//! Replace a constant, an IRI or a literal in a graph pattern, with a fresh variable.
+//!
+//! The variable is named like an object variable completion: an object after the
+//! label or local name of its predicate, any other constant after itself.
+
+use std::collections::HashMap;
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, Iri, SelectQuery},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use super::wrap::fresh_variables;
+use crate::server::{
+    Server,
+    lsp::{
+        CodeAction, CodeActionKind,
+        textdocument::{Range, TextDocumentItem, TextEdit},
+    },
+    message_handler::common::object_variable_name,
+};
+
+pub(super) fn code_actions(
+    element: &SyntaxElement,
+    server: &Server,
+    document: &TextDocumentItem,
+) -> Vec<CodeAction> {
+    let Some(constant) = selected_constant(element) else {
+        return vec![];
+    };
+    let name = variable_name(&constant, &server.state.label_memory);
+    let Some(variable) = constant
+        .ancestors()
+        .last()
+        .and_then(|root| {
+            fresh_variables(
+                &root,
+                &object_variable_name(server.settings.replacements.as_ref(), &name),
+            )
+            .next()
+        })
+        .map(|name| format!("?{name}"))
+    else {
+        return vec![];
+    };
+    let Some(replace) = Range::from_byte_offset_range(constant.text_range(), &document.text)
+        .map(|range| TextEdit::new(range, &variable))
+    else {
+        return vec![];
+    };
+    let mut code_action = CodeAction::new(
+        "Replace with variable",
+        Some(CodeActionKind::RefactorRewrite),
+    );
+    code_action.add_edit(&document.uri, replace.clone());
+    let mut code_actions = vec![code_action];
+    if let Some(projection) = add_to_projection(&constant, &variable, document) {
+        let mut code_action = CodeAction::new(
+            "Replace with variable and add to result",
+            Some(CodeActionKind::RefactorRewrite),
+        );
+        code_action.add_edit(&document.uri, replace);
+        code_action.add_edit(&document.uri, projection);
+        code_actions.push(code_action);
+    }
+    code_actions
+}
+
+/// An IRI or a literal.
+pub(super) fn is_constant(node: &SyntaxNode) -> bool {
+    matches!(
+        node.kind(),
+        SyntaxKind::iri
+            | SyntaxKind::RDFLiteral
+            | SyntaxKind::NumericLiteral
+            | SyntaxKind::BooleanLiteral
+    )
+}
+
+/// The constant around `element`, if it is a term of a triple pattern or the
+/// name of a `GRAPH` pattern, where a variable may take its place.
+fn selected_constant(element: &SyntaxElement) -> Option<SyntaxNode> {
+    let parent = match element {
+        SyntaxElement::Node(node) => Some(node.clone()),
+        SyntaxElement::Token(token) => token.parent(),
+    }?;
+    let constant = parent.ancestors().find(|node| {
+        is_constant(node)
+            && node.parent().is_some_and(|slot| match slot.kind() {
+                SyntaxKind::VarOrTerm => true,
+                SyntaxKind::VarOrIri => slot
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == SyntaxKind::GraphGraphPattern),
+                // NOTE: only a predicate that is a plain IRI, not part of a longer path.
+                SyntaxKind::PathPrimary => slot
+                    .ancestors()
+                    .find(|ancestor| ancestor.kind() == SyntaxKind::VerbPath)
+                    .is_some_and(|verb| verb.text_range() == node.text_range()),
+                _ => false,
+            })
+    })?;
+    // NOTE: a variable in a template would be unbound.
+    constant
+        .ancestors()
+        .any(|ancestor| ancestor.kind() == SyntaxKind::GroupGraphPattern)
+        .then_some(constant)
+}
+
+/// The name of the variable replacing `constant`, before replacements.
+fn variable_name(constant: &SyntaxNode, labels: &HashMap<String, String>) -> String {
+    let term_name = |term: &SyntaxNode| {
+        labels
+            .get(&term.text().to_string())
+            .cloned()
+            .or_else(|| Iri::cast(term.clone()).map(|iri| local_name(&iri)))
+    };
+    predicate(constant)
+        .and_then(|predicate| match predicate.kind() {
+            SyntaxKind::a => Some("type".to_string()),
+            _ => predicate.as_node().and_then(term_name),
+        })
+        .or_else(|| term_name(constant))
+        .unwrap_or_else(|| "value".to_string())
+}
+
+/// The predicate of `constant` if it is an object and the predicate is `a` or an IRI.
+fn predicate(constant: &SyntaxNode) -> Option<SyntaxElement> {
+    // NOTE: VarOrTerm > GraphNode(Path) > Object(Path) > ObjectList(Path)
+    let object = constant.parent()?.parent()?.parent()?;
+    if !matches!(object.kind(), SyntaxKind::ObjectPath | SyntaxKind::Object) {
+        return None;
+    }
+    let verb = object.parent()?.prev_sibling()?;
+    let predicate = verb
+        .descendants_with_tokens()
+        .find(|element| matches!(element.kind(), SyntaxKind::a | SyntaxKind::iri))?;
+    (predicate.text_range() == verb.text_range()).then_some(predicate)
+}
+
+/// The local name of `iri`: the part after the prefix, or after the last `/` or `#`.
+fn local_name(iri: &Iri) -> String {
+    match (iri.prefixed_name(), iri.raw_iri()) {
+        (Some(prefixed_name), _) => prefixed_name.name(),
+        (None, Some(raw)) => raw
+            .trim_end_matches(['/', '#'])
+            .rsplit(['/', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        (None, None) => String::new(),
+    }
+}
+
+/// The edit adding `variable` to the select clause of the query around `constant`.
+///
+/// Not offered for `SELECT *`, which already projects it, or a grouped query,
+/// where an ungrouped variable may not be projected.
+fn add_to_projection(
+    constant: &SyntaxNode,
+    variable: &str,
+    document: &TextDocumentItem,
+) -> Option<TextEdit> {
+    let select_query = constant.ancestors().find_map(SelectQuery::cast)?;
+    let select_clause = select_query.select_clause()?;
+    if select_clause.is_star_selection()
+        || select_query
+            .soulution_modifier()
+            .and_then(|solution_modifier| solution_modifier.group_clause())
+            .is_some()
+    {
+        return None;
+    }
+    // NOTE: plain variables go before the assignments.
+    let (offset, new_text) = select_clause
+        .syntax()
+        .children_with_tokens()
+        .find(|child| child.kind() == SyntaxKind::LParen)
+        .map(|lparen| (lparen.text_range().start(), format!("{variable} ")))
+        .unwrap_or((
+            select_clause.syntax().text_range().end(),
+            format!(" {variable}"),
+        ));
+    let range = Range::from_byte_offset_range(TextRange::empty(offset), &document.text)?;
+    Some(TextEdit::new(range, &new_text))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::code_actions;
+    use crate::server::{Server, lsp::textdocument::TextDocumentItem};
+
+    /// Applies the code action titled `title` on the first occurrence of `needle`.
+    fn apply(server: &Server, input: &str, needle: &str, title: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (root, _) = parse(&document.text);
+        let offset = TextSize::new(input.find(needle)? as u32);
+        let element = root.covering_element(TextRange::at(offset, 1.into()));
+        let code_action = code_actions(&element, server, &document)
+            .into_iter()
+            .find(|code_action| code_action.title == title)?;
+        let edits = code_action.edit.changes?.remove("query.rq")?;
+        document.apply_text_edits(edits);
+        Some(document.text)
+    }
+
+    #[test]
+    fn replace_object_with_variable() {
+        let mut server = Server::new(|_message| {});
+        server
+            .state
+            .label_memory
+            .insert("wdt:P19".to_string(), "place of birth".to_string());
+        let input = indoc! {
+            "SELECT ?person WHERE {
+               ?person wdt:P19 wd:Q64 ; wdt:P31 wd:Q5 .
+             }"
+        };
+        assert_eq!(
+            apply(&server, input, "wd:Q64", "Replace with variable").unwrap(),
+            indoc! {
+                "SELECT ?person WHERE {
+                   ?person wdt:P19 ?place_of_birth ; wdt:P31 wd:Q5 .
+                 }
+                 "
+            }
+        );
+        assert_eq!(
+            apply(
+                &server,
+                input,
+                "wd:Q5",
+                "Replace with variable and add to result"
+            )
+            .unwrap(),
+            indoc! {
+                "SELECT ?person ?p31 WHERE {
+                   ?person wdt:P19 wd:Q64 ; wdt:P31 ?p31 .
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn replace_with_fresh_variable() {
+        let server = Server::new(|_message| {});
+        let input = indoc! {
+            r#"SELECT * WHERE {
+                 ?s a <http://example.org/Person> ; <http://example.org/name> ?type .
+                 <http://example.org/alice> <http://example.org/age> 42
+               }"#
+        };
+        assert_eq!(
+            apply(
+                &server,
+                input,
+                "<http://example.org/Person>",
+                "Replace with variable"
+            )
+            .unwrap(),
+            indoc! {
+                r#"SELECT * WHERE {
+                     ?s a ?type1 ; <http://example.org/name> ?type .
+                     <http://example.org/alice> <http://example.org/age> 42
+                   }
+                   "#
+            }
+        );
+        assert_eq!(
+            apply(
+                &server,
+                input,
+                "<http://example.org/alice>",
+                "Replace with variable"
+            )
+            .unwrap(),
+            indoc! {
+                r#"SELECT * WHERE {
+                     ?s a <http://example.org/Person> ; <http://example.org/name> ?type .
+                     ?alice <http://example.org/age> 42
+                   }
+                   "#
+            }
+        );
+        assert_eq!(
+            apply(
+                &server,
+                input,
+                "<http://example.org/name>",
+                "Replace with variable"
+            )
+            .unwrap(),
+            indoc! {
+                r#"SELECT * WHERE {
+                     ?s a <http://example.org/Person> ; ?name ?type .
+                     <http://example.org/alice> <http://example.org/age> 42
+                   }
+                   "#
+            }
+        );
+        // NOTE: `SELECT *` already projects the variable.
+        assert_eq!(
+            apply(
+                &server,
+                input,
+                "42",
+                "Replace with variable and add to result"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn replace_with_variable_not_offered() {
+        let server = Server::new(|_message| {});
+        let input = indoc! {
+            "CONSTRUCT { ?s <p> <o> } WHERE {
+               ?s <q>/<r> ?o FILTER(?o = <c>)
+             }"
+        };
+        for needle in ["<o>", "<q>", "<c>"] {
+            assert_eq!(
+                apply(&server, input, needle, "Replace with variable"),
+                None,
+                "{needle}"
+            );
+        }
+    }
+}
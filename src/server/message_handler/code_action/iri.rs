@@ -1,13 +1,14 @@
 use std::collections::HashSet;
 
-use ll_sparql_parser::ast::AstNode;
+use ll_sparql_parser::{SyntaxElement, SyntaxNode, ast::AstNode, syntax_kind::SyntaxKind};
 
+use super::term::{is_simple_local_name, prefix_declarations, resolve_iri, write_iri};
 use crate::server::{
     Server,
     analysis::{find_all_prefix_declarations, find_all_uncompacted_iris},
     lsp::{
         CodeAction, CodeActionKind,
-        textdocument::{Range, TextEdit},
+        textdocument::{Range, TextDocumentItem, TextEdit},
     },
 };
 
@@ -17,6 +18,74 @@ pub(super) fn code_actions(server: &Server, document_uri: String) -> Vec<CodeAct
         .collect()
 }
 
+/// Convert the selected IRI between `<...>` and a prefixed name.
+pub(super) fn convert(
+    element: &SyntaxElement,
+    server: &Server,
+    document: &TextDocumentItem,
+) -> Option<CodeAction> {
+    let iri = match element {
+        SyntaxElement::Node(node) => node.ancestors().find(is_iri),
+        SyntaxElement::Token(token) => token.parent_ancestors().find(is_iri),
+    }?;
+    let root = iri.ancestors().last()?;
+    let prefixes = prefix_declarations(&root);
+    let range = Range::from_byte_offset_range(iri.text_range(), &document.text)?;
+    let full_iri = match resolve_iri(&iri, &prefixes) {
+        Some(full_iri) => full_iri,
+        None => {
+            let (prefix, local) = iri
+                .text()
+                .to_string()
+                .split_once(':')
+                .map(|(prefix, local)| (prefix.to_string(), local.to_string()))?;
+            let record = server
+                .state
+                .get_default_converter()
+                .find_by_prefix(&prefix)
+                .ok()?;
+            format!("{}{local}", record.uri_prefix)
+        }
+    };
+    if iri.first_token()?.kind() != SyntaxKind::IRIREF {
+        let mut code_action =
+            CodeAction::new("Convert to full IRI", Some(CodeActionKind::RefactorRewrite));
+        code_action.add_edit(
+            &document.uri,
+            TextEdit::new(range, &format!("<{full_iri}>")),
+        );
+        return Some(code_action);
+    }
+    let mut code_action = CodeAction::new(
+        "Convert to prefixed name",
+        Some(CodeActionKind::RefactorRewrite),
+    );
+    let declared = write_iri(&full_iri, &prefixes);
+    if !declared.starts_with('<') {
+        code_action.add_edit(&document.uri, TextEdit::new(range, &declared));
+        return Some(code_action);
+    }
+    let (prefix, uri_prefix, curie) = server.shorten_uri(&full_iri, None)?;
+    if !is_simple_local_name(&full_iri[uri_prefix.len()..])
+        || prefixes.iter().any(|(declared, _)| *declared == prefix)
+    {
+        return None;
+    }
+    code_action.add_edit(&document.uri, TextEdit::new(range, &curie));
+    code_action.add_edit(
+        &document.uri,
+        TextEdit::new(
+            Range::new(0, 0, 0, 0),
+            &format!("PREFIX {}: <{}>\n", prefix, uri_prefix),
+        ),
+    );
+    Some(code_action)
+}
+
+fn is_iri(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::iri
+}
+
 // TODO: Handle errors properly.
 fn shorten_all_uris(server: &Server, document_uri: &String) -> Option<CodeAction> {
     let mut code_action = CodeAction::new("Shorten all URIs", Some(CodeActionKind::Refactor));
@@ -67,7 +136,7 @@ mod test {
         Server,
         configuration::BackendConfiguration,
         lsp::textdocument::{Range, TextDocumentItem, TextEdit},
-        message_handler::code_action::iri::shorten_all_uris,
+        message_handler::code_action::iri::{convert, shorten_all_uris},
         state::ServerState,
     };
     use indoc::indoc;
@@ -83,6 +152,7 @@ mod test {
             prefix_map: HashMap::new(),
             default: true,
             queries: HashMap::new(),
+            files: Vec::new(),
             additional_data: None,
         });
         state.set_default_backend("test".to_string());
@@ -141,4 +211,40 @@ mod test {
             ]
         );
     }
+
+    fn convert_at(server: &Server, needle: &str) -> Option<Vec<TextEdit>> {
+        let document = server.state.get_document("uri").unwrap();
+        let (tree, _) = ll_sparql_parser::parse(&document.text);
+        let offset = document.text.find(needle).unwrap() as u32;
+        let element = tree.covering_element(text_size::TextRange::at(offset.into(), 1.into()));
+        convert(&element, server, document)?
+            .edit
+            .changes
+            .unwrap()
+            .remove("uri")
+    }
+
+    #[test]
+    fn convert_single_iri() {
+        let mut server = Server::new(|_message| {});
+        server.state = setup_state(indoc!(
+            "PREFIX s: <https://schema.org/>
+             SELECT * {
+               ?a <https://schema.org/name> ?b .
+               ?c schema:name <http://example.org/a/b>
+             }"
+        ));
+        assert_eq!(
+            convert_at(&server, "<https://schema.org/name>").unwrap(),
+            vec![TextEdit::new(Range::new(2, 5, 2, 30), "s:name")]
+        );
+        assert_eq!(
+            convert_at(&server, "schema:name").unwrap(),
+            vec![TextEdit::new(
+                Range::new(3, 5, 3, 16),
+                "<https://schema.org/name>"
+            )]
+        );
+        assert_eq!(convert_at(&server, "<http://example.org"), None);
+    }
 }
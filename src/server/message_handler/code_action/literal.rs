@@ -0,0 +1,373 @@
+//! Rewrite literals: switch the quotes of a string and convert between typed
+//! literals like `"5"^^xsd:integer` and their shorthand `5`.
+//!
+//! Escape sequences are kept as they are, only quotes and line breaks are
+//! (un)escaped as the target quotes require.
+
+use std::collections::HashMap;
+
+use ll_sparql_parser::{SyntaxElement, SyntaxNode, syntax_kind::SyntaxKind};
+
+use super::term::{prefix_declarations, resolve_iri, write_iri};
+use crate::server::lsp::{
+    CodeAction, CodeActionKind, WorkspaceEdit,
+    textdocument::{Range, TextDocumentItem, TextEdit},
+};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+pub(super) fn code_actions(
+    element: &SyntaxElement,
+    document: &TextDocumentItem,
+) -> Vec<CodeAction> {
+    let ancestors: Vec<SyntaxNode> = match element {
+        SyntaxElement::Node(node) => node.ancestors().collect(),
+        SyntaxElement::Token(token) => token.parent_ancestors().collect(),
+    };
+    let mut actions = switch_quotes(&ancestors);
+    actions.extend(typed_to_shorthand(&ancestors));
+    actions.extend(shorthand_to_typed(&ancestors));
+    actions
+        .into_iter()
+        .filter_map(|(title, node, new_text)| {
+            Some(CodeAction {
+                title,
+                kind: Some(CodeActionKind::RefactorRewrite),
+                diagnostics: vec![],
+                edit: WorkspaceEdit {
+                    changes: Some(HashMap::from_iter([(
+                        document.uri.to_string(),
+                        vec![TextEdit::new(
+                            Range::from_byte_offset_range(node.text_range(), &document.text)?,
+                            &new_text,
+                        )],
+                    )])),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Delimiter and "is long" of each string token kind.
+fn quotes(kind: SyntaxKind) -> Option<(char, bool)> {
+    match kind {
+        SyntaxKind::STRING_LITERAL1 => Some(('\'', false)),
+        SyntaxKind::STRING_LITERAL2 => Some(('"', false)),
+        SyntaxKind::STRING_LITERAL_LONG1 => Some(('\'', true)),
+        SyntaxKind::STRING_LITERAL_LONG2 => Some(('"', true)),
+        _ => None,
+    }
+}
+
+/// A piece of string content: a character or an escape sequence that is kept.
+#[derive(Debug, PartialEq)]
+enum Piece {
+    Char(char),
+    Escape(String),
+}
+
+/// Splits the content of a string token, escaped quotes become plain characters.
+fn pieces(content: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut chars = content.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            pieces.push(Piece::Char(char));
+            continue;
+        }
+        match chars.next() {
+            Some(quote @ ('"' | '\'')) => pieces.push(Piece::Char(quote)),
+            Some(escaped) => pieces.push(Piece::Escape(format!("\\{escaped}"))),
+            None => pieces.push(Piece::Escape("\\".to_string())),
+        }
+    }
+    pieces
+}
+
+/// Writes `pieces` as a string with the given quotes.
+fn write_string(pieces: &[Piece], quote: char, long: bool) -> String {
+    let delimiter = if long {
+        quote.to_string().repeat(3)
+    } else {
+        quote.to_string()
+    };
+    let mut content = String::new();
+    // NOTE: a long string may contain up to two unescaped quotes in a row,
+    // but must not end with one.
+    let mut run = 0;
+    for (idx, piece) in pieces.iter().enumerate() {
+        match piece {
+            Piece::Char(char) if *char == quote => {
+                if !long || run == 2 || idx + 1 == pieces.len() {
+                    content.push('\\');
+                    run = 0;
+                } else {
+                    run += 1;
+                }
+                content.push(*char);
+            }
+            Piece::Char('\n') if !long => content.push_str("\\n"),
+            Piece::Char('\r') if !long => content.push_str("\\r"),
+            Piece::Char(char) => {
+                content.push(*char);
+                run = 0;
+            }
+            Piece::Escape(escape) => {
+                content.push_str(escape);
+                run = 0;
+            }
+        }
+    }
+    format!("{delimiter}{content}{delimiter}")
+}
+
+fn switch_quotes(ancestors: &[SyntaxNode]) -> Vec<(String, SyntaxNode, String)> {
+    let Some(string) = ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::String)
+    else {
+        return vec![];
+    };
+    let Some(token) = string.first_token() else {
+        return vec![];
+    };
+    let Some((quote, long)) = quotes(token.kind()) else {
+        return vec![];
+    };
+    let delimiter_len = if long { 3 } else { 1 };
+    let text = token.text();
+    if text.len() < 2 * delimiter_len {
+        return vec![];
+    }
+    let pieces = pieces(&text[delimiter_len..text.len() - delimiter_len]);
+    [('"', false), ('\'', false), ('"', true), ('\'', true)]
+        .into_iter()
+        .filter(|target| *target != (quote, long))
+        .map(|(quote, long)| {
+            let delimiter = if long {
+                quote.to_string().repeat(3)
+            } else {
+                quote.to_string()
+            };
+            (
+                format!("Convert to {delimiter}…{delimiter}"),
+                string.clone(),
+                write_string(&pieces, quote, long),
+            )
+        })
+        .collect()
+}
+
+/// Whether `value` is the lexical form of a shorthand literal of `datatype`.
+fn is_shorthand(value: &str, datatype: &str) -> bool {
+    let unsigned = value.trim_start_matches(['+', '-']);
+    let digits = |text: &str| !text.is_empty() && text.chars().all(|char| char.is_ascii_digit());
+    let decimal = |text: &str| {
+        text.split_once('.').is_some_and(|(integer, fraction)| {
+            (integer.is_empty() || digits(integer)) && digits(fraction)
+        })
+    };
+    if unsigned.len() + 1 < value.len() {
+        return false;
+    }
+    match datatype {
+        "integer" => digits(unsigned),
+        "decimal" => decimal(unsigned),
+        "double" => unsigned
+            .split_once(['e', 'E'])
+            .is_some_and(|(mantissa, exponent)| {
+                (digits(mantissa)
+                    || decimal(mantissa)
+                    || mantissa.strip_suffix('.').is_some_and(digits))
+                    && digits(exponent.trim_start_matches(['+', '-']))
+                    && exponent.trim_start_matches(['+', '-']).len() + 1 >= exponent.len()
+            }),
+        "boolean" => value == "true" || value == "false",
+        _ => false,
+    }
+}
+
+fn typed_to_shorthand(ancestors: &[SyntaxNode]) -> Option<(String, SyntaxNode, String)> {
+    let literal = ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::RDFLiteral)?;
+    let root = literal.ancestors().last()?;
+    let string = literal.first_child()?.first_token()?;
+    let (_, long) = quotes(string.kind())?;
+    let delimiter_len = if long { 3 } else { 1 };
+    let value = string
+        .text()
+        .get(delimiter_len..string.text().len().checked_sub(delimiter_len)?)?;
+    let datatype = literal
+        .children()
+        .find(|child| child.kind() == SyntaxKind::iri)?;
+    let datatype = resolve_iri(&datatype, &prefix_declarations(&root))?;
+    let datatype = datatype.strip_prefix(XSD)?;
+    is_shorthand(value, datatype).then(|| {
+        let title = if datatype == "boolean" {
+            "Convert to boolean"
+        } else {
+            "Convert to number"
+        };
+        (title.to_string(), literal.clone(), value.to_string())
+    })
+}
+
+fn shorthand_to_typed(ancestors: &[SyntaxNode]) -> Option<(String, SyntaxNode, String)> {
+    let literal = ancestors.iter().find(|node| {
+        matches!(
+            node.kind(),
+            SyntaxKind::NumericLiteral | SyntaxKind::BooleanLiteral
+        )
+    })?;
+    let datatype = match literal.last_token()?.kind() {
+        SyntaxKind::INTEGER | SyntaxKind::INTEGER_POSITIVE | SyntaxKind::INTEGER_NEGATIVE => {
+            "integer"
+        }
+        SyntaxKind::DECIMAL | SyntaxKind::DECIMAL_POSITIVE | SyntaxKind::DECIMAL_NEGATIVE => {
+            "decimal"
+        }
+        SyntaxKind::DOUBLE | SyntaxKind::DOUBLE_POSITIVE | SyntaxKind::DOUBLE_NEGATIVE => "double",
+        SyntaxKind::True | SyntaxKind::False => "boolean",
+        _ => return None,
+    };
+    let root = literal.ancestors().last()?;
+    Some((
+        "Convert to typed literal".to_string(),
+        literal.clone(),
+        format!(
+            "\"{}\"^^{}",
+            literal.text(),
+            write_iri(&format!("{XSD}{datatype}"), &prefix_declarations(&root))
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::{Piece, code_actions, is_shorthand, pieces, write_string};
+    use crate::server::lsp::textdocument::TextDocumentItem;
+
+    fn convert(input: &str, needle: &str, title: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let start = input.find(needle).unwrap();
+        let range = TextRange::at(
+            TextSize::new(start as u32),
+            TextSize::new(needle.len() as u32),
+        );
+        let action = code_actions(&tree.covering_element(range), &document)
+            .into_iter()
+            .find(|action| action.title == title)?;
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+        Some(document.text)
+    }
+
+    #[test]
+    fn escape_quotes() {
+        let content = pieces(r#"it's \"ok\"\n"#);
+        assert_eq!(content[2], Piece::Char('\''));
+        assert_eq!(content.last(), Some(&Piece::Escape("\\n".to_string())));
+        assert_eq!(write_string(&content, '\'', false), r#"'it\'s "ok"\n'"#);
+        assert_eq!(write_string(&content, '"', true), r#""""it's "ok"\n""""#);
+        assert_eq!(
+            write_string(&pieces(r#"a\"\"\"b\""#), '"', true),
+            r#""""a""\"b\"""""#
+        );
+        assert_eq!(
+            write_string(&pieces("line\nbreak"), '"', false),
+            r#""line\nbreak""#
+        );
+    }
+
+    #[test]
+    fn switch_quotes() {
+        assert_eq!(
+            convert(
+                r#"SELECT * WHERE { ?s ?p "it's" }"#,
+                "it's",
+                "Convert to '…'"
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s ?p 'it\\'s' }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s ?p '''a\n\"b\"''' }",
+                "a\n",
+                "Convert to \"…\""
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s ?p \"a\\n\\\"b\\\"\" }\n"
+        );
+    }
+
+    #[test]
+    fn shorthand() {
+        assert!(is_shorthand("5", "integer"));
+        assert!(is_shorthand("-5", "integer"));
+        assert!(!is_shorthand("+-5", "integer"));
+        assert!(!is_shorthand("5.0", "integer"));
+        assert!(is_shorthand(".5", "decimal"));
+        assert!(!is_shorthand("5.", "decimal"));
+        assert!(is_shorthand("1.5e-3", "double"));
+        assert!(!is_shorthand("1.5", "double"));
+        assert!(is_shorthand("true", "boolean"));
+        assert!(!is_shorthand("1", "boolean"));
+    }
+
+    #[test]
+    fn typed_literal_to_shorthand_and_back() {
+        let input = "PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>\nSELECT * WHERE { ?s ?p \"5\"^^xsd:integer }";
+        assert_eq!(
+            convert(input, "xsd:integer", "Convert to number").unwrap(),
+            "PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>\nSELECT * WHERE { ?s ?p 5 }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s ?p \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> }",
+                "true",
+                "Convert to boolean"
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s ?p true }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s ?p \"five\"^^xsd:integer }",
+                "five",
+                "Convert to number"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>\nSELECT * WHERE { ?s ?p 1.5 }",
+                "1.5",
+                "Convert to typed literal"
+            )
+            .unwrap(),
+            "PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>\nSELECT * WHERE { ?s ?p \"1.5\"^^xsd:decimal }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s ?p false }",
+                "false",
+                "Convert to typed literal"
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s ?p \"false\"^^<http://www.w3.org/2001/XMLSchema#boolean> }\n"
+        );
+    }
+}
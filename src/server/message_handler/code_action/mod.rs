@@ -1,12 +1,22 @@
+mod blocks;
+mod constant;
 mod iri;
+mod literal;
+pub(crate) mod organize_prefixes;
+mod path;
+mod query_form;
 mod quickfix;
 pub(crate) mod same_subject;
 mod select;
+mod sub_select;
+mod term;
+mod values;
 mod variable;
+mod wrap;
 use crate::server::{
     Server,
     lsp::{
-        CodeAction, CodeActionParams, CodeActionRequest, CodeActionResponse,
+        CodeAction, CodeActionKind, CodeActionParams, CodeActionRequest, CodeActionResponse,
         diagnostic::Diagnostic,
         errors::{ErrorCode, LSPError},
     },
@@ -30,8 +40,13 @@ pub(super) async fn handle_codeaction_request(
 ) -> Result<(), LSPError> {
     let mut server = server_rc.lock().await;
     let mut code_action_response = CodeActionResponse::new(request.get_id());
-    code_action_response.add_code_actions(generate_code_actions(&mut server, &request.params)?);
-    code_action_response.add_code_actions(generate_quickfixes(&mut server, request));
+    let context = &request.params.context;
+    let mut code_actions = generate_code_actions(&mut server, &request.params)?;
+    code_actions.retain(|code_action| context.requests(code_action.kind.as_ref()));
+    code_action_response.add_code_actions(code_actions);
+    if context.requests(Some(&CodeActionKind::QuickFix)) {
+        code_action_response.add_code_actions(generate_quickfixes(&mut server, request));
+    }
     server.send_message(code_action_response)
 }
 
@@ -72,7 +87,23 @@ fn generate_code_actions(
         ))?;
 
     let selected_element: SyntaxElement = root.covering_element(range);
-    let mut code_actions = vec![];
+    let mut code_actions = query_form::code_actions(
+        &selected_element,
+        document,
+        &server.settings.format.indent_unit(),
+    );
+    code_actions.extend(wrap::unwrap(&selected_element, document));
+    code_actions.extend(path::expand(&selected_element, document));
+    code_actions.extend(values::code_actions(&selected_element, document));
+    code_actions.extend(literal::code_actions(&selected_element, document));
+    code_actions.extend(term::code_actions(&selected_element, document));
+    code_actions.extend(iri::convert(&selected_element, server, document));
+    code_actions.extend(constant::code_actions(&selected_element, server, document));
+    code_actions.extend(blocks::code_actions(
+        &selected_element,
+        document,
+        &server.settings.format.indent_unit(),
+    ));
     if selected_element
         .parent()
         .and_then(Iri::cast)
@@ -85,13 +116,33 @@ fn generate_code_actions(
         selected_element.kind(),
         SyntaxKind::SelectQuery | SyntaxKind::SELECT | SyntaxKind::SubSelect
     ) {
+        code_actions.extend(sub_select::inline(&selected_element, document));
         code_actions.extend(select::code_actions(
             selected_element,
             document,
             server.settings.format.tab_size.unwrap_or(2),
         ));
     }
+    code_actions.extend(sub_select::extract(
+        &root,
+        range,
+        document,
+        &server.settings.format.indent_unit(),
+    ));
+    code_actions.extend(path::collapse(&root, range, document));
+    code_actions.extend(wrap::wrap(
+        &root,
+        range,
+        document,
+        server,
+        &server.settings.format.indent_unit(),
+    ));
     // NOTE: Global code actions
+    code_actions.extend(organize_prefixes::code_action(
+        server,
+        document,
+        root.clone(),
+    ));
     if let Some(code_action) =
         same_subject::contract_all_triple_groups(document, root, &server.settings.format)?
     {
@@ -0,0 +1,465 @@
+//! "Organize prefixes": rewrites the prefix declarations of a prologue in one go.
+//!
+//! Unused and duplicate declarations are dropped, used but undeclared prefixes
+//! are declared if the converter of the backend they are used with knows them,
+//! and the result is sorted by `prefixes.order` (then alphabetically) and
+//! aligned per `format.align_prefixes`.
+//! Comments stay with the declaration they precede or trail.
+//! In an update only the prologue of the first operation is organized.
+
+use std::collections::{HashMap, HashSet};
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, PrefixedName, Prologue, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+use text_size::{TextRange, TextSize};
+use unicode_width::UnicodeWidthStr;
+
+use crate::server::{
+    Server,
+    lsp::{
+        CodeAction, CodeActionKind, WorkspaceEdit,
+        textdocument::{Range, TextDocumentItem, TextEdit},
+    },
+    message_handler::{common::used_prefixes, misc::resolve_backend_at_token},
+};
+
+pub(super) fn code_action(
+    server: &Server,
+    document: &TextDocumentItem,
+    root: SyntaxNode,
+) -> Option<CodeAction> {
+    let query_unit = QueryUnit::cast(root.clone());
+    let namespace = |prefixed_name: &PrefixedName| {
+        // NOTE: backends can only be resolved in queries.
+        let backend = query_unit
+            .as_ref()
+            .zip(prefixed_name.syntax().first_token())
+            .and_then(|(query_unit, token)| resolve_backend_at_token(server, query_unit, &token));
+        backend
+            .and_then(|backend| server.state.get_converter(&backend.name))
+            .unwrap_or_else(|| server.state.get_default_converter())
+            .find_by_prefix(&prefixed_name.prefix())
+            .ok()
+            .map(|record| record.uri_prefix.clone())
+    };
+    let edit = organize_prefixes(
+        document,
+        root,
+        namespace,
+        server
+            .settings
+            .prefixes
+            .as_ref()
+            .and_then(|prefixes| prefixes.order.as_deref())
+            .unwrap_or_default(),
+        server.settings.format.align_prefixes,
+    )?;
+    Some(CodeAction {
+        title: "Organize prefixes".to_string(),
+        kind: Some(CodeActionKind::SourceOrganizeImports),
+        diagnostics: vec![],
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(document.uri.to_string(), vec![edit])])),
+        },
+    })
+}
+
+/// A prefix declaration with the comments that belong to it.
+struct Declaration {
+    prefix: String,
+    iri: String,
+    leading_comments: Vec<String>,
+    trailing_comment: Option<String>,
+}
+
+/// Computes the edit that organizes the prologue of `root`.
+/// `namespace` looks up the namespace of a prefixed name whose prefix is not declared.
+///
+/// Returns `None` if the prologue is already organized, if `root` is neither a
+/// query nor an update or if a `BASE` declaration sits between the prefix
+/// declarations, since moving a relative prefix IRI across it would change its
+/// meaning.
+pub(crate) fn organize_prefixes(
+    document: &TextDocumentItem,
+    root: SyntaxNode,
+    namespace: impl Fn(&PrefixedName) -> Option<String>,
+    order: &[String],
+    align: bool,
+) -> Option<TextEdit> {
+    let used_prefixes = used_prefixes(&root);
+    let prologue = match root.kind() {
+        SyntaxKind::QueryUnit => QueryUnit::cast(root.clone())?.prologue(),
+        SyntaxKind::UpdateUnit => root
+            .first_child()
+            .and_then(|update| update.children().find_map(Prologue::cast)),
+        _ => return None,
+    };
+    // NOTE: prefixes declared by a later operation of an update are not missing.
+    let declared_later: HashSet<String> = root
+        .descendants()
+        .filter_map(Prologue::cast)
+        .filter(|other| {
+            prologue
+                .as_ref()
+                .is_none_or(|prologue| prologue.syntax() != other.syntax())
+        })
+        .flat_map(|other| other.prefix_declarations())
+        .filter_map(|declaration| declaration.prefix())
+        .collect();
+    let prefix_decls: Vec<SyntaxNode> = prologue
+        .iter()
+        .flat_map(|prologue| prologue.syntax().children())
+        .filter(|child| child.kind() == SyntaxKind::PrefixDecl)
+        .collect();
+
+    let mut declarations = match (prefix_decls.first(), prefix_decls.last()) {
+        (Some(first), Some(last)) => collect_declarations(
+            prologue.as_ref()?.syntax(),
+            TextRange::new(first.text_range().start(), last.text_range().end()),
+        )?,
+        _ => vec![],
+    };
+
+    // NOTE: the first declaration of a prefix wins, later ones are duplicates.
+    let mut seen = HashSet::new();
+    declarations.retain(|declaration| {
+        used_prefixes.contains(&declaration.prefix) && seen.insert(declaration.prefix.clone())
+    });
+    let mut missing: Vec<&String> = used_prefixes
+        .iter()
+        .filter(|prefix| !seen.contains(*prefix) && !declared_later.contains(*prefix))
+        .collect();
+    missing.sort();
+    declarations.extend(missing.into_iter().filter_map(|prefix| {
+        let first_use = root
+            .descendants()
+            .filter_map(PrefixedName::cast)
+            .find(|prefixed_name| prefixed_name.prefix() == *prefix)?;
+        Some(Declaration {
+            prefix: prefix.clone(),
+            iri: format!("<{}>", namespace(&first_use)?),
+            leading_comments: vec![],
+            trailing_comment: None,
+        })
+    }));
+    declarations.sort_by_key(|declaration| {
+        (
+            order
+                .iter()
+                .position(|prefix| *prefix == declaration.prefix)
+                .unwrap_or(order.len()),
+            declaration.prefix.clone(),
+        )
+    });
+
+    let width = declarations
+        .iter()
+        .map(|declaration| declaration.prefix.width())
+        .max()
+        .unwrap_or(0);
+    let text = declarations
+        .iter()
+        .flat_map(|declaration| {
+            let padding = if align {
+                " ".repeat(width - declaration.prefix.width())
+            } else {
+                String::new()
+            };
+            let mut lines = declaration.leading_comments.clone();
+            lines.push(format!(
+                "PREFIX {}:{padding} {}{}",
+                declaration.prefix,
+                declaration.iri,
+                declaration
+                    .trailing_comment
+                    .as_ref()
+                    .map(|comment| format!(" {comment}"))
+                    .unwrap_or_default()
+            ));
+            lines
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let (range, new_text) = match (prefix_decls.first(), prefix_decls.last()) {
+        (Some(first), Some(last)) => (
+            TextRange::new(first.text_range().start(), last.text_range().end()),
+            text,
+        ),
+        _ if text.is_empty() => return None,
+        // NOTE: a prologue without prefixes may still hold a BASE declaration.
+        _ => match prologue.filter(|prologue| prologue.syntax().first_child().is_some()) {
+            Some(prologue) => (
+                TextRange::empty(prologue.syntax().text_range().end()),
+                format!("\n{text}"),
+            ),
+            None => (TextRange::empty(TextSize::new(0)), format!("{text}\n")),
+        },
+    };
+    let old_text = &document.text[range];
+    (old_text != new_text).then(|| {
+        TextEdit::new(
+            Range::from_byte_offset_range(range, &document.text)
+                .expect("prologue range should be inside the document"),
+            &new_text,
+        )
+    })
+}
+
+/// Collects the prefix declarations of `prologue` in `range`, attaching each
+/// comment to the declaration it trails on the same line, or otherwise to the
+/// next declaration.
+fn collect_declarations(prologue: &SyntaxNode, range: TextRange) -> Option<Vec<Declaration>> {
+    let mut declarations: Vec<Declaration> = Vec::new();
+    let mut leading_comments = Vec::new();
+    let mut same_line = false;
+    for element in prologue
+        .children_with_tokens()
+        .filter(|element| range.contains_range(element.text_range()))
+    {
+        match element {
+            SyntaxElement::Node(node) if node.kind() == SyntaxKind::PrefixDecl => {
+                let tokens: Vec<_> = node
+                    .children_with_tokens()
+                    .filter_map(|child| child.into_token())
+                    .collect();
+                let token_text = |kind: SyntaxKind| {
+                    tokens
+                        .iter()
+                        .find(|token| token.kind() == kind)
+                        .map(|token| token.text().to_string())
+                };
+                declarations.push(Declaration {
+                    prefix: token_text(SyntaxKind::PNAME_NS)?
+                        .trim_end_matches(':')
+                        .to_string(),
+                    iri: token_text(SyntaxKind::IRIREF)?,
+                    leading_comments: std::mem::take(&mut leading_comments),
+                    trailing_comment: None,
+                });
+                same_line = true;
+            }
+            SyntaxElement::Node(_) => return None,
+            SyntaxElement::Token(token) if token.kind() == SyntaxKind::Comment => {
+                match declarations.last_mut() {
+                    Some(declaration) if same_line => {
+                        declaration.trailing_comment = Some(token.text().to_string())
+                    }
+                    _ => leading_comments.push(token.text().to_string()),
+                }
+            }
+            SyntaxElement::Token(token) => {
+                if token.text().contains('\n') {
+                    same_line = false;
+                }
+            }
+        }
+    }
+    Some(declarations)
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+
+    use std::collections::HashMap;
+
+    use super::{code_action, organize_prefixes};
+    use crate::server::{
+        Server, configuration::BackendConfiguration, lsp::textdocument::TextDocumentItem,
+        prefix_catalog::catalog_converter,
+    };
+
+    fn organize(input: &str, order: &[&str], align: bool) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (root, _) = parse(&document.text);
+        let order: Vec<String> = order.iter().map(|prefix| prefix.to_string()).collect();
+        let catalog = catalog_converter();
+        let edit = organize_prefixes(
+            &document,
+            root,
+            |prefixed_name| {
+                catalog
+                    .find_by_prefix(&prefixed_name.prefix())
+                    .ok()
+                    .map(|record| record.uri_prefix.clone())
+            },
+            &order,
+            align,
+        )?;
+        document.apply_text_edits(vec![edit]);
+        Some(document.text)
+    }
+
+    #[test]
+    fn organize_prefixes_removes_sorts_and_adds() {
+        let input = indoc! {
+            "PREFIX wdt: <http://www.wikidata.org/prop/direct/>
+             PREFIX unused: <http://example.org/unused/>
+             PREFIX wd: <http://www.wikidata.org/entity/>
+             PREFIX wd: <http://www.wikidata.org/entity/>
+             SELECT * WHERE {
+               ?s wdt:P31 wd:Q5 ; rdfs:label ?label
+             }
+             "
+        };
+        assert_eq!(
+            organize(input, &[], false).unwrap(),
+            indoc! {
+                "PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>
+                 PREFIX wd: <http://www.wikidata.org/entity/>
+                 PREFIX wdt: <http://www.wikidata.org/prop/direct/>
+                 SELECT * WHERE {
+                   ?s wdt:P31 wd:Q5 ; rdfs:label ?label
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn organize_prefixes_with_order_and_alignment() {
+        let input = indoc! {
+            "PREFIX a: <a>
+             PREFIX long: <long>
+             PREFIX b: <b>
+             SELECT * WHERE { ?s a:p long:o ; b:p ?o }
+             "
+        };
+        assert_eq!(
+            organize(input, &["long", "b"], true).unwrap(),
+            indoc! {
+                "PREFIX long: <long>
+                 PREFIX b:    <b>
+                 PREFIX a:    <a>
+                 SELECT * WHERE { ?s a:p long:o ; b:p ?o }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn organize_prefixes_keeps_comments() {
+        let input = indoc! {
+            "# header
+             PREFIX b: <b> # trailing
+             # leading
+             PREFIX a: <a>
+             SELECT * WHERE { ?s a:p b:o }
+             "
+        };
+        assert_eq!(
+            organize(input, &[], false).unwrap(),
+            indoc! {
+                "# header
+                 # leading
+                 PREFIX a: <a>
+                 PREFIX b: <b> # trailing
+                 SELECT * WHERE { ?s a:p b:o }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn organize_prefixes_without_prologue() {
+        assert_eq!(
+            organize("SELECT * WHERE { ?s foaf:name ?o }", &[], false).unwrap(),
+            "PREFIX foaf: <http://xmlns.com/foaf/0.1/>\nSELECT * WHERE { ?s foaf:name ?o }\n"
+        );
+        assert_eq!(
+            organize("PREFIX a: <a>\nSELECT * WHERE { ?s a:p ?o }", &[], false),
+            None
+        );
+    }
+
+    #[test]
+    fn organize_prefixes_of_update() {
+        let input = indoc! {
+            "PREFIX b: <b>
+             PREFIX a: <a>
+             INSERT DATA { a:s b:p foaf:o }
+             "
+        };
+        assert_eq!(
+            organize(input, &[], false).unwrap(),
+            indoc! {
+                "PREFIX a: <a>
+                 PREFIX b: <b>
+                 PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+                 INSERT DATA { a:s b:p foaf:o }
+                 "
+            }
+        );
+    }
+
+    fn backend(name: &str, url: &str) -> BackendConfiguration {
+        BackendConfiguration {
+            name: name.to_string(),
+            url: url.to_string(),
+            health_check_url: None,
+            engine: None,
+            request_method: None,
+            prefix_map: HashMap::new(),
+            default: false,
+            queries: HashMap::new(),
+            files: Vec::new(),
+            additional_data: None,
+        }
+    }
+
+    #[test]
+    fn organize_prefixes_declares_with_service_backend() {
+        let mut server = Server::new(|_message| {});
+        for (name, url, prefix, namespace) in [
+            (
+                "wikidata",
+                "https://wikidata",
+                "wd",
+                "http://www.wikidata.org/entity/",
+            ),
+            (
+                "osm",
+                "https://osm",
+                "osmkey",
+                "https://www.openstreetmap.org/wiki/Key:",
+            ),
+        ] {
+            server.state.add_backend(backend(name, url));
+            server
+                .state
+                .load_prefix_map(
+                    name.to_string(),
+                    &HashMap::from([(prefix.to_string(), namespace.to_string())]),
+                )
+                .unwrap();
+        }
+        server.state.set_default_backend("wikidata".to_string());
+        let input = "SELECT * WHERE { ?s ?p wd:Q1 SERVICE <https://osm> { ?s osmkey:name ?o } }";
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (root, _) = parse(&document.text);
+        let action = code_action(&server, &document, root).unwrap();
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+        assert_eq!(
+            document.text,
+            indoc! {
+                "PREFIX osmkey: <https://www.openstreetmap.org/wiki/Key:>
+                 PREFIX wd: <http://www.wikidata.org/entity/>
+                 SELECT * WHERE { ?s ?p wd:Q1 SERVICE <https://osm> { ?s osmkey:name ?o } }
+                 "
+            }
+        );
+    }
+}
@@ -0,0 +1,475 @@
+//! Expand a property path into triples and collapse a chain of triples into a
+//! property path.
+//!
+//! Sequences and inverse paths are expanded into triples with fresh
+//! intermediate variables, e.g. `?a wdt:P31/wdt:P279* ?c` becomes
+//! `?a wdt:P31 ?node . ?node wdt:P279* ?c`. Alternatives and elements with a
+//! modifier have no triple equivalent and are kept as they are. Under
+//! `SELECT *` the intermediate variables would become part of the result, so
+//! such paths are not expanded there.
+//! Collapsing only joins triples through variables that are used nowhere else.
+
+use std::collections::HashMap;
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, SelectQuery, Triple, Var},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use super::{sub_select::line_indentation, wrap::fresh_variables};
+use crate::server::{
+    lsp::{
+        CodeAction, CodeActionKind, WorkspaceEdit,
+        textdocument::{Range, TextDocumentItem, TextEdit},
+    },
+    message_handler::common::find_variable_occurrences,
+};
+
+/// Subject, verb and object of a triple with a single property and object.
+pub(super) struct SimpleTriple {
+    pub(super) syntax: SyntaxNode,
+    pub(super) subject: SyntaxNode,
+    pub(super) verb: SyntaxNode,
+    pub(super) object: SyntaxNode,
+}
+
+impl SimpleTriple {
+    pub(super) fn cast(triple: Triple) -> Option<Self> {
+        let subject = triple.subject()?.syntax().clone();
+        let [property] = triple
+            .properties_list_path()?
+            .properties()
+            .try_into()
+            .ok()?;
+        let mut objects = property.object?.syntax().children();
+        // NOTE: ObjectPath > GraphNodePath > VarOrTerm
+        let object = objects.next()?.first_child()?.first_child()?;
+        if objects.next().is_some()
+            || subject.kind() != SyntaxKind::VarOrTerm
+            || object.kind() != SyntaxKind::VarOrTerm
+        {
+            return None;
+        }
+        Some(Self {
+            syntax: triple.syntax().clone(),
+            subject,
+            verb: property.verb.syntax().clone(),
+            object,
+        })
+    }
+}
+
+pub(super) fn expand(element: &SyntaxElement, document: &TextDocumentItem) -> Option<CodeAction> {
+    let verb = match element {
+        SyntaxElement::Node(node) => node.ancestors().find(is_verb_path),
+        SyntaxElement::Token(token) => token.parent_ancestors().find(is_verb_path),
+    }?;
+    let triple = SimpleTriple::cast(verb.parent()?.parent().and_then(Triple::cast)?)?;
+    let root = triple.syntax.ancestors().last()?;
+    let mut fresh = fresh_variables(&root, "node");
+    let mut introduces_variables = false;
+    let mut triples = vec![];
+    expand_path(
+        &triple.subject.text().to_string(),
+        &verb,
+        &triple.object.text().to_string(),
+        &mut || {
+            introduces_variables = true;
+            format!("?{}", fresh.next().expect("there are always unused names"))
+        },
+        &mut triples,
+    );
+    if introduces_variables && is_star_selected(&triple.syntax) {
+        return None;
+    }
+    if triples.len() < 2
+        && triples
+            .first()
+            .is_none_or(|(subject, _, _)| triple.subject.text() == subject.as_str())
+    {
+        return None;
+    }
+    let base = line_indentation(&document.text, triple.syntax.text_range().start());
+    let new_text = triples
+        .iter()
+        .map(|(subject, verb, object)| format!("{subject} {verb} {object}"))
+        .collect::<Vec<_>>()
+        .join(&format!(" .\n{base}"));
+    code_action(
+        "Expand Property Path",
+        document,
+        triple.syntax.text_range(),
+        new_text,
+    )
+}
+
+/// Whether the variables of `node` are projected by a `SELECT *`.
+pub(super) fn is_star_selected(node: &SyntaxNode) -> bool {
+    node.ancestors()
+        .find_map(SelectQuery::cast)
+        .and_then(|select_query| select_query.select_clause())
+        .is_some_and(|select_clause| select_clause.is_star_selection())
+}
+
+fn is_verb_path(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::VerbPath
+}
+
+/// Appends the triples that are equivalent to `subject path object`.
+fn expand_path(
+    subject: &str,
+    path: &SyntaxNode,
+    object: &str,
+    fresh: &mut dyn FnMut() -> String,
+    triples: &mut Vec<(String, String, String)>,
+) {
+    let children: Vec<SyntaxNode> = path.children().collect();
+    match (path.kind(), children.as_slice()) {
+        (SyntaxKind::VerbPath | SyntaxKind::Path | SyntaxKind::PathAlternative, [child]) => {
+            expand_path(subject, child, object, fresh, triples)
+        }
+        (SyntaxKind::PathSequence, [first, rest @ ..]) => {
+            let mut start = subject.to_string();
+            for (idx, element) in std::iter::once(first).chain(rest).enumerate() {
+                let end = if idx == rest.len() {
+                    object.to_string()
+                } else {
+                    fresh()
+                };
+                expand_path(&start, element, &end, fresh, triples);
+                start = end;
+            }
+        }
+        (SyntaxKind::PathEltOrInverse, [element]) => {
+            if path
+                .first_token()
+                .is_some_and(|token| token.kind() == SyntaxKind::Zirkumflex)
+            {
+                expand_path(object, element, subject, fresh, triples)
+            } else {
+                expand_path(subject, element, object, fresh, triples)
+            }
+        }
+        // NOTE: an element with a modifier has a second child, the PathMod.
+        (SyntaxKind::PathElt, [primary]) => expand_path(subject, primary, object, fresh, triples),
+        (SyntaxKind::PathPrimary, [inner]) if inner.kind() == SyntaxKind::Path => {
+            expand_path(subject, inner, object, fresh, triples)
+        }
+        _ => triples.push((
+            subject.to_string(),
+            path.text().to_string(),
+            object.to_string(),
+        )),
+    }
+}
+
+pub(super) fn collapse(
+    root: &SyntaxNode,
+    range: TextRange,
+    document: &TextDocumentItem,
+) -> Option<CodeAction> {
+    if range.is_empty() {
+        return None;
+    }
+    let triples: Vec<Triple> = root
+        .descendants()
+        .filter_map(Triple::cast)
+        .filter(|triple| {
+            triple
+                .syntax()
+                .text_range()
+                .intersect(range)
+                .is_some_and(|intersection| !intersection.is_empty())
+        })
+        .collect();
+    if triples.len() < 2 {
+        return None;
+    }
+    let triples_block = triples.first()?.triples_block()?;
+    if triples.iter().any(|triple| {
+        triple
+            .triples_block()
+            .is_none_or(|block| block.syntax() != triples_block.syntax())
+    }) {
+        return None;
+    }
+    let triples = triples
+        .into_iter()
+        .map(SimpleTriple::cast)
+        .collect::<Option<Vec<_>>>()?;
+    if triples
+        .iter()
+        .any(|triple| triple.verb.kind() != SyntaxKind::VerbPath)
+    {
+        return None;
+    }
+
+    // NOTE: the first triple is walked in the direction of the variable it
+    // shares with the second one.
+    let (first, second) = (&triples[0], &triples[1]);
+    let links_to_second = |node: &SyntaxNode| {
+        node.text() == second.subject.text() || node.text() == second.object.text()
+    };
+    let (start, mut link, inverse) = if links_to_second(&first.object) {
+        (&first.subject, &first.object, false)
+    } else if links_to_second(&first.subject) {
+        (&first.object, &first.subject, true)
+    } else {
+        return None;
+    };
+    let mut elements = vec![path_element(&first.verb, inverse)];
+    let mut links = vec![link];
+    for (idx, triple) in triples.iter().enumerate().skip(1) {
+        let (end, inverse) = if triple.subject.text() == link.text() {
+            (&triple.object, false)
+        } else if triple.object.text() == link.text() {
+            (&triple.subject, true)
+        } else {
+            return None;
+        };
+        elements.push(path_element(&triple.verb, inverse));
+        link = end;
+        if idx + 1 < triples.len() {
+            links.push(link);
+        }
+    }
+    if !links.iter().all(|link| is_hidden_variable(link)) {
+        return None;
+    }
+
+    let collapse_range = TextRange::new(
+        triples.first()?.syntax.text_range().start(),
+        triples.last()?.syntax.text_range().end(),
+    );
+    code_action(
+        "Collapse into Property Path",
+        document,
+        collapse_range,
+        format!("{} {} {}", start.text(), elements.join("/"), link.text()),
+    )
+}
+
+/// A variable that occurs exactly in the two triples it links and is not
+/// projected by `SELECT *`.
+pub(super) fn is_hidden_variable(node: &SyntaxNode) -> bool {
+    let Some(var) = node.first_child().and_then(Var::cast) else {
+        return false;
+    };
+    find_variable_occurrences(&var).len() == 2
+        && var
+            .syntax()
+            .ancestors()
+            .find_map(SelectQuery::cast)
+            .and_then(|select_query| select_query.select_clause())
+            .is_some_and(|select_clause| !select_clause.is_star_selection())
+}
+
+/// The text of `verb` as an element of a sequence path.
+fn path_element(verb: &SyntaxNode, inverse: bool) -> String {
+    let text = verb.text().to_string();
+    // NOTE: VerbPath > Path > PathAlternative > PathSequence > PathEltOrInverse
+    let sequences: Vec<SyntaxNode> = verb
+        .first_child()
+        .and_then(|path| path.first_child())
+        .map(|alternative| alternative.children().collect())
+        .unwrap_or_default();
+    let single_element = match sequences.as_slice() {
+        [sequence] => {
+            let elements: Vec<SyntaxNode> = sequence.children().collect();
+            (elements.len() == 1).then(|| elements[0].clone())
+        }
+        _ => None,
+    };
+    match (inverse, single_element) {
+        (false, _) if sequences.len() == 1 => text,
+        (false, _) => format!("({text})"),
+        (true, Some(element)) => match element.first_token() {
+            Some(token) if token.kind() == SyntaxKind::Zirkumflex => element
+                .last_child()
+                .map_or(text, |inner| inner.text().to_string()),
+            _ => format!("^{text}"),
+        },
+        (true, None) => format!("^({text})"),
+    }
+}
+
+fn code_action(
+    title: &str,
+    document: &TextDocumentItem,
+    range: TextRange,
+    new_text: String,
+) -> Option<CodeAction> {
+    Some(CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::RefactorRewrite),
+        diagnostics: vec![],
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(
+                document.uri.to_string(),
+                vec![TextEdit::new(
+                    Range::from_byte_offset_range(range, &document.text)?,
+                    &new_text,
+                )],
+            )])),
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::{collapse, expand};
+    use crate::server::lsp::{CodeAction, textdocument::TextDocumentItem};
+
+    fn apply(document: &mut TextDocumentItem, action: CodeAction) {
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+    }
+
+    fn range_of(text: &str, needle: &str) -> TextRange {
+        let start = text.find(needle).unwrap();
+        TextRange::new(
+            TextSize::new(start as u32),
+            TextSize::new((start + needle.len()) as u32),
+        )
+    }
+
+    fn expand_at(input: &str, needle: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let action = expand(&tree.covering_element(range_of(input, needle)), &document)?;
+        apply(&mut document, action);
+        Some(document.text)
+    }
+
+    fn collapse_at(input: &str, selection: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let action = collapse(&tree, range_of(input, selection), &document)?;
+        apply(&mut document, action);
+        Some(document.text)
+    }
+
+    #[test]
+    fn expand_sequence_path() {
+        let input = indoc! {
+            "SELECT ?a ?c WHERE {
+               ?a <P31>/<P279>* ?c .
+             }
+             "
+        };
+        assert_eq!(
+            expand_at(input, "<P31>").unwrap(),
+            indoc! {
+                "SELECT ?a ?c WHERE {
+                   ?a <P31> ?node .
+                   ?node <P279>* ?c .
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn expand_inverse_and_nested_sequence() {
+        let input = "SELECT ?a ?node WHERE { ?a ^(<p>/<q>)/(<r>|<s>) ?node }";
+        assert_eq!(
+            expand_at(input, "<q>").unwrap(),
+            "SELECT ?a ?node WHERE { ?node1 <p> ?node2 .\n?node2 <q> ?a .\n?node1 <r>|<s> ?node }\n"
+        );
+    }
+
+    #[test]
+    fn expand_under_select_star_only_without_new_variables() {
+        assert_eq!(expand_at("SELECT * WHERE { ?a <p>/<q> ?b }", "<p>"), None);
+        assert_eq!(
+            expand_at("SELECT * WHERE { ?a ^<p> ?b }", "<p>").unwrap(),
+            "SELECT * WHERE { ?b <p> ?a }\n"
+        );
+    }
+
+    #[test]
+    fn expand_not_offered_for_plain_predicates() {
+        assert_eq!(expand_at("SELECT * WHERE { ?a <p> ?b }", "<p>"), None);
+        assert_eq!(expand_at("SELECT * WHERE { ?a <p>* ?b }", "<p>"), None);
+        assert_eq!(
+            expand_at("SELECT * WHERE { ?a <p>/<q> ?b, ?c }", "<p>"),
+            None
+        );
+    }
+
+    #[test]
+    fn collapse_chain() {
+        let input = indoc! {
+            "SELECT ?a ?d WHERE {
+               ?a <P31> ?class .
+               ?class <P279>* ?c .
+               ?d <label> ?c
+             }
+             "
+        };
+        assert_eq!(
+            collapse_at(input, "?a <P31> ?class .\n  ?class").unwrap(),
+            indoc! {
+                "SELECT ?a ?d WHERE {
+                   ?a <P31>/<P279>* ?c .
+                   ?d <label> ?c
+                 }
+                 "
+            }
+        );
+        assert_eq!(
+            collapse_at(input, "<P279>* ?c .\n  ?d <label>").unwrap(),
+            indoc! {
+                "SELECT ?a ?d WHERE {
+                   ?a <P31> ?class .
+                   ?class <P279>*/^<label> ?d
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn collapse_inverse_and_alternatives() {
+        assert_eq!(
+            collapse_at(
+                "SELECT ?a ?c WHERE { ?x <p>|<q> ?a . ?x ^<r> ?c }",
+                "?x <p>|<q> ?a . ?x"
+            )
+            .unwrap(),
+            "SELECT ?a ?c WHERE { ?a ^(<p>|<q>)/^<r> ?c }\n"
+        );
+    }
+
+    #[test]
+    fn collapse_keeps_used_variables() {
+        // NOTE: ?class is projected
+        assert_eq!(
+            collapse_at(
+                "SELECT ?class WHERE { ?a <p> ?class . ?class <q> ?c }",
+                "?a <p> ?class . ?class"
+            ),
+            None
+        );
+        assert_eq!(
+            collapse_at(
+                "SELECT * WHERE { ?a <p> ?class . ?class <q> ?c }",
+                "?a <p> ?class . ?class"
+            ),
+            None
+        );
+    }
+}
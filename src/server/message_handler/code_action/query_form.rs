@@ -0,0 +1,501 @@
+//! Convert a query into another query form, keeping its `WHERE` clause.
+//!
+//! - `SELECT` to `CONSTRUCT`, with a template built from the triple patterns
+//! - `SELECT` to `ASK`
+//! - `SELECT` to `SELECT (COUNT(*) AS ?count)`, or a fresh name if `?count` is used
+//! - `CONSTRUCT`, `ASK` and `DESCRIBE` back to `SELECT`
+
+use std::collections::{HashMap, HashSet};
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, SelectQuery, Triple, Var},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use super::wrap::fresh_variables;
+use crate::server::lsp::{
+    CodeAction, CodeActionKind, WorkspaceEdit,
+    textdocument::{Range, TextDocumentItem, TextEdit},
+};
+
+pub(super) fn code_actions(
+    element: &SyntaxElement,
+    document: &TextDocumentItem,
+    indent: &str,
+) -> Vec<CodeAction> {
+    let query = match element {
+        SyntaxElement::Node(node) => Some(node.clone()),
+        SyntaxElement::Token(token) => token
+            .parent_ancestors()
+            .take(2)
+            .find(|node| is_query_form(node.kind())),
+    };
+    let Some(query) = query.filter(|query| is_query_form(query.kind())) else {
+        return vec![];
+    };
+    let rewrites = match query.kind() {
+        SyntaxKind::SelectQuery => vec![
+            to_construct(&query, document, indent),
+            to_ask(&query),
+            to_count(&query),
+        ],
+        SyntaxKind::ConstructQuery => vec![construct_to_select(&query)],
+        SyntaxKind::AskQuery => vec![ask_to_select(&query)],
+        SyntaxKind::DescribeQuery => vec![describe_to_select(&query)],
+        _ => vec![],
+    };
+    rewrites
+        .into_iter()
+        .flatten()
+        .filter_map(|(title, edits)| {
+            let edits = edits
+                .into_iter()
+                .map(|(range, text)| {
+                    Some(TextEdit::new(
+                        Range::from_byte_offset_range(range, &document.text)?,
+                        &text,
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(CodeAction {
+                title: title.to_string(),
+                kind: Some(CodeActionKind::RefactorRewrite),
+                diagnostics: vec![],
+                edit: WorkspaceEdit {
+                    changes: Some(HashMap::from_iter([(document.uri.to_string(), edits)])),
+                },
+            })
+        })
+        .collect()
+}
+
+type Rewrite = (&'static str, Vec<(TextRange, String)>);
+
+fn is_query_form(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::SelectQuery
+            | SyntaxKind::ConstructQuery
+            | SyntaxKind::AskQuery
+            | SyntaxKind::DescribeQuery
+    )
+}
+
+/// A variable, `a` or a single IRI, but no property path.
+fn is_simple_verb(verb: &SyntaxNode) -> bool {
+    verb.kind() == SyntaxKind::VerbSimple
+        || verb.text() == "a"
+        || verb
+            .descendants()
+            .find(|node| node.kind() == SyntaxKind::iri)
+            .is_some_and(|iri| iri.text_range() == verb.text_range())
+}
+
+fn child(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxNode> {
+    node.children().find(|child| child.kind() == kind)
+}
+
+fn to_construct(query: &SyntaxNode, document: &TextDocumentItem, indent: &str) -> Option<Rewrite> {
+    let select_clause = child(query, SyntaxKind::SelectClause)?;
+    let where_clause = child(query, SyntaxKind::WhereClause)?;
+    // NOTE: triples in sub-selects, MINUS or (NOT) EXISTS do not produce the
+    // solutions of this query, and complex paths are not allowed in templates.
+    let triples: Vec<String> = where_clause
+        .descendants()
+        .filter_map(Triple::cast)
+        .filter(|triple| {
+            !triple.syntax().ancestors().any(|ancestor| {
+                matches!(
+                    ancestor.kind(),
+                    SyntaxKind::SubSelect
+                        | SyntaxKind::MinusGraphPattern
+                        | SyntaxKind::ExistsFunc
+                        | SyntaxKind::NotExistsFunc
+                )
+            })
+        })
+        .filter(|triple| {
+            triple.properties_list_path().is_some_and(|property_list| {
+                property_list
+                    .properties()
+                    .iter()
+                    .all(|property| is_simple_verb(property.verb.syntax()))
+            })
+        })
+        .map(|triple| {
+            reindent(
+                &triple.syntax().text().to_string(),
+                triple.syntax(),
+                document,
+                indent,
+            )
+        })
+        .collect();
+    if triples.is_empty() {
+        return None;
+    }
+    let template = triples
+        .iter()
+        .map(|triple| format!("{indent}{triple} .\n"))
+        .collect::<String>();
+    Some((
+        "Convert to CONSTRUCT",
+        vec![(
+            select_clause.text_range(),
+            format!("CONSTRUCT {{\n{template}}}"),
+        )],
+    ))
+}
+
+fn to_ask(query: &SyntaxNode) -> Option<Rewrite> {
+    let select_clause = child(query, SyntaxKind::SelectClause)?;
+    Some((
+        "Convert to ASK",
+        vec![(select_clause.text_range(), "ASK".to_string())],
+    ))
+}
+
+fn to_count(query: &SyntaxNode) -> Option<Rewrite> {
+    let select_query = SelectQuery::cast(query.clone())?;
+    let select_clause = select_query.select_clause()?;
+    let solution_modifier = child(query, SyntaxKind::SolutionModifier);
+    // NOTE: counting the groups of a grouped query needs a sub-select
+    if solution_modifier.as_ref().is_some_and(|solution_modifier| {
+        child(solution_modifier, SyntaxKind::GroupClause).is_some()
+    }) || select_clause
+        .syntax()
+        .descendants()
+        .any(|node| node.kind() == SyntaxKind::Aggregate)
+    {
+        return None;
+    }
+    let is_distinct = select_clause
+        .syntax()
+        .children_with_tokens()
+        .any(|child| child.kind() == SyntaxKind::DISTINCT);
+    let projected = select_clause.projected_variables();
+    let counted = match (
+        is_distinct,
+        select_clause.is_star_selection(),
+        projected.as_slice(),
+    ) {
+        (false, _, _) => "*".to_string(),
+        (true, true, _) => "DISTINCT *".to_string(),
+        (true, false, [var]) => format!("DISTINCT {}", var.text()),
+        // NOTE: there is no COUNT over distinct tuples of some variables
+        (true, false, _) => return None,
+    };
+    let count = fresh_variables(&query.ancestors().last()?, "count").next()?;
+    let mut edits = vec![(
+        select_clause.syntax().text_range(),
+        format!("SELECT (COUNT({counted}) AS ?{count})"),
+    )];
+    // NOTE: ORDER BY, LIMIT and OFFSET refer to the original solutions
+    if let Some(solution_modifier) = solution_modifier {
+        let start = solution_modifier
+            .prev_sibling_or_token()
+            .filter(|previous| previous.kind() == SyntaxKind::WHITESPACE)
+            .map_or(solution_modifier.text_range().start(), |whitespace| {
+                whitespace.text_range().start()
+            });
+        edits.push((
+            TextRange::new(start, solution_modifier.text_range().end()),
+            String::new(),
+        ));
+    }
+    Some(("Convert to COUNT", edits))
+}
+
+fn construct_to_select(query: &SyntaxNode) -> Option<Rewrite> {
+    let keyword = query
+        .children_with_tokens()
+        .find(|child| child.kind() == SyntaxKind::CONSTRUCT)?;
+    let (template, replaced) = match child(query, SyntaxKind::ConstructTemplate) {
+        Some(template) => (
+            template.clone(),
+            TextRange::new(keyword.text_range().start(), template.text_range().end()),
+        ),
+        // NOTE: `CONSTRUCT WHERE { ... }`, the pattern is the template
+        None => (
+            child(query, SyntaxKind::TriplesTemplate)?,
+            keyword.text_range(),
+        ),
+    };
+    Some((
+        "Convert to SELECT",
+        vec![(replaced, format!("SELECT {}", selection([template])))],
+    ))
+}
+
+fn ask_to_select(query: &SyntaxNode) -> Option<Rewrite> {
+    let keyword = query
+        .children_with_tokens()
+        .find(|child| child.kind() == SyntaxKind::ASK)?;
+    Some((
+        "Convert to SELECT",
+        vec![(keyword.text_range(), "SELECT *".to_string())],
+    ))
+}
+
+fn describe_to_select(query: &SyntaxNode) -> Option<Rewrite> {
+    child(query, SyntaxKind::WhereClause)?;
+    let keyword = query
+        .children_with_tokens()
+        .find(|child| child.kind() == SyntaxKind::DESCRIBE)?;
+    let end = query
+        .children_with_tokens()
+        .filter(|child| matches!(child.kind(), SyntaxKind::VarOrIri | SyntaxKind::Star))
+        .last()
+        .map_or(keyword.text_range().end(), |last| last.text_range().end());
+    let described = query
+        .children()
+        .filter(|child| child.kind() == SyntaxKind::VarOrIri);
+    Some((
+        "Convert to SELECT",
+        vec![(
+            TextRange::new(keyword.text_range().start(), end),
+            format!("SELECT {}", selection(described)),
+        )],
+    ))
+}
+
+/// The variables in `nodes` in order of appearance, or `*` if there are none.
+fn selection(nodes: impl IntoIterator<Item = SyntaxNode>) -> String {
+    let mut seen = HashSet::new();
+    let variables: Vec<String> = nodes
+        .into_iter()
+        .flat_map(|node| node.descendants().filter_map(Var::cast).collect::<Vec<_>>())
+        .filter(|var| seen.insert(var.var_name()))
+        .map(|var| var.text())
+        .collect();
+    if variables.is_empty() {
+        "*".to_string()
+    } else {
+        variables.join(" ")
+    }
+}
+
+/// Moves the continuation lines of `text` from the indentation of `node` to
+/// one level of `indent`.
+fn reindent(text: &str, node: &SyntaxNode, document: &TextDocumentItem, indent: &str) -> String {
+    let line_start = document.text[..node.text_range().start().into()]
+        .rfind('\n')
+        .map_or(0, |idx| idx + 1);
+    let line = &document.text[line_start..];
+    let base = line.len() - line.trim_start_matches([' ', '\t']).len();
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            if idx == 0 {
+                line.to_string()
+            } else {
+                let whitespace = line.len() - line.trim_start_matches([' ', '\t']).len();
+                format!("{indent}{}", &line[whitespace.min(base)..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::code_actions;
+    use crate::server::lsp::textdocument::TextDocumentItem;
+
+    /// Applies the code action `title` offered on the first `keyword` of `input`.
+    fn convert(input: &str, keyword: &str, title: &str) -> Option<String> {
+        convert_with_indent(input, keyword, title, "  ")
+    }
+
+    fn convert_with_indent(
+        input: &str,
+        keyword: &str,
+        title: &str,
+        indent: &str,
+    ) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let range = TextRange::at(
+            TextSize::new(input.find(keyword).unwrap() as u32),
+            TextSize::new(keyword.len() as u32),
+        );
+        let action = code_actions(&tree.covering_element(range), &document, indent)
+            .into_iter()
+            .find(|action| action.title == title)?;
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+        Some(document.text)
+    }
+
+    #[test]
+    fn select_to_construct() {
+        let input = indoc! {
+            "SELECT ?name WHERE {
+               ?person <name> ?name ;
+                       <knows>/<name> ?friend .
+               ?person a <Person>
+               MINUS { ?person <age> ?age }
+             }
+             "
+        };
+        assert_eq!(
+            convert(input, "SELECT", "Convert to CONSTRUCT").unwrap(),
+            indoc! {
+                "CONSTRUCT {
+                   ?person a <Person> .
+                 } WHERE {
+                   ?person <name> ?name ;
+                           <knows>/<name> ?friend .
+                   ?person a <Person>
+                   MINUS { ?person <age> ?age }
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn select_to_construct_keeps_multiline_triples() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?person <name> ?name ;
+                       <age> ?age
+             }
+             "
+        };
+        assert_eq!(
+            convert(input, "SELECT", "Convert to CONSTRUCT").unwrap(),
+            indoc! {
+                "CONSTRUCT {
+                   ?person <name> ?name ;
+                           <age> ?age .
+                 } WHERE {
+                   ?person <name> ?name ;
+                           <age> ?age
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn select_to_construct_with_tabs() {
+        assert_eq!(
+            convert_with_indent(
+                "SELECT * WHERE {\n\t?s <p> ?o\n}\n",
+                "SELECT",
+                "Convert to CONSTRUCT",
+                "\t"
+            )
+            .unwrap(),
+            "CONSTRUCT {\n\t?s <p> ?o .\n} WHERE {\n\t?s <p> ?o\n}\n"
+        );
+    }
+
+    #[test]
+    fn select_to_ask() {
+        assert_eq!(
+            convert("SELECT ?s WHERE { ?s ?p ?o }", "SELECT", "Convert to ASK").unwrap(),
+            "ASK WHERE { ?s ?p ?o }\n"
+        );
+    }
+
+    #[test]
+    fn select_to_count() {
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { ?s ?p ?o } ORDER BY ?s LIMIT 10",
+                "SELECT",
+                "Convert to COUNT"
+            )
+            .unwrap(),
+            "SELECT (COUNT(*) AS ?count) WHERE { ?s ?p ?o }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT DISTINCT ?s WHERE { ?s ?p ?o }",
+                "SELECT",
+                "Convert to COUNT"
+            )
+            .unwrap(),
+            "SELECT (COUNT(DISTINCT ?s) AS ?count) WHERE { ?s ?p ?o }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT DISTINCT ?s ?o WHERE { ?s ?p ?o }",
+                "SELECT",
+                "Convert to COUNT"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?count WHERE { ?s <p> ?count }",
+                "SELECT",
+                "Convert to COUNT"
+            )
+            .unwrap(),
+            "SELECT (COUNT(*) AS ?count1) WHERE { ?s <p> ?count }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?s (COUNT(?o) AS ?n) WHERE { ?s ?p ?o } GROUP BY ?s",
+                "SELECT",
+                "Convert to COUNT"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn construct_to_select() {
+        assert_eq!(
+            convert(
+                "CONSTRUCT { ?s <name> ?name } WHERE { ?s <label> ?name ; <age> ?age }",
+                "CONSTRUCT",
+                "Convert to SELECT"
+            )
+            .unwrap(),
+            "SELECT ?s ?name WHERE { ?s <label> ?name ; <age> ?age }\n"
+        );
+        assert_eq!(
+            convert(
+                "CONSTRUCT WHERE { ?s <name> ?name }",
+                "CONSTRUCT",
+                "Convert to SELECT"
+            )
+            .unwrap(),
+            "SELECT ?s ?name WHERE { ?s <name> ?name }\n"
+        );
+    }
+
+    #[test]
+    fn ask_and_describe_to_select() {
+        assert_eq!(
+            convert("ASK { ?s ?p ?o }", "ASK", "Convert to SELECT").unwrap(),
+            "SELECT * { ?s ?p ?o }\n"
+        );
+        assert_eq!(
+            convert(
+                "DESCRIBE ?s <iri> WHERE { ?s ?p <iri> }",
+                "DESCRIBE",
+                "Convert to SELECT"
+            )
+            .unwrap(),
+            "SELECT ?s WHERE { ?s ?p <iri> }\n"
+        );
+    }
+}
@@ -138,11 +138,7 @@ pub(crate) fn declare_prefix(
     diagnostic: Diagnostic,
 ) -> Result<Option<CodeAction>, LSPError> {
     if let Some(LSPAny::String(prefix)) = &diagnostic.data {
-        if let Some(Ok(record)) = server
-            .state
-            .get_default_converter()
-            .map(|converter| converter.find_by_prefix(prefix))
-        {
+        if let Ok(record) = server.state.get_default_converter().find_by_prefix(prefix) {
             Ok(Some(CodeAction {
                 title: format!("Declare prefix \"{}\"", prefix),
                 kind: Some(CodeActionKind::QuickFix),
@@ -0,0 +1,469 @@
+//! Extract triple patterns into a sub-select and inline a sub-select again.
+//!
+//! "Extract into Sub-Select" wraps the selected triples of a basic graph
+//! pattern into `{ SELECT ... WHERE { ... } }`. It projects exactly the
+//! variables that are also used outside the selection, or every variable of
+//! the selection if the query selects `*`.
+//! "Inline Sub-Select" is the reverse for sub-selects without modifiers. Its
+//! body has to be plain triples, unless the sub-select is the only pattern of
+//! its group: `OPTIONAL`, `MINUS` and `FILTER` would else apply to the
+//! patterns next to the sub-select.
+
+use std::collections::{HashMap, HashSet};
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, Triple, Var},
+    syntax_kind::SyntaxKind,
+};
+use text_size::{TextRange, TextSize};
+
+use super::path::is_star_selected;
+use crate::server::{
+    lsp::{
+        CodeAction, CodeActionKind, WorkspaceEdit,
+        textdocument::{Range, TextDocumentItem, TextEdit},
+    },
+    message_handler::common::find_variable_occurrences,
+};
+
+pub(super) fn extract(
+    root: &SyntaxNode,
+    range: TextRange,
+    document: &TextDocumentItem,
+    indent: &str,
+) -> Option<CodeAction> {
+    if range.is_empty() {
+        return None;
+    }
+    let triples: Vec<Triple> = root
+        .descendants()
+        .filter_map(Triple::cast)
+        .filter(|triple| {
+            triple
+                .syntax()
+                .text_range()
+                .intersect(range)
+                .is_some_and(|intersection| !intersection.is_empty())
+        })
+        .collect();
+    // NOTE: only consecutive triples of one basic graph pattern are extracted,
+    // moving them across other patterns could change the result.
+    let triples_block = triples.first()?.triples_block()?;
+    if triples.iter().any(|triple| {
+        triple
+            .triples_block()
+            .is_none_or(|block| block.syntax() != triples_block.syntax())
+    }) {
+        return None;
+    }
+    let extract_range = TextRange::new(
+        triples.first()?.syntax().text_range().start(),
+        triples.last()?.syntax().text_range().end(),
+    );
+
+    // NOTE: under `SELECT *` every variable of the selection is in the result.
+    let star_selected = is_star_selected(triples_block.syntax());
+    let mut seen = HashSet::new();
+    let projection: Vec<String> = root
+        .descendants()
+        .filter_map(Var::cast)
+        .filter(|var| extract_range.contains_range(var.syntax().text_range()))
+        .filter(|var| seen.insert(var.var_name()))
+        .filter(|var| {
+            star_selected
+                || find_variable_occurrences(var).iter().any(|occurrence| {
+                    !extract_range.contains_range(occurrence.syntax().text_range())
+                })
+        })
+        .map(|var| var.text())
+        .collect();
+    let selection = if projection.is_empty() {
+        "*".to_string()
+    } else {
+        projection.join(" ")
+    };
+
+    let base = line_indentation(&document.text, extract_range.start());
+    let body = document.text[extract_range].replace('\n', &format!("\n{}", indent.repeat(2)));
+    let new_text = format!(
+        "{{\n{base}{indent}SELECT {selection} WHERE {{\n{base}{indent}{indent}{body}\n{base}{indent}}}\n{base}}}"
+    );
+
+    Some(CodeAction {
+        title: "Extract into Sub-Select".to_string(),
+        kind: Some(CodeActionKind::RefactorExtract),
+        diagnostics: vec![],
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(
+                document.uri.to_string(),
+                vec![TextEdit::new(
+                    Range::from_byte_offset_range(extract_range, &document.text)?,
+                    &new_text,
+                )],
+            )])),
+        },
+    })
+}
+
+pub(super) fn inline(element: &SyntaxElement, document: &TextDocumentItem) -> Option<CodeAction> {
+    let sub_select = match element {
+        SyntaxElement::Node(node) => Some(node.clone()),
+        SyntaxElement::Token(token) => token.parent_ancestors().nth(1),
+    }
+    .filter(|node| node.kind() == SyntaxKind::SubSelect)?;
+
+    // NOTE: modifiers, VALUES, DISTINCT and assignments change the solutions,
+    // such a sub-select can not be inlined.
+    let select_clause = sub_select
+        .children()
+        .find(|child| child.kind() == SyntaxKind::SelectClause)?;
+    if sub_select.children().any(|child| match child.kind() {
+        SyntaxKind::SolutionModifier | SyntaxKind::ValuesClause => {
+            child.text_range().len() > 0.into()
+        }
+        _ => false,
+    }) || select_clause.children_with_tokens().any(|child| {
+        matches!(
+            child.kind(),
+            SyntaxKind::DISTINCT | SyntaxKind::REDUCED | SyntaxKind::LParen
+        )
+    }) {
+        return None;
+    }
+
+    // NOTE: `{ SELECT ... }` has to be a plain group in a group, not a UNION branch.
+    let wrapper = sub_select.parent()?;
+    let group_or_union = wrapper.parent()?;
+    let graph_pattern = group_or_union.parent()?;
+    if wrapper.kind() != SyntaxKind::GroupGraphPattern
+        || group_or_union.kind() != SyntaxKind::GroupOrUnionGraphPattern
+        || group_or_union.children().count() != 1
+        || graph_pattern.kind() != SyntaxKind::GraphPatternNotTriples
+    {
+        return None;
+    }
+
+    let where_pattern = sub_select
+        .children()
+        .find(|child| child.kind() == SyntaxKind::WhereClause)?
+        .children()
+        .find(|child| child.kind() == SyntaxKind::GroupGraphPattern)?;
+    let body = where_pattern
+        .children()
+        .find(|child| child.kind() == SyntaxKind::GroupGraphPatternSub);
+    let is_plain_triples = body.as_ref().is_none_or(|body| {
+        body.children()
+            .all(|child| child.kind() == SyntaxKind::TriplesBlock)
+    });
+    let is_only_pattern = graph_pattern
+        .parent()
+        .is_some_and(|group| group.children().count() == 1);
+    if !is_plain_triples && !is_only_pattern {
+        return None;
+    }
+    if where_pattern
+        .children()
+        .any(|child| child.kind() == SyntaxKind::SubSelect)
+    {
+        return None;
+    }
+
+    // NOTE: variables that are not projected must not clash with variables
+    // outside of the sub-select once they are in the same scope.
+    let is_star = select_clause
+        .children_with_tokens()
+        .any(|child| child.kind() == SyntaxKind::Star);
+    if !is_star {
+        let projected: HashSet<String> = select_clause
+            .children()
+            .filter_map(Var::cast)
+            .map(|var| var.var_name())
+            .collect();
+        let outside: HashSet<String> = sub_select
+            .ancestors()
+            .last()?
+            .descendants()
+            .filter_map(Var::cast)
+            .filter(|var| {
+                !sub_select
+                    .text_range()
+                    .contains_range(var.syntax().text_range())
+            })
+            .map(|var| var.var_name())
+            .collect();
+        if where_pattern
+            .descendants()
+            .filter_map(Var::cast)
+            .any(|var| !projected.contains(&var.var_name()) && outside.contains(&var.var_name()))
+        {
+            return None;
+        }
+    }
+
+    let edits = splice_group(&graph_pattern, body.as_ref(), document)?;
+
+    Some(CodeAction {
+        title: "Inline Sub-Select".to_string(),
+        kind: Some(CodeActionKind::RefactorInline),
+        diagnostics: vec![],
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(document.uri.to_string(), edits)])),
+        },
+    })
+}
+
+/// Replaces `graph_pattern` with the content of the group `body`, dedented
+/// to the indentation of `graph_pattern`.
+pub(super) fn splice_group(
+    graph_pattern: &SyntaxNode,
+    body: Option<&SyntaxNode>,
+    document: &TextDocumentItem,
+) -> Option<Vec<TextEdit>> {
+    let mut edits = vec![];
+    let new_text = match body {
+        Some(body) => {
+            let body_indentation = line_indentation(&document.text, body.text_range().start());
+            let base = line_indentation(&document.text, graph_pattern.text_range().start());
+            let dedent = body_indentation.len().saturating_sub(base.len());
+            let mut text = body
+                .text()
+                .to_string()
+                .lines()
+                .enumerate()
+                .map(|(idx, line)| {
+                    if idx == 0 {
+                        line.to_string()
+                    } else {
+                        let whitespace = line.len() - line.trim_start_matches(' ').len();
+                        line[whitespace.min(dedent)..].to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if body
+                .first_child()
+                .is_some_and(|child| child.kind() == SyntaxKind::TriplesBlock)
+            {
+                edits.extend(separate_previous_triples(graph_pattern, document));
+            }
+            if body
+                .last_child()
+                .is_some_and(|child| is_unterminated_triples(&child))
+                && graph_pattern
+                    .next_sibling_or_token()
+                    .into_iter()
+                    .flat_map(|next| {
+                        std::iter::successors(Some(next), |element| element.next_sibling_or_token())
+                    })
+                    .find(|element| element.kind() != SyntaxKind::WHITESPACE)
+                    .is_some_and(|element| element.kind() == SyntaxKind::TriplesBlock)
+            {
+                text.push_str(" .");
+            }
+            text
+        }
+        None => String::new(),
+    };
+    edits.push(TextEdit::new(
+        Range::from_byte_offset_range(graph_pattern.text_range(), &document.text)?,
+        &new_text,
+    ));
+    Some(edits)
+}
+
+// NOTE: triples that end up next to each other have to be separated by a dot.
+fn is_unterminated_triples(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::TriplesBlock && !node.text().to_string().trim_end().ends_with('.')
+}
+
+/// Terminates the triples right before `graph_pattern`, if they are not yet.
+pub(super) fn separate_previous_triples(
+    graph_pattern: &SyntaxNode,
+    document: &TextDocumentItem,
+) -> Option<TextEdit> {
+    let previous = graph_pattern
+        .prev_sibling()
+        .filter(is_unterminated_triples)?;
+    Some(TextEdit::new(
+        Range::from_byte_offset_range(
+            TextRange::empty(previous.text_range().end()),
+            &document.text,
+        )?,
+        " .",
+    ))
+}
+
+/// The leading whitespace of the line that contains `offset`.
+pub(super) fn line_indentation(text: &str, offset: TextSize) -> &str {
+    let line_start = text[..offset.into()].rfind('\n').map_or(0, |idx| idx + 1);
+    let line = &text[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::{extract, inline};
+    use crate::server::lsp::{CodeAction, textdocument::TextDocumentItem};
+
+    fn apply(document: &mut TextDocumentItem, action: CodeAction) {
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+    }
+
+    fn range_of(text: &str, needle: &str) -> TextRange {
+        let start = text.find(needle).unwrap();
+        TextRange::new(
+            TextSize::new(start as u32),
+            TextSize::new((start + needle.len()) as u32),
+        )
+    }
+
+    /// Range of the last `SELECT` keyword, the one of the sub-select.
+    fn sub_select_keyword(text: &str) -> TextRange {
+        TextRange::at(
+            TextSize::new(text.rfind("SELECT").unwrap() as u32),
+            TextSize::new(6),
+        )
+    }
+
+    #[test]
+    fn extract_projects_variables_used_outside() {
+        let input = indoc! {
+            "SELECT ?name WHERE {
+               ?person <knows> ?friend .
+               ?friend <name> ?name .
+               ?friend <age> ?age
+             }
+             "
+        };
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let range = range_of(input, "?friend <name> ?name .\n  ?friend <age> ?age");
+        let action = extract(&tree, range, &document, "  ").unwrap();
+        apply(&mut document, action);
+        assert_eq!(
+            document.text,
+            indoc! {
+                "SELECT ?name WHERE {
+                   ?person <knows> ?friend .
+                   {
+                     SELECT ?friend ?name WHERE {
+                       ?friend <name> ?name .
+                       ?friend <age> ?age
+                     }
+                   }
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn extract_under_select_star_projects_every_variable() {
+        let input = "SELECT * WHERE {\n\t?a <p> ?b .\n\t?b <q> ?c\n}\n";
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let action = extract(&tree, range_of(input, "?b <q> ?c"), &document, "\t").unwrap();
+        apply(&mut document, action);
+        assert_eq!(
+            document.text,
+            "SELECT * WHERE {\n\t?a <p> ?b .\n\t{\n\t\tSELECT ?b ?c WHERE {\n\t\t\t?b <q> ?c\n\t\t}\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn extract_requires_one_basic_graph_pattern() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?a <p> ?b
+               OPTIONAL { ?b <q> ?c }
+               ?c <r> ?d
+             }
+             "
+        };
+        let document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let range = range_of(input, "?a <p> ?b\n  OPTIONAL { ?b <q> ?c }\n  ?c <r> ?d");
+        assert!(extract(&tree, range, &document, "  ").is_none());
+        assert!(extract(&tree, TextRange::empty(range.start()), &document, "  ").is_none());
+    }
+
+    #[test]
+    fn inline_sub_select() {
+        let input = indoc! {
+            "SELECT ?name WHERE {
+               ?person <knows> ?friend
+               {
+                 SELECT ?friend ?name WHERE {
+                   ?friend <name> ?name .
+                   ?friend <age> ?age
+                 }
+               }
+               ?friend <email> ?email
+             }
+             "
+        };
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let element = tree.covering_element(sub_select_keyword(input));
+        let action = inline(&element, &document).unwrap();
+        apply(&mut document, action);
+        assert_eq!(
+            document.text,
+            indoc! {
+                "SELECT ?name WHERE {
+                   ?person <knows> ?friend .
+                   ?friend <name> ?name .
+                   ?friend <age> ?age .
+                   ?friend <email> ?email
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn inline_only_pattern_of_group() {
+        let input = "SELECT * { { SELECT * { ?x <q> ?z OPTIONAL { ?z <r> ?w } } } }";
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let element = tree.covering_element(sub_select_keyword(input));
+        let action = inline(&element, &document).unwrap();
+        apply(&mut document, action);
+        assert_eq!(
+            document.text,
+            "SELECT * { ?x <q> ?z OPTIONAL { ?z <r> ?w } }\n"
+        );
+    }
+
+    #[test]
+    fn inline_sub_select_refuses_unsafe_cases() {
+        for input in [
+            "SELECT * { { SELECT ?a { ?a ?b ?c } LIMIT 1 } }",
+            "SELECT * { { SELECT DISTINCT ?a { ?a ?b ?c } } }",
+            "SELECT * { { SELECT (?a AS ?x) { ?a ?b ?c } } }",
+            "SELECT * { ?b ?p ?o { SELECT ?a { ?a ?b ?c } } }",
+            "SELECT * { { SELECT ?a { ?a ?b ?c } } UNION { ?a ?b ?c } }",
+            "SELECT * { ?x <p> ?y { SELECT * { OPTIONAL { ?x <q> ?z } } } }",
+            "SELECT * { ?x <p> ?y { SELECT * { ?x <q> ?z MINUS { ?x <r> ?y } } } }",
+            "SELECT * { ?x <p> ?y { SELECT * { ?x <q> ?z FILTER(?z > 1) } } }",
+        ] {
+            let document = TextDocumentItem::new("query.rq", input);
+            let (tree, _) = parse(&document.text);
+            let element = tree.covering_element(sub_select_keyword(input));
+            assert!(inline(&element, &document).is_none(), "{input}");
+        }
+    }
+}
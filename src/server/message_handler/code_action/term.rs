@@ -0,0 +1,488 @@
+//! Rewrite terms: `a` and `rdf:type`, and blank node property lists `[ ... ]`
+//! and named blank nodes or variables.
+//!
+//! A named node is only turned back into `[ ... ]` if it is the subject of one
+//! triple and occurs at most once more, as an object in the same group.
+
+use std::collections::{HashMap, HashSet};
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode, SyntaxToken,
+    ast::{AstNode, Prologue, Var},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use super::{path::is_hidden_variable, sub_select::line_indentation, wrap::fresh_variables};
+use crate::server::lsp::{
+    CodeAction, CodeActionKind, WorkspaceEdit,
+    textdocument::{Range, TextDocumentItem, TextEdit},
+};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Prefix and namespace of the prefix declarations in the prologue of `root`.
+pub(super) fn prefix_declarations(root: &SyntaxNode) -> Vec<(String, String)> {
+    root.first_child()
+        .and_then(|child| child.first_child())
+        .and_then(Prologue::cast)
+        .map(|prologue| prologue.prefix_declarations())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|declaration| Some((declaration.prefix()?, declaration.raw_uri_prefix()?)))
+        .collect()
+}
+
+/// The full IRI of an `iri` node.
+pub(super) fn resolve_iri(iri: &SyntaxNode, prefixes: &[(String, String)]) -> Option<String> {
+    let text = iri.text().to_string();
+    if let Some(raw) = text.strip_prefix('<') {
+        return raw.strip_suffix('>').map(str::to_string);
+    }
+    let (prefix, local) = text.split_once(':')?;
+    prefixes
+        .iter()
+        .find(|(declared, _)| declared == prefix)
+        .map(|(_, namespace)| format!("{namespace}{local}"))
+}
+
+/// `iri` as a prefixed name if one of `prefixes` covers it, otherwise as `<iri>`.
+pub(super) fn write_iri(iri: &str, prefixes: &[(String, String)]) -> String {
+    prefixes
+        .iter()
+        .find_map(|(prefix, namespace)| {
+            let local = iri.strip_prefix(namespace.as_str())?;
+            is_simple_local_name(local).then(|| format!("{prefix}:{local}"))
+        })
+        .unwrap_or_else(|| format!("<{iri}>"))
+}
+
+/// A local name that needs no escaping in a prefixed name.
+pub(super) fn is_simple_local_name(local: &str) -> bool {
+    local
+        .chars()
+        .enumerate()
+        .all(|(idx, char)| char.is_alphanumeric() || char == '_' || (idx > 0 && char == '-'))
+}
+
+pub(super) fn code_actions(
+    element: &SyntaxElement,
+    document: &TextDocumentItem,
+) -> Vec<CodeAction> {
+    let ancestors: Vec<SyntaxNode> = match element {
+        SyntaxElement::Node(node) => node.ancestors().collect(),
+        SyntaxElement::Token(token) => token.parent_ancestors().collect(),
+    };
+    [
+        a_to_rdf_type(element),
+        rdf_type_to_a(&ancestors),
+        to_named_node(&ancestors, document, true),
+        to_named_node(&ancestors, document, false),
+        to_property_list(element),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|(title, edits)| {
+        Some(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::RefactorRewrite),
+            diagnostics: vec![],
+            edit: WorkspaceEdit {
+                changes: Some(HashMap::from_iter([(
+                    document.uri.to_string(),
+                    edits
+                        .into_iter()
+                        .map(|(range, new_text)| {
+                            Some(TextEdit::new(
+                                Range::from_byte_offset_range(range, &document.text)?,
+                                &new_text,
+                            ))
+                        })
+                        .collect::<Option<Vec<_>>>()?,
+                )])),
+            },
+        })
+    })
+    .collect()
+}
+
+type Edits = Vec<(TextRange, String)>;
+
+/// Whether `node` is the parent of a verb that may be written as `a`.
+fn is_verb_position(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        SyntaxKind::PathPrimary | SyntaxKind::PathOneInPropertySet | SyntaxKind::Verb => true,
+        SyntaxKind::VarOrIri => node
+            .parent()
+            .is_some_and(|parent| parent.kind() == SyntaxKind::Verb),
+        _ => false,
+    }
+}
+
+fn a_to_rdf_type(element: &SyntaxElement) -> Option<(&'static str, Edits)> {
+    let token = element
+        .as_token()
+        .filter(|token| token.kind() == SyntaxKind::a)?;
+    if !token.parent().as_ref().is_some_and(is_verb_position) {
+        return None;
+    }
+    let root = token.parent_ancestors().last()?;
+    Some((
+        "Convert to rdf:type",
+        vec![(
+            token.text_range(),
+            write_iri(RDF_TYPE, &prefix_declarations(&root)),
+        )],
+    ))
+}
+
+fn rdf_type_to_a(ancestors: &[SyntaxNode]) -> Option<(&'static str, Edits)> {
+    let iri = ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::iri)?;
+    if !iri.parent().as_ref().is_some_and(is_verb_position) {
+        return None;
+    }
+    let root = iri.ancestors().last()?;
+    (resolve_iri(iri, &prefix_declarations(&root))? == RDF_TYPE)
+        .then(|| ("Convert to 'a'", vec![(iri.text_range(), "a".to_string())]))
+}
+
+fn is_blank_node_property_list(node: &SyntaxNode) -> bool {
+    matches!(
+        node.kind(),
+        SyntaxKind::BlankNodePropertyListPath | SyntaxKind::BlankNodePropertyList
+    )
+}
+
+fn is_triple(node: &SyntaxNode) -> bool {
+    matches!(
+        node.kind(),
+        SyntaxKind::TriplesSameSubjectPath | SyntaxKind::TriplesSameSubject
+    )
+}
+
+/// `b`, `b1`, `b2`, ... without the blank node labels used in `root`.
+fn fresh_labels(root: &SyntaxNode) -> impl Iterator<Item = String> {
+    let used: HashSet<String> = root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == SyntaxKind::BLANK_NODE_LABEL)
+        .map(|token| token.text().trim_start_matches("_:").to_string())
+        .collect();
+    std::iter::once("b".to_string())
+        .chain((1..).map(|idx| format!("b{idx}")))
+        .filter(move |candidate| !used.contains(candidate))
+}
+
+fn to_named_node(
+    ancestors: &[SyntaxNode],
+    document: &TextDocumentItem,
+    label: bool,
+) -> Option<(&'static str, Edits)> {
+    let property_list = ancestors
+        .iter()
+        .find(|node| is_blank_node_property_list(node))?;
+    let properties = property_list.children().next()?.text().to_string();
+    let root = property_list.ancestors().last()?;
+    let (title, name) = if label {
+        (
+            "Convert to blank node label",
+            format!("_:{}", fresh_labels(&root).next()?),
+        )
+    } else {
+        (
+            "Convert to variable",
+            format!("?{}", fresh_variables(&root, "node").next()?),
+        )
+    };
+    // NOTE: BlankNodePropertyList(Path) > TriplesNode(Path) > TriplesSameSubject(Path) or GraphNode(Path)
+    let triples_node = property_list.parent()?;
+    let parent = triples_node.parent()?;
+    if is_triple(&parent) {
+        let rest = parent
+            .children()
+            .nth(1)
+            .map(|rest| rest.text().to_string())
+            .filter(|rest| !rest.is_empty());
+        let new_text = match rest {
+            Some(rest) => format!("{name} {properties} ; {rest}"),
+            None => format!("{name} {properties}"),
+        };
+        return Some((title, vec![(parent.text_range(), new_text)]));
+    }
+    let triple = parent.ancestors().find(is_triple)?;
+    let indentation = line_indentation(&document.text, triple.text_range().start());
+    Some((
+        title,
+        vec![
+            (triples_node.text_range(), name.clone()),
+            (
+                TextRange::empty(triple.text_range().end()),
+                format!(" .\n{indentation}{name} {properties}"),
+            ),
+        ],
+    ))
+}
+
+/// The group a triple belongs to, named nodes are only merged within one.
+fn scope(node: &SyntaxNode) -> Option<SyntaxNode> {
+    node.ancestors().find(|ancestor| {
+        matches!(
+            ancestor.kind(),
+            SyntaxKind::GroupGraphPattern
+                | SyntaxKind::ConstructTemplate
+                | SyntaxKind::QuadPattern
+                | SyntaxKind::QuadData
+        )
+    })
+}
+
+pub(super) fn next_non_trivia(token: Option<SyntaxToken>, forward: bool) -> Option<SyntaxToken> {
+    let mut token = token?;
+    while token.kind().is_trivia() {
+        token = if forward {
+            token.next_token()?
+        } else {
+            token.prev_token()?
+        };
+    }
+    Some(token)
+}
+
+fn to_property_list(element: &SyntaxElement) -> Option<(&'static str, Edits)> {
+    // NOTE: the VarOrTerm nodes of all occurrences of the selected node
+    let occurrences: Vec<SyntaxNode> = match element {
+        SyntaxElement::Token(token) if token.kind() == SyntaxKind::BLANK_NODE_LABEL => {
+            let root = token.parent_ancestors().last()?;
+            root.descendants_with_tokens()
+                .filter_map(|element| element.into_token())
+                .filter(|other| {
+                    other.kind() == SyntaxKind::BLANK_NODE_LABEL && other.text() == token.text()
+                })
+                .map(|other| other.parent()?.parent())
+                .collect::<Option<_>>()?
+        }
+        _ => {
+            let var = element.parent().and_then(Var::cast)?;
+            let var_or_term = var.syntax().parent()?;
+            if !is_hidden_variable(&var_or_term) && !is_unused_variable(&var) {
+                return None;
+            }
+            let root = var_or_term.ancestors().last()?;
+            root.descendants()
+                .filter_map(Var::cast)
+                .filter(|other| other.var_name() == var.var_name())
+                .map(|other| other.syntax().parent())
+                .collect::<Option<_>>()?
+        }
+    };
+    let (subjects, objects): (Vec<SyntaxNode>, Vec<SyntaxNode>) = occurrences
+        .into_iter()
+        .partition(|occurrence| occurrence.parent().as_ref().is_some_and(is_triple));
+    let [subject] = subjects.try_into().ok()?;
+    let triple = subject.parent()?;
+    let properties = triple.children().nth(1)?.text().to_string();
+    match objects.as_slice() {
+        [] => Some((
+            "Convert to blank node property list",
+            vec![(triple.text_range(), format!("[ {properties} ]"))],
+        )),
+        [object] => {
+            // NOTE: VarOrTerm > GraphNode(Path) > Object(Path)
+            let is_object = object
+                .parent()
+                .and_then(|graph_node| graph_node.parent())
+                .is_some_and(|parent| {
+                    matches!(parent.kind(), SyntaxKind::ObjectPath | SyntaxKind::Object)
+                });
+            if !is_object
+                || triple.text_range().contains_range(object.text_range())
+                || scope(&triple) != scope(object)
+            {
+                return None;
+            }
+            let previous = next_non_trivia(triple.first_token()?.prev_token(), false)?;
+            let next = next_non_trivia(triple.last_token()?.next_token(), true);
+            let end = match next {
+                Some(dot) if dot.kind() == SyntaxKind::Dot => dot.text_range().end(),
+                _ => triple.text_range().end(),
+            };
+            Some((
+                "Convert to blank node property list",
+                vec![
+                    (object.text_range(), format!("[ {properties} ]")),
+                    (
+                        TextRange::new(previous.text_range().end(), end),
+                        String::new(),
+                    ),
+                ],
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// A variable that occurs only once and is not projected by `SELECT *`.
+fn is_unused_variable(var: &Var) -> bool {
+    let root = var.syntax().ancestors().last();
+    root.is_some_and(|root| {
+        root.descendants()
+            .filter_map(Var::cast)
+            .filter(|other| other.var_name() == var.var_name())
+            .count()
+            == 1
+            && !root
+                .descendants_with_tokens()
+                .any(|element| element.kind() == SyntaxKind::Star)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::{code_actions, write_iri};
+    use crate::server::lsp::textdocument::TextDocumentItem;
+
+    fn convert(input: &str, needle: &str, title: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let start = input.find(needle).unwrap();
+        let range = TextRange::at(
+            TextSize::new(start as u32),
+            TextSize::new(needle.len() as u32),
+        );
+        let action = code_actions(&tree.covering_element(range), &document)
+            .into_iter()
+            .find(|action| action.title == title)?;
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+        Some(document.text)
+    }
+
+    #[test]
+    fn write_iri_prefers_prefixed_names() {
+        let prefixes = vec![("ex".to_string(), "http://example.org/".to_string())];
+        assert_eq!(write_iri("http://example.org/a-b", &prefixes), "ex:a-b");
+        assert_eq!(
+            write_iri("http://example.org/a/b", &prefixes),
+            "<http://example.org/a/b>"
+        );
+        assert_eq!(
+            write_iri("http://other.org/a", &prefixes),
+            "<http://other.org/a>"
+        );
+    }
+
+    #[test]
+    fn a_and_rdf_type() {
+        assert_eq!(
+            convert("SELECT ?s WHERE { ?s a ?o }", "a", "Convert to rdf:type").unwrap(),
+            "SELECT ?s WHERE { ?s <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> ?o }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { ?s <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> ?o }",
+                "syntax",
+                "Convert to 'a'"
+            )
+            .unwrap(),
+            "SELECT ?s WHERE { ?s a ?o }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { ?s ?p <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> }",
+                "syntax",
+                "Convert to 'a'"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn property_list_to_named_node_and_back() {
+        let input = indoc! {
+            "SELECT ?s WHERE {
+               ?s <p> [ <q> <o> ] .
+             }
+             "
+        };
+        let labeled = indoc! {
+            "SELECT ?s WHERE {
+               ?s <p> _:b .
+               _:b <q> <o> .
+             }
+             "
+        };
+        assert_eq!(
+            convert(input, "[", "Convert to blank node label").unwrap(),
+            labeled
+        );
+        assert_eq!(
+            convert(labeled, "_:b", "Convert to blank node property list").unwrap(),
+            input
+        );
+        assert_eq!(
+            convert(input, "<q>", "Convert to variable").unwrap(),
+            indoc! {
+                "SELECT ?s WHERE {
+                   ?s <p> ?node .
+                   ?node <q> <o> .
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn subject_property_list_to_named_node_and_back() {
+        assert_eq!(
+            convert(
+                "SELECT ?o WHERE { [ <q> ?o ] <r> <z> }",
+                "[",
+                "Convert to blank node label"
+            )
+            .unwrap(),
+            "SELECT ?o WHERE { _:b <q> ?o ; <r> <z> }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?o WHERE { _:b <q> ?o ; <r> <z> }",
+                "_:b",
+                "Convert to blank node property list"
+            )
+            .unwrap(),
+            "SELECT ?o WHERE { [ <q> ?o ; <r> <z> ] }\n"
+        );
+    }
+
+    #[test]
+    fn named_node_in_other_group_stays() {
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { ?s <p> ?x OPTIONAL { ?x <q> <o> } }",
+                "?x",
+                "Convert to blank node property list"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x . ?x <q> <o> }",
+                "?x",
+                "Convert to blank node property list"
+            ),
+            None
+        );
+    }
+}
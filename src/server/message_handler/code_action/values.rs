@@ -0,0 +1,460 @@
+//! Convert between `FILTER (?x IN (...))`, `VALUES ?x { ... }` and a `UNION` of
+//! triples that differ in one constant.
+//!
+//! Engines like QLever and Virtuoso evaluate `VALUES` as a join, which is much
+//! faster than filtering all solutions or scanning the index once per branch.
+//! `FILTER IN` and `VALUES` only have the same solutions if a triple of the
+//! group always binds the variable, and only for IRIs: `IN` compares values
+//! while `VALUES` joins on terms, so `1` and `1.0` only match in the former.
+//! A `UNION` is not converted under `SELECT *`, which would project the new
+//! variable.
+
+use std::collections::HashMap;
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, Triple},
+    syntax_kind::SyntaxKind,
+};
+
+use super::{
+    path::{SimpleTriple, is_star_selected},
+    sub_select::{line_indentation, separate_previous_triples},
+    wrap::fresh_variables,
+};
+use crate::server::lsp::{
+    CodeAction, CodeActionKind, WorkspaceEdit,
+    textdocument::{Range, TextDocumentItem, TextEdit},
+};
+
+pub(super) fn code_actions(
+    element: &SyntaxElement,
+    document: &TextDocumentItem,
+) -> Vec<CodeAction> {
+    let ancestors: Vec<SyntaxNode> = match element {
+        SyntaxElement::Node(node) => node.ancestors().collect(),
+        SyntaxElement::Token(token) => token.parent_ancestors().collect(),
+    };
+    [
+        filter_in_to_values(&ancestors, document),
+        values_to_filter_in(&ancestors, document),
+        union_to_values(&ancestors, document),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(title, edits)| CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::RefactorRewrite),
+        diagnostics: vec![],
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(document.uri.to_string(), edits)])),
+        },
+    })
+    .collect()
+}
+
+fn is_constant(node: &SyntaxNode) -> bool {
+    matches!(
+        node.kind(),
+        SyntaxKind::iri
+            | SyntaxKind::RDFLiteral
+            | SyntaxKind::NumericLiteral
+            | SyntaxKind::BooleanLiteral
+    )
+}
+
+/// The descendant of `node` that matches `is_kind` and spans all of `node`.
+fn exactly(node: &SyntaxNode, is_kind: impl Fn(&SyntaxNode) -> bool) -> Option<SyntaxNode> {
+    node.descendants()
+        .find(|descendant| is_kind(descendant) && descendant.text_range() == node.text_range())
+}
+
+fn is_iri(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::iri
+}
+
+/// Whether a triple of the group that contains `pattern`, outside of any
+/// nested `OPTIONAL` or other graph pattern, binds `var`.
+fn is_bound_in_group(pattern: &SyntaxNode, var: &SyntaxNode) -> bool {
+    pattern
+        .parent()
+        .filter(|group| group.kind() == SyntaxKind::GroupGraphPatternSub)
+        .is_some_and(|group| {
+            group
+                .children()
+                .filter(|child| child.kind() == SyntaxKind::TriplesBlock)
+                .flat_map(|triples_block| triples_block.descendants())
+                .any(|node| node.kind() == SyntaxKind::Var && node.text() == var.text())
+        })
+}
+
+fn child(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxNode> {
+    node.children().find(|child| child.kind() == kind)
+}
+
+fn replace(node: &SyntaxNode, new_text: &str, document: &TextDocumentItem) -> Option<TextEdit> {
+    Some(TextEdit::new(
+        Range::from_byte_offset_range(node.text_range(), &document.text)?,
+        new_text,
+    ))
+}
+
+fn filter_in_to_values(
+    ancestors: &[SyntaxNode],
+    document: &TextDocumentItem,
+) -> Option<(&'static str, Vec<TextEdit>)> {
+    let filter = ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::Filter)?;
+    // NOTE: Filter > Constraint > BrackettedExpression > Expression
+    let expression = child(
+        &child(
+            &child(filter, SyntaxKind::Constraint)?,
+            SyntaxKind::BrackettedExpression,
+        )?,
+        SyntaxKind::Expression,
+    )?;
+    let relational = exactly(&expression, |node| {
+        node.kind() == SyntaxKind::RelationalExpression
+    })?;
+    let operators: Vec<SyntaxKind> = relational
+        .children_with_tokens()
+        .filter_map(|child| child.into_token())
+        .map(|token| token.kind())
+        .filter(|kind| *kind != SyntaxKind::WHITESPACE)
+        .collect();
+    if operators != [SyntaxKind::IN] {
+        return None;
+    }
+    let var = exactly(&relational.first_child()?, |node| {
+        node.kind() == SyntaxKind::Var
+    })?;
+    let values = child(&relational, SyntaxKind::ExpressionList)?
+        .children()
+        .map(|value| exactly(&value, is_iri).map(|iri| iri.text().to_string()))
+        .collect::<Option<Vec<_>>>()?;
+    let graph_pattern = filter.parent()?;
+    if !is_bound_in_group(&graph_pattern, &var) {
+        return None;
+    }
+    Some((
+        "Convert to VALUES",
+        vec![replace(
+            &graph_pattern,
+            &format!("VALUES {var} {{ {} }}", values.join(" ")),
+            document,
+        )?],
+    ))
+}
+
+fn values_to_filter_in(
+    ancestors: &[SyntaxNode],
+    document: &TextDocumentItem,
+) -> Option<(&'static str, Vec<TextEdit>)> {
+    let inline_data = ancestors
+        .iter()
+        .find(|node| node.kind() == SyntaxKind::InlineData)?;
+    let one_var = child(
+        &child(inline_data, SyntaxKind::DataBlock)?,
+        SyntaxKind::InlineDataOneVar,
+    )?;
+    let var = child(&one_var, SyntaxKind::Var)?;
+    // NOTE: UNDEF is a token, it has no child node
+    let values = one_var
+        .children()
+        .filter(|child| child.kind() == SyntaxKind::DataBlockValue)
+        .map(|value| {
+            value
+                .first_child()
+                .filter(is_iri)
+                .map(|iri| iri.text().to_string())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let graph_pattern = inline_data.parent()?;
+    if !is_bound_in_group(&graph_pattern, &var) {
+        return None;
+    }
+    Some((
+        "Convert to FILTER IN",
+        vec![replace(
+            &graph_pattern,
+            &format!("FILTER ({var} IN ({}))", values.join(", ")),
+            document,
+        )?],
+    ))
+}
+
+fn union_to_values(
+    ancestors: &[SyntaxNode],
+    document: &TextDocumentItem,
+) -> Option<(&'static str, Vec<TextEdit>)> {
+    let union = ancestors.iter().find(|node| {
+        node.kind() == SyntaxKind::GroupOrUnionGraphPattern
+            && node
+                .children_with_tokens()
+                .any(|child| child.kind() == SyntaxKind::UNION)
+    })?;
+    let graph_pattern = union.parent()?;
+    if is_star_selected(&graph_pattern) {
+        return None;
+    }
+    let triples = union
+        .children()
+        .map(|branch| {
+            let [triples_block] = child(&branch, SyntaxKind::GroupGraphPatternSub)?
+                .children()
+                .collect::<Vec<_>>()
+                .try_into()
+                .ok()?;
+            let [triple] = triples_block
+                .descendants()
+                .filter_map(Triple::cast)
+                .collect::<Vec<_>>()
+                .try_into()
+                .ok()?;
+            let triple = SimpleTriple::cast(triple)?;
+            Some([triple.subject, triple.verb, triple.object])
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    // NOTE: the branches have to differ in exactly one position
+    let [position] = (0..3)
+        .filter(|&idx| {
+            triples
+                .iter()
+                .any(|triple| triple[idx].text() != triples[0][idx].text())
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .ok()?;
+    let values = triples
+        .iter()
+        .map(|triple| {
+            let term = &triple[position];
+            let constant = match term.kind() {
+                SyntaxKind::VerbPath => exactly(term, |node| node.kind() == SyntaxKind::iri),
+                _ => term.first_child().filter(is_constant),
+            };
+            constant.map(|constant| constant.text().to_string())
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let var = format!(
+        "?{}",
+        fresh_variables(&union.ancestors().last()?, "value")
+            .next()
+            .expect("there is always an unused variable name")
+    );
+    let triple = triples[0]
+        .iter()
+        .enumerate()
+        .map(|(idx, term)| {
+            if idx == position {
+                var.clone()
+            } else {
+                term.text().to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let base = line_indentation(&document.text, graph_pattern.text_range().start());
+    let mut edits: Vec<TextEdit> = separate_previous_triples(&graph_pattern, document)
+        .into_iter()
+        .collect();
+    edits.push(replace(
+        &graph_pattern,
+        &format!("{triple} .\n{base}VALUES {var} {{ {} }}", values.join(" ")),
+        document,
+    )?);
+    Some(("Convert UNION to VALUES", edits))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::code_actions;
+    use crate::server::lsp::textdocument::TextDocumentItem;
+
+    fn convert(input: &str, needle: &str, title: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let start = input.find(needle).unwrap();
+        let range = TextRange::at(
+            TextSize::new(start as u32),
+            TextSize::new(needle.len() as u32),
+        );
+        let action = code_actions(&tree.covering_element(range), &document)
+            .into_iter()
+            .find(|action| action.title == title)?;
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+        Some(document.text)
+    }
+
+    #[test]
+    fn filter_in_to_values() {
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x FILTER (?x IN (<a>, <b>)) }",
+                "FILTER",
+                "Convert to VALUES"
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s <p> ?x VALUES ?x { <a> <b> } }\n"
+        );
+        assert_eq!(
+            convert(
+                r#"SELECT * WHERE { ?s <p> ?x FILTER (?x IN (<a>, "b"@en, 3)) }"#,
+                "FILTER",
+                "Convert to VALUES"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x FILTER (?x NOT IN (<a>)) }",
+                "FILTER",
+                "Convert to VALUES"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x FILTER (?x IN (<a>, ?y)) }",
+                "FILTER",
+                "Convert to VALUES"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn filter_in_needs_variable_bound_in_group() {
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?o OPTIONAL { ?s <q> ?x } FILTER (?x IN (<a>)) }",
+                "FILTER",
+                "Convert to VALUES"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?o OPTIONAL { ?s <q> ?x } VALUES ?x { <a> } }",
+                "VALUES",
+                "Convert to FILTER IN"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?o FILTER (?s IN (<a>)) }",
+                "FILTER",
+                "Convert to VALUES"
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s <p> ?o VALUES ?s { <a> } }\n"
+        );
+    }
+
+    #[test]
+    fn values_to_filter_in() {
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x VALUES ?x { <a> <b> } }",
+                "VALUES",
+                "Convert to FILTER IN"
+            )
+            .unwrap(),
+            "SELECT * WHERE { ?s <p> ?x FILTER (?x IN (<a>, <b>)) }\n"
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x VALUES ?x { <a> 3 } }",
+                "VALUES",
+                "Convert to FILTER IN"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { ?s <p> ?x VALUES ?x { <a> UNDEF } }",
+                "VALUES",
+                "Convert to FILTER IN"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn union_to_values() {
+        let input = indoc! {
+            "SELECT ?s ?label WHERE {
+               ?s <label> ?label
+               { ?s <type> <Person> } UNION { ?s <type> <Place> . } UNION { ?s <type> <Thing> }
+             }
+             "
+        };
+        assert_eq!(
+            convert(input, "UNION", "Convert UNION to VALUES").unwrap(),
+            indoc! {
+                "SELECT ?s ?label WHERE {
+                   ?s <label> ?label .
+                   ?s <type> ?value .
+                   VALUES ?value { <Person> <Place> <Thing> }
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn union_to_values_not_under_select_star() {
+        assert_eq!(
+            convert(
+                "SELECT * WHERE { { ?s <a> <b> } UNION { ?s <a> <c> } }",
+                "UNION",
+                "Convert UNION to VALUES"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn union_to_values_needs_one_difference() {
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { { ?s <a> <b> } UNION { ?s <c> <d> } }",
+                "UNION",
+                "Convert UNION to VALUES"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { { ?s <a> <b> } UNION { ?s <a> ?o } }",
+                "UNION",
+                "Convert UNION to VALUES"
+            ),
+            None
+        );
+        assert_eq!(
+            convert(
+                "SELECT ?s WHERE { { ?s <a> <b> . ?s <c> ?d } UNION { ?s <a> <e> } }",
+                "UNION",
+                "Convert UNION to VALUES"
+            ),
+            None
+        );
+    }
+}
@@ -8,6 +8,7 @@ mod add_label;
 mod add_to_result;
 mod filter_var;
 mod filter_var_lang;
+mod replace_with_constant;
 
 pub(super) fn code_actions(
     var: Var,
@@ -27,6 +28,9 @@ pub(super) fn code_actions(
     if let Some(code_action) = filter_var::code_action(&var, document) {
         code_actions.push(code_action)
     }
+    if let Some(code_action) = replace_with_constant::code_action(&var, document) {
+        code_actions.push(code_action)
+    }
     if let Some(code_action_vec) = add_aggregate_to_result::code_actions(&var, document) {
         for code_action in code_action_vec {
             code_actions.push(code_action)
@@ -0,0 +1,233 @@
+//! Replace with constant code action
+//! Replace a variable bound to one constant, by `BIND` or a single valued `VALUES`,
+//! with that constant everywhere it occurs and drop the binding.
+
+use ll_sparql_parser::{
+    SyntaxNode,
+    ast::{AstNode, Var},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use crate::server::{
+    lsp::{
+        CodeAction, CodeActionKind,
+        textdocument::{Range, TextDocumentItem, TextEdit},
+    },
+    message_handler::{
+        code_action::{constant::is_constant, term::next_non_trivia},
+        common::find_variable_occurrences,
+    },
+};
+
+pub(super) fn code_action(var: &Var, document: &TextDocumentItem) -> Option<CodeAction> {
+    let occurrences = find_variable_occurrences(var);
+    let (binding, constant) = occurrences.iter().find_map(binding)?;
+    let scope = scope(&binding)?;
+    let constant_text = constant.text().to_string();
+    let mut edits = vec![removal(&binding)?];
+    for occurrence in occurrences.iter().filter(|occurrence| {
+        !binding
+            .text_range()
+            .contains_range(occurrence.syntax().text_range())
+    }) {
+        if !scope
+            .text_range()
+            .contains_range(occurrence.syntax().text_range())
+        {
+            return None;
+        }
+        let parent = occurrence.syntax().parent()?;
+        match parent.kind() {
+            SyntaxKind::VarOrTerm | SyntaxKind::PrimaryExpression => {
+                edits.push((occurrence.syntax().text_range(), constant_text.clone()))
+            }
+            SyntaxKind::VerbSimple | SyntaxKind::VarOrIri if constant.kind() == SyntaxKind::iri => {
+                edits.push((occurrence.syntax().text_range(), constant_text.clone()))
+            }
+            // NOTE: a constant can not be projected, the variable is dropped instead.
+            SyntaxKind::SelectClause
+                if occurrence
+                    .syntax()
+                    .prev_sibling()
+                    .is_none_or(|sibling| sibling.kind() != SyntaxKind::Expression)
+                    && parent.children().count() > 1 =>
+            {
+                let previous =
+                    next_non_trivia(occurrence.syntax().first_token()?.prev_token(), false)?;
+                edits.push((
+                    TextRange::new(
+                        previous.text_range().end(),
+                        occurrence.syntax().text_range().end(),
+                    ),
+                    String::new(),
+                ))
+            }
+            _ => return None,
+        }
+    }
+    let mut code_action = CodeAction::new(
+        &format!("Replace {} with {}", var.text(), constant_text),
+        Some(CodeActionKind::RefactorRewrite),
+    );
+    for (range, new_text) in edits {
+        code_action.add_edit(
+            &document.uri,
+            TextEdit::new(
+                Range::from_byte_offset_range(range, &document.text)?,
+                &new_text,
+            ),
+        );
+    }
+    Some(code_action)
+}
+
+/// The `BIND` or `VALUES` binding `var` to a single constant, and that constant.
+fn binding(var: &Var) -> Option<(SyntaxNode, SyntaxNode)> {
+    let parent = var.syntax().parent()?;
+    match parent.kind() {
+        SyntaxKind::Bind => {
+            let expression = parent
+                .children()
+                .find(|child| child.kind() == SyntaxKind::Expression)?;
+            let constant = expression.descendants().find(is_constant)?;
+            (constant.text_range() == expression.text_range())
+                .then_some((parent.parent()?, constant))
+        }
+        SyntaxKind::InlineDataOneVar => {
+            let mut values = parent
+                .children()
+                .filter(|child| child.kind() == SyntaxKind::DataBlockValue);
+            let constant = values.next()?.first_child().filter(is_constant)?;
+            if values.next().is_some() {
+                return None;
+            }
+            // NOTE: InlineDataOneVar > DataBlock > InlineData | ValuesClause
+            let inline_data = parent.parent()?.parent()?;
+            match inline_data.kind() {
+                SyntaxKind::ValuesClause => Some((inline_data, constant)),
+                _ => Some((inline_data.parent()?, constant)),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The part of the query in which `binding` pins the variable.
+///
+/// A binding in the top level group of a query pins it in the whole query,
+/// a binding in a nested group only within that group.
+fn scope(binding: &SyntaxNode) -> Option<SyntaxNode> {
+    if binding.kind() == SyntaxKind::ValuesClause {
+        return binding.parent();
+    }
+    let group = binding
+        .ancestors()
+        .find(|ancestor| ancestor.kind() == SyntaxKind::GroupGraphPattern)?;
+    match group.parent() {
+        Some(where_clause) if where_clause.kind() == SyntaxKind::WhereClause => {
+            where_clause.parent()
+        }
+        _ => Some(group),
+    }
+}
+
+/// The edit removing `binding` along with the whitespace before and a trailing dot.
+fn removal(binding: &SyntaxNode) -> Option<(TextRange, String)> {
+    let previous = next_non_trivia(binding.first_token()?.prev_token(), false)?;
+    let end = match next_non_trivia(binding.last_token()?.next_token(), true) {
+        Some(dot) if dot.kind() == SyntaxKind::Dot => dot.text_range().end(),
+        _ => binding.text_range().end(),
+    };
+    Some((
+        TextRange::new(previous.text_range().end(), end),
+        String::new(),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use ll_sparql_parser::{
+        ast::{AstNode, Var},
+        parse,
+    };
+
+    use super::code_action;
+    use crate::server::lsp::textdocument::TextDocumentItem;
+
+    fn replace(input: &str, name: &str) -> Option<(String, String)> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (root, _) = parse(&document.text);
+        let var = root
+            .descendants()
+            .filter_map(Var::cast)
+            .find(|var| var.var_name() == name)?;
+        let code_action = code_action(&var, &document)?;
+        let edits = code_action.edit.changes?.remove("query.rq")?;
+        document.apply_text_edits(edits);
+        Some((code_action.title, document.text))
+    }
+
+    #[test]
+    fn replace_variable_bound_by_values() {
+        let input = indoc! {
+            "SELECT ?item ?label WHERE {
+               VALUES ?item { wd:Q42 }
+               ?item rdfs:label ?label .
+               FILTER(?item != wd:Q1)
+             }"
+        };
+        assert_eq!(
+            replace(input, "item").unwrap(),
+            (
+                "Replace ?item with wd:Q42".to_string(),
+                indoc! {
+                    "SELECT ?label WHERE {
+                       wd:Q42 rdfs:label ?label .
+                       FILTER(wd:Q42 != wd:Q1)
+                     }
+                     "
+                }
+                .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn replace_variable_bound_by_bind() {
+        let input = indoc! {
+            r#"SELECT * WHERE {
+                 ?s ?p ?o .
+                 BIND("x"@en AS ?o) .
+               }"#
+        };
+        assert_eq!(
+            replace(input, "o").unwrap().1,
+            indoc! {
+                r#"SELECT * WHERE {
+                     ?s ?p "x"@en .
+                   }
+                   "#
+            }
+        );
+        // NOTE: a literal can not be a predicate.
+        assert_eq!(
+            replace("SELECT * WHERE { ?s ?p ?o BIND(1 AS ?p) }", "p"),
+            None
+        );
+    }
+
+    #[test]
+    fn replace_with_constant_not_offered() {
+        for input in [
+            "SELECT ?x WHERE { ?s ?p ?x VALUES ?x { <a> } }",
+            "SELECT * WHERE { ?s ?p ?x VALUES ?x { <a> <b> } }",
+            "SELECT * WHERE { ?s ?p ?x OPTIONAL { BIND(<a> AS ?x) } }",
+            "SELECT * WHERE { ?s ?p ?x BIND(?s AS ?x) }",
+            "SELECT * WHERE { ?s ?p ?x BIND(<a> AS ?x) } ORDER BY ?x",
+        ] {
+            assert_eq!(replace(input, "x"), None, "{input}");
+        }
+    }
+}
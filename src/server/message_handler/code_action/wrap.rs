@@ -0,0 +1,446 @@
+//! Wrap the selected patterns of a group in `OPTIONAL`, `MINUS`,
+//! `FILTER NOT EXISTS`, `GRAPH` or `SERVICE`, and unwrap them again.
+//!
+//! The selection may contain triples and other graph patterns, as long as they
+//! belong to the same group. `SERVICE` is offered for every configured backend
+//! except the default one, just like the completion of service urls.
+
+use std::collections::{HashMap, HashSet};
+
+use ll_sparql_parser::{
+    SyntaxElement, SyntaxNode,
+    ast::{AstNode, QueryUnit, Triple, Var},
+    syntax_kind::SyntaxKind,
+};
+use text_size::TextRange;
+
+use super::sub_select::{line_indentation, splice_group};
+use crate::server::{
+    Server,
+    lsp::{
+        CodeAction, CodeActionKind, WorkspaceEdit,
+        textdocument::{Range, TextDocumentItem, TextEdit},
+    },
+    message_handler::misc::compute_service_prefix,
+};
+
+pub(super) fn wrap(
+    root: &SyntaxNode,
+    range: TextRange,
+    document: &TextDocumentItem,
+    server: &Server,
+    indent: &str,
+) -> Vec<CodeAction> {
+    if range.is_empty() {
+        return vec![];
+    }
+    let Some(wrap_range) = wrap_range(root, range) else {
+        return vec![];
+    };
+    let base = line_indentation(&document.text, wrap_range.start());
+    let body = document.text[wrap_range].replace('\n', &format!("\n{indent}"));
+    let wrapped = |keyword: &str| format!("{keyword} {{\n{base}{indent}{body}\n{base}}}");
+
+    let mut wrappers = vec![
+        ("Wrap in OPTIONAL".to_string(), "OPTIONAL".to_string(), None),
+        ("Wrap in MINUS".to_string(), "MINUS".to_string(), None),
+        (
+            "Wrap in FILTER NOT EXISTS".to_string(),
+            "FILTER NOT EXISTS".to_string(),
+            None,
+        ),
+        (
+            "Wrap in GRAPH".to_string(),
+            format!(
+                "GRAPH ?{}",
+                fresh_variables(root, "g")
+                    .next()
+                    .expect("there is always an unused variable name")
+            ),
+            None,
+        ),
+    ];
+    let query_unit = QueryUnit::cast(root.clone());
+    let default_backend = server.state.get_default_backend();
+    wrappers.extend(
+        server
+            .state
+            .get_all_backends()
+            .into_iter()
+            .filter(|backend| backend.has_endpoint())
+            .filter(|backend| default_backend.is_none_or(|default| backend.name != default.name))
+            .map(|backend| {
+                let (prefix, import_edit) = compute_service_prefix(query_unit.as_ref(), backend);
+                (
+                    format!("Wrap in SERVICE {}", backend.name),
+                    format!("SERVICE {prefix}"),
+                    import_edit,
+                )
+            }),
+    );
+
+    wrappers
+        .into_iter()
+        .filter_map(|(title, keyword, import_edit)| {
+            let mut edits = import_edit.unwrap_or_default();
+            edits.push(TextEdit::new(
+                Range::from_byte_offset_range(wrap_range, &document.text)?,
+                &wrapped(&keyword),
+            ));
+            Some(CodeAction {
+                title,
+                kind: Some(CodeActionKind::RefactorRewrite),
+                diagnostics: vec![],
+                edit: WorkspaceEdit {
+                    changes: Some(HashMap::from_iter([(document.uri.to_string(), edits)])),
+                },
+            })
+        })
+        .collect()
+}
+
+/// The range from the first to the last triple or graph pattern of the
+/// innermost group that intersect `range`.
+fn wrap_range(root: &SyntaxNode, range: TextRange) -> Option<TextRange> {
+    let group = match root.covering_element(range) {
+        SyntaxElement::Node(node) => node.ancestors().find(is_group),
+        SyntaxElement::Token(token) => token.parent_ancestors().find(is_group),
+    }?;
+    let patterns: Vec<SyntaxNode> = group
+        .children()
+        .flat_map(|child| match child.kind() {
+            SyntaxKind::TriplesBlock => child
+                .descendants()
+                .filter(|node| Triple::can_cast(node.kind()))
+                .collect(),
+            SyntaxKind::GraphPatternNotTriples => vec![child],
+            _ => vec![],
+        })
+        .filter(|pattern| {
+            pattern
+                .text_range()
+                .intersect(range)
+                .is_some_and(|intersection| !intersection.is_empty())
+        })
+        .collect();
+    Some(TextRange::new(
+        patterns.first()?.text_range().start(),
+        patterns.last()?.text_range().end(),
+    ))
+}
+
+fn is_group(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::GroupGraphPatternSub
+}
+
+/// `name`, `name1`, `name2`, ... without the names of variables that are
+/// already used in `root`.
+pub(super) fn fresh_variables(root: &SyntaxNode, name: &str) -> impl Iterator<Item = String> {
+    let used: HashSet<String> = root
+        .descendants()
+        .filter_map(Var::cast)
+        .map(|var| var.var_name())
+        .collect();
+    std::iter::once(name.to_string())
+        .chain((1..).map(move |idx| format!("{name}{idx}")))
+        .filter(move |candidate| !used.contains(candidate))
+}
+
+pub(super) fn unwrap(element: &SyntaxElement, document: &TextDocumentItem) -> Option<CodeAction> {
+    let graph_pattern = match element {
+        SyntaxElement::Node(node) => node.ancestors().find(is_graph_pattern),
+        SyntaxElement::Token(token) => token.parent_ancestors().find(is_graph_pattern),
+    }?;
+    let (title, group) = wrapped_group(&graph_pattern)?;
+    // NOTE: only offered on the keywords, not inside of the wrapped group.
+    if element.text_range().end() > group.text_range().start()
+        && element.text_range() != graph_pattern.text_range()
+    {
+        return None;
+    }
+    let edits = match group
+        .children()
+        .find(|child| child.kind() == SyntaxKind::GroupGraphPatternSub)
+    {
+        Some(body) => splice_group(&graph_pattern, Some(&body), document)?,
+        // NOTE: a sub-select keeps its braces
+        None if group
+            .children()
+            .any(|child| child.kind() == SyntaxKind::SubSelect) =>
+        {
+            vec![TextEdit::new(
+                Range::from_byte_offset_range(graph_pattern.text_range(), &document.text)?,
+                &group.text().to_string(),
+            )]
+        }
+        None => splice_group(&graph_pattern, None, document)?,
+    };
+    Some(CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::RefactorRewrite),
+        diagnostics: vec![],
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(document.uri.to_string(), edits)])),
+        },
+    })
+}
+
+fn is_graph_pattern(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::GraphPatternNotTriples
+}
+
+/// The title of the unwrap action and the group wrapped by `graph_pattern`.
+fn wrapped_group(graph_pattern: &SyntaxNode) -> Option<(&'static str, SyntaxNode)> {
+    let pattern = graph_pattern.first_child()?;
+    let title = match pattern.kind() {
+        SyntaxKind::OptionalGraphPattern => "Unwrap OPTIONAL",
+        SyntaxKind::MinusGraphPattern => "Unwrap MINUS",
+        SyntaxKind::GraphGraphPattern => "Unwrap GRAPH",
+        SyntaxKind::ServiceGraphPattern => "Unwrap SERVICE",
+        SyntaxKind::Filter => {
+            // NOTE: Filter > Constraint > BuiltInCall > NotExistsFunc
+            let not_exists = pattern
+                .children()
+                .find(|child| child.kind() == SyntaxKind::Constraint)?
+                .first_child()?
+                .first_child()
+                .filter(|node| node.kind() == SyntaxKind::NotExistsFunc)?;
+            return Some((
+                "Unwrap FILTER NOT EXISTS",
+                not_exists
+                    .children()
+                    .find(|child| child.kind() == SyntaxKind::GroupGraphPattern)?,
+            ));
+        }
+        _ => return None,
+    };
+    Some((
+        title,
+        pattern
+            .children()
+            .find(|child| child.kind() == SyntaxKind::GroupGraphPattern)?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use indoc::indoc;
+    use ll_sparql_parser::parse;
+    use text_size::{TextRange, TextSize};
+
+    use super::{unwrap, wrap};
+    use crate::server::{
+        Server,
+        configuration::BackendConfiguration,
+        lsp::{CodeAction, textdocument::TextDocumentItem},
+    };
+
+    fn apply(document: &mut TextDocumentItem, action: CodeAction) {
+        document.apply_text_edits(
+            action
+                .edit
+                .changes
+                .unwrap()
+                .into_values()
+                .flatten()
+                .collect(),
+        );
+    }
+
+    fn range_of(text: &str, needle: &str) -> TextRange {
+        let start = text.find(needle).unwrap();
+        TextRange::new(
+            TextSize::new(start as u32),
+            TextSize::new((start + needle.len()) as u32),
+        )
+    }
+
+    fn backend(name: &str, url: &str) -> BackendConfiguration {
+        BackendConfiguration {
+            name: name.to_string(),
+            url: url.to_string(),
+            health_check_url: None,
+            engine: None,
+            request_method: None,
+            prefix_map: HashMap::new(),
+            default: false,
+            queries: HashMap::new(),
+            files: Vec::new(),
+            additional_data: None,
+        }
+    }
+
+    fn wrap_with(server: &Server, input: &str, selection: &str, title: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let action = wrap(
+            &tree,
+            range_of(input, selection),
+            &document,
+            server,
+            &server.settings.format.indent_unit(),
+        )
+        .into_iter()
+        .find(|action| action.title == title)?;
+        apply(&mut document, action);
+        Some(document.text)
+    }
+
+    fn unwrap_at(input: &str, keyword: &str) -> Option<String> {
+        let mut document = TextDocumentItem::new("query.rq", input);
+        let (tree, _) = parse(&document.text);
+        let action = unwrap(&tree.covering_element(range_of(input, keyword)), &document)?;
+        apply(&mut document, action);
+        Some(document.text)
+    }
+
+    #[test]
+    fn wrap_triples_in_optional() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?person <name> ?name .
+               ?person <age> ?age ;
+                       <email> ?email
+             }
+             "
+        };
+        let server = Server::new(|_message| {});
+        assert_eq!(
+            wrap_with(
+                &server,
+                input,
+                "<age> ?age ;\n          <email>",
+                "Wrap in OPTIONAL"
+            )
+            .unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   ?person <name> ?name .
+                   OPTIONAL {
+                     ?person <age> ?age ;
+                             <email> ?email
+                   }
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn wrap_with_tabs() {
+        let input = "SELECT * WHERE {\n\t?s ?p ?o\n}\n";
+        let mut server = Server::new(|_message| {});
+        server.settings.format.insert_spaces = Some(false);
+        assert_eq!(
+            wrap_with(&server, input, "?s ?p ?o", "Wrap in MINUS").unwrap(),
+            "SELECT * WHERE {\n\tMINUS {\n\t\t?s ?p ?o\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn wrap_patterns_in_graph_with_fresh_variable() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?g <name> ?name .
+               OPTIONAL { ?g <age> ?age }
+             }
+             "
+        };
+        let server = Server::new(|_message| {});
+        assert_eq!(
+            wrap_with(
+                &server,
+                input,
+                "<name> ?name .\n  OPTIONAL",
+                "Wrap in GRAPH"
+            )
+            .unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   GRAPH ?g1 {
+                     ?g <name> ?name .
+                     OPTIONAL { ?g <age> ?age }
+                   }
+                 }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn wrap_in_service_of_backend() {
+        let input = "SELECT * WHERE { ?s ?p ?o }";
+        let mut server = Server::new(|_message| {});
+        server
+            .state
+            .add_backend(backend("wikidata", "https://qlever.dev/api/wikidata"));
+        server
+            .state
+            .add_backend(backend("osm", "https://qlever.dev/api/osm-planet"));
+        server.state.set_default_backend("wikidata".to_string());
+        assert_eq!(
+            wrap_with(&server, input, "?s ?p ?o", "Wrap in SERVICE wikidata"),
+            None
+        );
+        assert_eq!(
+            wrap_with(&server, input, "?s ?p ?o", "Wrap in SERVICE osm").unwrap(),
+            indoc! {
+                "PREFIX osm-service: <https://qlever.dev/api/osm-planet>
+                 SELECT * WHERE { SERVICE osm-service: {
+                   ?s ?p ?o
+                 } }
+                 "
+            }
+        );
+    }
+
+    #[test]
+    fn no_wrap_in_service_of_backend_without_endpoint() {
+        let input = "SELECT * WHERE { ?s ?p ?o }";
+        let mut server = Server::new(|_message| {});
+        server.state.add_backend(BackendConfiguration {
+            files: vec!["ontology.ttl".to_string()],
+            ..backend("local", "")
+        });
+        assert_eq!(
+            wrap_with(&server, input, "?s ?p ?o", "Wrap in SERVICE local"),
+            None
+        );
+    }
+
+    #[test]
+    fn unwrap_optional() {
+        let input = indoc! {
+            "SELECT * WHERE {
+               ?person <name> ?name
+               OPTIONAL {
+                 ?person <age> ?age
+               }
+             }
+             "
+        };
+        assert_eq!(
+            unwrap_at(input, "OPTIONAL").unwrap(),
+            indoc! {
+                "SELECT * WHERE {
+                   ?person <name> ?name .
+                   ?person <age> ?age
+                 }
+                 "
+            }
+        );
+        assert_eq!(unwrap_at(input, "<age>"), None);
+    }
+
+    #[test]
+    fn unwrap_filter_not_exists() {
+        let input = "SELECT * WHERE { ?s ?p ?o FILTER NOT EXISTS { ?s <q> ?o } }";
+        assert_eq!(
+            unwrap_at(input, "EXISTS").unwrap(),
+            "SELECT * WHERE { ?s ?p ?o . ?s <q> ?o }\n"
+        );
+        assert_eq!(unwrap_at("SELECT * WHERE { FILTER (?a) }", "FILTER"), None);
+    }
+}
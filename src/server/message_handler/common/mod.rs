@@ -1,13 +1,50 @@
 //! Shared analysis helpers used by multiple LSP feature handlers.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use ll_sparql_parser::{
     SyntaxNode,
-    ast::{AstNode, SelectQuery, Var},
+    ast::{AstNode, Iri, PrefixedName, QueryUnit, SelectQuery, Var},
     syntax_kind::SyntaxKind,
 };
 
+use crate::server::configuration::Replacements;
+
+/// The full IRI of `iri`, a prefixed name is expanded with the `PREFIX`
+/// declarations of `query_unit`.
+pub(crate) fn expand_iri(iri: &Iri, query_unit: &QueryUnit) -> Option<String> {
+    if let Some(raw_iri) = iri.raw_iri() {
+        return Some(raw_iri);
+    }
+    let prefixed_name = iri.prefixed_name()?;
+    query_unit
+        .prologue()?
+        .prefix_declarations()
+        .iter()
+        .find(|declaration| {
+            declaration
+                .prefix()
+                .is_some_and(|prefix| prefix == prefixed_name.prefix())
+        })
+        .and_then(|declaration| declaration.raw_uri_prefix())
+        .map(|uri_prefix| format!("{uri_prefix}{}", prefixed_name.name()))
+}
+
+/// The prefixes of all prefixed names in `root`, a `QueryUnit` or an
+/// `UpdateUnit`, outside of its prologue.
+pub(crate) fn used_prefixes(root: &SyntaxNode) -> HashSet<String> {
+    // NOTE: the prologues of an update hold no prefixed names either.
+    let body = match QueryUnit::cast(root.clone()) {
+        Some(query_unit) => query_unit.strip_prologue(),
+        None => Some(root.clone()),
+    };
+    body.iter()
+        .flat_map(|node| node.descendants())
+        .filter_map(PrefixedName::cast)
+        .map(|prefixed_name| prefixed_name.prefix())
+        .collect()
+}
+
 /// Find all occurrences of the variable `trigger` that denote the same variable.
 ///
 /// Two occurrences of a variable with the same name denote the same variable,
@@ -111,6 +148,75 @@ fn is_var_name_char(c: char) -> bool {
         || matches!(c, '\u{00B7}' | '\u{0300}'..='\u{036F}' | '\u{203F}'..='\u{2040}')
 }
 
+/// Names a variable after `name`, the label or local name of a term: the
+/// configured object variable `replacements` are applied first, then the result
+/// is turned into a valid variable name by [`to_sparql_variable`].
+pub(crate) fn object_variable_name(replacements: Option<&Replacements>, name: &str) -> String {
+    match replacements {
+        Some(replacements) => to_sparql_variable(&replacements.apply_object_variable(name)),
+        None => to_sparql_variable(name),
+    }
+}
+
+/// Transforms an arbitrary string into a valid SPARQL variable name.
+///
+/// SPARQL variable names must:
+/// - Start with a letter (A-Z, a-z) or underscore
+/// - Contain only letters, digits, underscores
+/// - Be prefixed with '?' or '$'
+///
+/// This function:
+/// - Removes the '?' or '$' prefix if present
+/// - Replaces invalid characters with underscores
+/// - Ensures the name starts with a valid character
+/// - Returns the variable name WITHOUT '?' or '$' prefix
+pub(crate) fn to_sparql_variable(s: &str) -> String {
+    if s.is_empty() {
+        return "var".to_string();
+    }
+
+    // NOTE: first pass: convert into snace_case
+    let s = &s
+        .trim()
+        .to_lowercase()
+        .replace([' ', '-', ',', '.', '(', ')', '$', '?'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_");
+
+    let mut result = String::new();
+    let mut chars = s.chars();
+
+    // Handle first character - must be letter or underscore
+    if let Some(first) = chars.next() {
+        if first.is_ascii_alphabetic() || first == '_' {
+            result.push(first);
+        } else if first.is_ascii_digit() {
+            // If starts with digit, prefix with underscore
+            result.push('_');
+            result.push(first);
+        } else {
+            // Replace invalid first char with underscore
+            result.push('_');
+        }
+    }
+
+    // Process remaining characters
+    for c in chars {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            result.push(c);
+        } else {
+            result.push('_');
+        }
+    }
+
+    // Ensure we have at least some content
+    if result.is_empty() {
+        result.push_str("var");
+    }
+    result
+}
+
 /// Register all scope boundaries enclosing `node` and return the region id
 /// of the innermost one (the region `node` belongs to).
 fn register_regions(
@@ -330,4 +436,66 @@ mod tests {
             assert!(!is_valid_variable_name(name), "?{name} should be invalid");
         }
     }
+
+    /// The full object variable name pipeline: configured replacements first,
+    /// then the built in snake_case conversion.
+    fn pipeline(name: &str) -> String {
+        object_variable_name(Some(&Replacements::default()), name)
+    }
+
+    #[test]
+    fn test_to_sparql_variable_converts_to_snake_case() {
+        assert_eq!(to_sparql_variable("BirthDate"), "birthdate");
+        assert_eq!(to_sparql_variable("birth date"), "birth_date");
+        assert_eq!(to_sparql_variable("part-of"), "part_of");
+        assert_eq!(to_sparql_variable("place.of.birth"), "place_of_birth");
+        assert_eq!(to_sparql_variable("label (english)"), "label_english");
+    }
+
+    #[test]
+    fn test_to_sparql_variable_strips_variable_prefix() {
+        assert_eq!(to_sparql_variable("?author"), "author");
+        assert_eq!(to_sparql_variable("$author"), "author");
+    }
+
+    #[test]
+    fn test_to_sparql_variable_produces_valid_names() {
+        assert_eq!(to_sparql_variable(""), "var");
+        assert_eq!(to_sparql_variable("   "), "var");
+        assert_eq!(to_sparql_variable("2020"), "_2020");
+        assert_eq!(to_sparql_variable("_private"), "_private");
+        assert_eq!(to_sparql_variable("a/b"), "a_b");
+    }
+
+    #[test]
+    fn test_to_sparql_variable_trims_and_collapses_whitespace() {
+        assert_eq!(to_sparql_variable("  date of  birth "), "date_of_birth");
+    }
+
+    #[test]
+    fn test_pipeline_with_default_replacements() {
+        assert_eq!(pipeline("hasAuthor"), "author");
+        assert_eq!(pipeline("authoredBy"), "author");
+        assert_eq!(pipeline("has author"), "author");
+        assert_eq!(pipeline("P31"), "p31");
+    }
+
+    #[test]
+    fn test_pipeline_keeps_word_boundaries_as_snake_case() {
+        // NOTE: multi word names keep their boundaries, whether they are
+        // separated by whitespace, punctuation or camelCase.
+        assert_eq!(pipeline("has birth date"), "birth_date");
+        assert_eq!(pipeline("place of birth"), "place_of_birth");
+        assert_eq!(pipeline("hasBirthDate"), "birth_date");
+        assert_eq!(pipeline("birthDate"), "birth_date");
+        assert_eq!(pipeline("place-of-birth"), "place_of_birth");
+        assert_eq!(pipeline("hasPlaceOfBirth"), "place_of_birth");
+    }
+
+    #[test]
+    fn test_pipeline_produces_valid_names_for_degenerate_input() {
+        assert_eq!(pipeline("///"), "var");
+        assert_eq!(pipeline(""), "var");
+        assert_eq!(pipeline("P31/P279*"), "p31_p279");
+    }
 }
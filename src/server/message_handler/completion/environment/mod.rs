@@ -16,7 +16,7 @@ use indoc::indoc;
 use ll_sparql_parser::{
     SyntaxNode, SyntaxToken,
     ast::{AstNode, BlankPropertyList, GroupClause, InlineData, QueryUnit, SelectClause, Triple},
-    continuations_at, parse,
+    continuations_at, parse, parse_query,
     syntax_kind::SyntaxKind,
 };
 use std::{collections::HashSet, fmt::Display, rc::Rc, vec};
@@ -262,6 +262,40 @@ pub(super) enum CompletionLocation {
     /// }
     /// ```
     BuiltInCall,
+
+    /// Prefix of a PREFIX declaration
+    ///
+    /// ---
+    ///
+    /// **Example**
+    /// ```sparql
+    /// PREFIX >here<
+    /// ```
+    PrefixDeclaration,
+
+    /// Datatype of a literal
+    ///
+    /// ---
+    ///
+    /// **Example**
+    /// ```sparql
+    /// SELECT * WHERE {
+    ///   ?s ?p "42"^^>here<
+    /// }
+    /// ```
+    Datatype,
+
+    /// Language tag of a literal, with the verb of the triple the literal is an object of
+    ///
+    /// ---
+    ///
+    /// **Example**
+    /// ```sparql
+    /// SELECT * WHERE {
+    ///   ?s rdfs:label "Berlin"@>here<
+    /// }
+    /// ```
+    LanguageTag(Option<SyntaxNode>),
 }
 
 impl CompletionEnvironment {
@@ -333,7 +367,7 @@ impl CompletionEnvironment {
         let trigger_kind = request.get_completion_context().trigger_kind.clone();
         let trigger_character = request.get_completion_context().trigger_character.clone();
 
-        let truncated_tree = parse(&document.text[..trigger_offset.into()]).0;
+        let (truncated_tree, insert_template) = parse_truncated(&document.text, trigger_offset);
         let trigger_token = get_trigger_token(&truncated_tree, trigger_offset);
         let backend = trigger_token
             .as_ref()
@@ -353,10 +387,22 @@ impl CompletionEnvironment {
 
         let following_kind =
             get_following_token(&full_tree, trigger_offset).map(|token| token.kind());
-        let search_term = get_search_term(&truncated_tree, &anchor_token, trigger_offset);
+        let mut search_term = get_search_term(&truncated_tree, &anchor_token, trigger_offset);
         let continuations = get_continuations(&truncated_tree, &anchor_token);
         let mut location = get_location(&anchor_token, &continuations, trigger_offset);
-        let context = context(&location);
+        if let Some((suffix_location, suffix)) =
+            get_literal_suffix_location(&truncated_tree, trigger_offset)
+        {
+            location = suffix_location;
+            search_term = suffix;
+        }
+        // NOTE: the triples of an INSERT template are not in the dataset yet,
+        // they do not narrow the completion.
+        let context = if insert_template {
+            None
+        } else {
+            context(&location)
+        };
         let mut replace_range = get_replace_range(&document_position.position, &search_term);
 
         // NOTE: In a select binding a partially typed aggregate may be preceded by
@@ -406,6 +452,54 @@ impl CompletionEnvironment {
     }
 }
 
+/// Parses `text` up to `offset`.
+///
+/// Inside the template of an update, e.g. `INSERT DATA { <a> | }`, the triples
+/// are parsed as `Quads`, which are not localized. There the template is parsed
+/// as the pattern of an `ASK` query instead: the text between the prologue and
+/// the `{` of the template is blanked and ends with `ASK`, so every offset stays
+/// the same.
+///
+/// Also returns if the cursor is in the template of an `INSERT`.
+fn parse_truncated(text: &str, offset: TextSize) -> (SyntaxNode, bool) {
+    let truncated = &text[..offset.into()];
+    let tree = parse(truncated).0;
+    let anchor_token = get_trigger_token(&tree, offset).and_then(get_anchor_token);
+    let Some(template) = anchor_token.and_then(|token| {
+        token.parent_ancestors().find(|ancestor| {
+            matches!(
+                ancestor.kind(),
+                SyntaxKind::QuadData | SyntaxKind::QuadPattern
+            )
+        })
+    }) else {
+        return (tree, false);
+    };
+    let insert_template = template.parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            SyntaxKind::InsertData | SyntaxKind::InsertClause
+        )
+    });
+    let prologue_end: usize = tree
+        .descendants()
+        .find(|node| node.kind() == SyntaxKind::Prologue)
+        .map_or(0.into(), |prologue| prologue.text_range().end())
+        .into();
+    // NOTE: the template is preceded by at least the `INSERT` or `DELETE` keyword.
+    let template_start: usize = template.text_range().start().into();
+    let mut query = truncated[..prologue_end].to_string();
+    for char in truncated[prologue_end..template_start].chars() {
+        match char {
+            '\n' => query.push('\n'),
+            _ => query.push_str(&" ".repeat(char.len_utf8())),
+        }
+    }
+    query.replace_range(template_start - 3..template_start, "ASK");
+    query.push_str(&truncated[template_start..]);
+    (parse_query(&query).0, insert_template)
+}
+
 /// Returns the first non-trivia token at or after `offset` in `root`.
 ///
 /// Used for look-ahead past the cursor: trivia (whitespace and comments) are
@@ -420,6 +514,63 @@ fn get_following_token(root: &SyntaxNode, offset: TextSize) -> Option<SyntaxToke
         .find(|token| !token.kind().is_trivia())
 }
 
+/// Returns the location after the `^^` or `@` of a literal, and the part of the
+/// datatype or language tag typed so far.
+///
+/// The suffix of an unfinished literal is lexed as part of the literal or as
+/// error tokens, so it is found by walking back to the closing quote instead of
+/// the continuations.
+fn get_literal_suffix_location(
+    root: &SyntaxNode,
+    offset: TextSize,
+) -> Option<(CompletionLocation, Option<String>)> {
+    let mut token = get_trigger_token(root, offset)?;
+    while !matches!(
+        token.kind(),
+        SyntaxKind::STRING_LITERAL1
+            | SyntaxKind::STRING_LITERAL2
+            | SyntaxKind::STRING_LITERAL_LONG1
+            | SyntaxKind::STRING_LITERAL_LONG2
+    ) {
+        // NOTE: there is no trivia between a literal and its suffix.
+        if token.kind().is_trivia() {
+            return None;
+        }
+        token = token.prev_token()?;
+    }
+    if token.text_range().end() >= offset {
+        return None;
+    }
+    let suffix = root
+        .text()
+        .slice(TextRange::new(token.text_range().end(), offset))
+        .to_string();
+    let typed = |typed: &str| (!typed.is_empty()).then(|| typed.to_string());
+    if let Some(datatype) = suffix.strip_prefix("^^") {
+        datatype
+            .chars()
+            .all(|char| char.is_alphanumeric() || ":_-<>/#".contains(char))
+            .then(|| (CompletionLocation::Datatype, typed(datatype)))
+    } else if let Some(language_tag) = suffix.strip_prefix('@') {
+        // NOTE: the verb of an object: ObjectList(Path) > ... > literal
+        let verb = token
+            .parent_ancestors()
+            .find(|ancestor| {
+                matches!(
+                    ancestor.kind(),
+                    SyntaxKind::ObjectListPath | SyntaxKind::ObjectList
+                )
+            })
+            .and_then(|object_list| object_list.prev_sibling());
+        language_tag
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-')
+            .then(|| (CompletionLocation::LanguageTag(verb), typed(language_tag)))
+    } else {
+        None
+    }
+}
+
 fn get_search_term(
     root: &SyntaxNode,
     anchor_token: &Option<SyntaxToken>,
@@ -541,6 +692,9 @@ fn get_location(
         } else if (continues_with!([SyntaxKind::VarOrIri])
             && child_of!([SyntaxKind::GraphGraphPattern]))
             || continues_with!([SyntaxKind::DefaultGraphClause, SyntaxKind::SourceSelector])
+            // NOTE: the target of LOAD, CLEAR, DROP, ... and the graph of WITH.
+            || (continues_with!([SyntaxKind::iri]) && child_of!([SyntaxKind::GraphRef]))
+            || anchor.kind() == SyntaxKind::WITH
         {
             CompletionLocation::Graph
         }
@@ -595,6 +749,10 @@ fn get_location(
                 None => CompletionLocation::Unknown,
             }
         }
+        // NOTE: PrefixDeclaration
+        else if continues_with!([SyntaxKind::PNAME_NS]) && child_of!([SyntaxKind::PrefixDecl]) {
+            CompletionLocation::PrefixDeclaration
+        }
         //NOTE: BuiltInCall
         else if continues_with!([
             SyntaxKind::Constraint,
@@ -709,6 +867,7 @@ fn get_anchor_token(mut trigger_token: SyntaxToken) -> Option<SyntaxToken> {
             | SyntaxKind::RCurly
             | SyntaxKind::RParen
             | SyntaxKind::Slash
+            | SyntaxKind::Pipe
             | SyntaxKind::Zirkumflex
             | SyntaxKind::ANON
             | SyntaxKind::LParen
@@ -3,7 +3,8 @@ use ll_sparql_parser::{SyntaxToken, parse_query, syntax_kind::SyntaxKind};
 use crate::server::message_handler::completion::environment::CompletionLocation;
 
 use super::{
-    get_anchor_token, get_continuations, get_following_token, get_location, get_trigger_token,
+    get_anchor_token, get_continuations, get_following_token, get_literal_suffix_location,
+    get_location, get_trigger_token, parse_truncated,
 };
 
 fn match_location_at_offset(input: &str, matcher: CompletionLocation, offset: u32) -> bool {
@@ -145,6 +146,13 @@ fn localize_subject_2() {
         CompletionLocation::Subject,
         21
     ));
+    //           0123456789
+    let input = "PREFIX sch";
+    assert!(match_location_at_offset(
+        input,
+        CompletionLocation::PrefixDeclaration,
+        10
+    ));
 }
 
 #[test]
@@ -230,6 +238,18 @@ fn localize_predicate_5() {
     ));
 }
 
+#[test]
+fn localize_predicate_after_path_operators() {
+    //           0123456789012345678901234
+    let input = "Select * { ?a <p0>/<p1>|^ }";
+    for offset in [19, 24, 25] {
+        assert!(
+            matches!(location(input, offset), CompletionLocation::Predicate(_)),
+            "offset {offset}"
+        );
+    }
+}
+
 #[test]
 fn localize_object_1() {
     //           01234567890123456789
@@ -339,6 +359,65 @@ fn localize_group_condition_does_not_shadow_order_condition() {
     ));
 }
 
+#[test]
+fn localize_prefix_declaration() {
+    //           0123456789
+    let input = "PREFIX  ";
+    assert!(match_location_at_offset(
+        input,
+        CompletionLocation::PrefixDeclaration,
+        7
+    ));
+    //           01234567890123456789012345678
+    let input = "PREFIX a: <a>\nPREFIX \nSELECT";
+    assert!(match_location_at_offset(
+        input,
+        CompletionLocation::PrefixDeclaration,
+        21
+    ));
+}
+
+fn literal_suffix(input: &str) -> Option<(CompletionLocation, Option<String>)> {
+    let (root, _) = parse_query(input);
+    get_literal_suffix_location(&root, (input.len() as u32).into())
+}
+
+#[test]
+fn localize_datatype() {
+    assert_eq!(
+        literal_suffix(r#"SELECT * WHERE { ?s ?p "42"^^"#),
+        Some((CompletionLocation::Datatype, None))
+    );
+    assert_eq!(
+        literal_suffix(r#"SELECT * WHERE { FILTER(?o = "42"^^xsd:in"#),
+        Some((CompletionLocation::Datatype, Some("xsd:in".to_string())))
+    );
+    assert_eq!(literal_suffix(r#"SELECT * WHERE { ?s ?p "42" "#), None);
+    assert_eq!(
+        literal_suffix(r#"SELECT * WHERE { ?s ?p "42"^^xsd:int)"#),
+        None
+    );
+}
+
+#[test]
+fn localize_language_tag() {
+    let Some((CompletionLocation::LanguageTag(Some(verb)), search_term)) =
+        literal_suffix(r#"SELECT * WHERE { ?s rdfs:label "Berlin"@"#)
+    else {
+        panic!("expected a language tag location with a verb");
+    };
+    assert_eq!(verb.text().to_string(), "rdfs:label");
+    assert_eq!(search_term, None);
+    assert_eq!(
+        literal_suffix(r#"SELECT * WHERE { FILTER(?o = "Berlin"@en--"#),
+        Some((
+            CompletionLocation::LanguageTag(None),
+            Some("en--".to_string())
+        ))
+    );
+    assert_eq!(literal_suffix(r#"SELECT * WHERE { ?s ?p "a@"#), None);
+}
+
 // --- InlineData (VALUES) location tests ---
 
 #[test]
@@ -500,3 +579,70 @@ fn following_token_without_terminator() {
         Some(SyntaxKind::RCurly)
     );
 }
+
+// --- SPARQL Update location tests ---
+
+fn update_location(input: &str) -> (CompletionLocation, bool) {
+    let offset = (input.len() as u32).into();
+    let (root, insert_template) = parse_truncated(input, offset);
+    let trigger_token = get_trigger_token(&root, offset);
+    let anchor = trigger_token.and_then(get_anchor_token);
+    let continuations = get_continuations(&root, &anchor);
+    (
+        get_location(&anchor, &continuations, offset),
+        insert_template,
+    )
+}
+
+#[test]
+fn localize_insert_data_template() {
+    assert_eq!(
+        update_location("INSERT DATA { "),
+        (CompletionLocation::Subject, true)
+    );
+    assert!(matches!(
+        update_location("PREFIX ex: <x>\nINSERT DATA { ex:a "),
+        (CompletionLocation::Predicate(_), true)
+    ));
+    assert!(matches!(
+        update_location("INSERT DATA { <a> <b> <c> ; <d> "),
+        (CompletionLocation::Object(_), true)
+    ));
+}
+
+#[test]
+fn localize_delete_templates() {
+    assert!(matches!(
+        update_location("DELETE DATA { GRAPH <g> { <a> "),
+        (CompletionLocation::Predicate(_), false)
+    ));
+    assert!(matches!(
+        update_location("WITH <g> DELETE { ?s <p> "),
+        (CompletionLocation::Object(_), false)
+    ));
+    assert!(matches!(
+        update_location("DELETE WHERE { ?s "),
+        (CompletionLocation::Predicate(_), false)
+    ));
+    assert!(matches!(
+        update_location("DELETE { ?s <p> ?o } WHERE { ?s "),
+        (CompletionLocation::Predicate(_), false)
+    ));
+}
+
+#[test]
+fn localize_update_graph_targets() {
+    for input in [
+        "WITH ",
+        "LOAD <x> INTO GRAPH ",
+        "CLEAR GRAPH ",
+        "DROP SILENT GRAPH ",
+        "INSERT DATA { GRAPH ",
+    ] {
+        assert_eq!(
+            update_location(input).0,
+            CompletionLocation::Graph,
+            "{input}"
+        );
+    }
+}
@@ -1,12 +1,92 @@
+use super::super::{
+    CompletionEnvironment,
+    utils::{compact_with_prefix, get_prefix_declarations},
+};
 use crate::server::{
+    extension_catalog::{ExtensionKind, extensions},
     lsp::{
         CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat, ItemDefaults,
     },
     message_handler::completion::CompletionError,
 };
 
-pub(crate) async fn completions() -> Result<CompletionList, CompletionError> {
-    Ok(CompletionList {
+pub(crate) async fn completions(
+    environment: &CompletionEnvironment,
+) -> Result<CompletionList, CompletionError> {
+    let mut completion_list = built_in_calls();
+    if let Some(engine) = environment
+        .backend
+        .as_ref()
+        .and_then(|backend| backend.engine.as_ref())
+    {
+        let declarations = get_prefix_declarations(&environment.full_tree).await;
+        completion_list.items.extend(
+            extensions(engine)
+                .filter(|extension| extension.kind == ExtensionKind::Function)
+                .map(|extension| {
+                    let (function, import_edit) = compact_with_prefix(
+                        &declarations,
+                        extension.namespace.prefix,
+                        extension.namespace.uri,
+                        extension.name,
+                    );
+                    let item = CompletionItemBuilder::new()
+                        .label(&function)
+                        .label_details(&format!(" {}", extension.signature))
+                        .kind(CompletionItemKind::Function)
+                        .detail(&format!("{engine:?} extension"))
+                        .documentation(extension.documentation)
+                        .insert_text(&format!("{function}{}", snippet(extension.signature)));
+                    match import_edit {
+                        Some(import_edit) => item.additional_text_edits(vec![import_edit]),
+                        None => item,
+                    }
+                    .build()
+                }),
+        );
+    }
+    Ok(completion_list)
+}
+
+/// The snippet of the argument list of a function signature like `(a, b [, c])`,
+/// without the optional arguments.
+fn snippet(signature: &str) -> String {
+    let mut required = String::new();
+    let mut depth = 0;
+    for char in signature
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .chars()
+    {
+        match char {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 => required.push(char),
+            _ => {}
+        }
+    }
+    let arguments: Vec<&str> = required
+        .split(',')
+        .map(str::trim)
+        .filter(|argument| !argument.is_empty() && *argument != "...")
+        .collect();
+    let placeholders: Vec<String> = arguments
+        .iter()
+        .enumerate()
+        .map(|(idx, argument)| {
+            let number = if idx + 1 == arguments.len() {
+                0
+            } else {
+                idx + 1
+            };
+            format!("${{{number}:{argument}}}")
+        })
+        .collect();
+    format!("({})", placeholders.join(", "))
+}
+
+fn built_in_calls() -> CompletionList {
+    CompletionList {
         is_incomplete: false,
         item_defaults: Some(ItemDefaults {
             edit_range: None,
@@ -516,5 +596,5 @@ pub(crate) async fn completions() -> Result<CompletionList, CompletionError> {
                 .insert_text("GROUP_CONCAT(${1:expr}; SEPARATOR = \"${0:,}\")")
                 .build(),
         ],
-    })
+    }
 }
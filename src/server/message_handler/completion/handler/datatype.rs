@@ -0,0 +1,114 @@
+use super::super::{
+    CompletionEnvironment,
+    error::CompletionError,
+    utils::{compact_with_prefix, get_prefix_declarations},
+};
+use crate::server::{
+    Server,
+    lsp::{
+        CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat,
+        textdocument::TextEdit,
+    },
+};
+use futures::lock::Mutex;
+use std::rc::Rc;
+
+/// Conventional prefix and namespace.
+type Namespace = (&'static str, &'static str);
+
+const XSD: Namespace = ("xsd", "http://www.w3.org/2001/XMLSchema#");
+const RDF: Namespace = ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#");
+
+/// The XSD and RDF datatypes of literals, with a short description.
+const DATATYPES: &[(Namespace, &str, &str)] = &[
+    (XSD, "string", "Character string"),
+    (XSD, "boolean", "true or false"),
+    (XSD, "integer", "Integer number"),
+    (XSD, "decimal", "Decimal number"),
+    (XSD, "double", "64-bit floating point number"),
+    (XSD, "float", "32-bit floating point number"),
+    (XSD, "date", "Calendar date"),
+    (XSD, "dateTime", "Date and time with optional timezone"),
+    (XSD, "dateTimeStamp", "Date and time with required timezone"),
+    (XSD, "time", "Time of day"),
+    (XSD, "duration", "Duration of time"),
+    (XSD, "dayTimeDuration", "Duration in days and time"),
+    (XSD, "yearMonthDuration", "Duration of years and months"),
+    (XSD, "gYear", "Gregorian calendar year"),
+    (XSD, "gYearMonth", "Gregorian calendar month of a year"),
+    (XSD, "gMonth", "Gregorian calendar month"),
+    (XSD, "gMonthDay", "Gregorian calendar day of a month"),
+    (XSD, "gDay", "Gregorian calendar day"),
+    (XSD, "long", "64-bit integer"),
+    (XSD, "int", "32-bit integer"),
+    (XSD, "short", "16-bit integer"),
+    (XSD, "byte", "8-bit integer"),
+    (XSD, "nonNegativeInteger", "Integer >= 0"),
+    (XSD, "positiveInteger", "Integer > 0"),
+    (XSD, "nonPositiveInteger", "Integer <= 0"),
+    (XSD, "negativeInteger", "Integer < 0"),
+    (XSD, "unsignedLong", "64-bit integer >= 0"),
+    (XSD, "unsignedInt", "32-bit integer >= 0"),
+    (XSD, "unsignedShort", "16-bit integer >= 0"),
+    (XSD, "unsignedByte", "8-bit integer >= 0"),
+    (XSD, "anyURI", "Absolute or relative URI"),
+    (XSD, "language", "Language tag"),
+    (XSD, "normalizedString", "String without line breaks"),
+    (XSD, "token", "Whitespace collapsed string"),
+    (XSD, "hexBinary", "Hex encoded binary data"),
+    (XSD, "base64Binary", "Base64 encoded binary data"),
+    (RDF, "langString", "String with a language tag"),
+    (
+        RDF,
+        "dirLangString",
+        "String with a language tag and direction",
+    ),
+    (RDF, "HTML", "HTML fragment"),
+    (RDF, "XMLLiteral", "XML fragment"),
+    (RDF, "JSON", "JSON value"),
+];
+
+pub async fn completions(
+    server_rc: Rc<Mutex<Server>>,
+    environment: &CompletionEnvironment,
+) -> Result<CompletionList, CompletionError> {
+    let server = server_rc.lock().await;
+    let declarations = get_prefix_declarations(&environment.full_tree).await;
+    Ok(CompletionList {
+        is_incomplete: false,
+        item_defaults: None,
+        items: DATATYPES
+            .iter()
+            .enumerate()
+            .map(|(idx, ((prefix, namespace), name, description))| {
+                let iri = format!("{namespace}{name}");
+                // NOTE: the backend may know the namespace by another prefix.
+                let prefix = server
+                    .shorten_uri(
+                        &iri,
+                        environment
+                            .backend
+                            .as_ref()
+                            .map(|backend| backend.name.as_str()),
+                    )
+                    .filter(|(_, uri_prefix, _)| uri_prefix == namespace)
+                    .map_or(prefix.to_string(), |(prefix, _, _)| prefix);
+                let (text, import_edit) =
+                    compact_with_prefix(&declarations, &prefix, namespace, name);
+                let item = CompletionItemBuilder::new()
+                    .label(&text)
+                    .kind(CompletionItemKind::TypeParameter)
+                    .detail(description)
+                    .documentation(&iri)
+                    .sort_text(&format!("{idx:0>5}"))
+                    .text_edit(TextEdit::new(environment.replace_range.clone(), &text))
+                    .insert_text_format(InsertTextFormat::PlainText);
+                match import_edit {
+                    Some(import_edit) => item.additional_text_edits(vec![import_edit]),
+                    None => item,
+                }
+                .build()
+            })
+            .collect(),
+    })
+}
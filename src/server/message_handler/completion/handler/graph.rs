@@ -1,11 +1,43 @@
-use crate::server::lsp::{
-    CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat,
+use std::rc::Rc;
+
+use futures::lock::Mutex;
+
+use crate::server::{
+    Server,
+    lsp::{CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat},
 };
 
-use super::{super::environment::CompletionEnvironment, super::error::CompletionError};
+use super::super::{
+    environment::CompletionEnvironment,
+    error::CompletionError,
+    utils::{CompletionTemplate, dispatch_completion_query},
+};
+
+pub async fn completions(
+    server_rc: Rc<Mutex<Server>>,
+    environment: &CompletionEnvironment,
+) -> Result<CompletionList, CompletionError> {
+    let template_context = environment.template_context().await;
+    match dispatch_completion_query(
+        server_rc,
+        environment,
+        template_context,
+        CompletionTemplate::GraphCompletion,
+        false,
+    )
+    .await
+    {
+        Ok(completion_list) => Ok(completion_list),
+        Err(err) => {
+            // NOTE: without a (reachable) backend there are no graphs to suggest.
+            tracing::info!("Graph completion query failed:\n{:?}", err);
+            Ok(placeholder())
+        }
+    }
+}
 
-pub fn completions(_context: &CompletionEnvironment) -> Result<CompletionList, CompletionError> {
-    Ok(CompletionList {
+fn placeholder() -> CompletionList {
+    CompletionList {
         is_incomplete: false,
         item_defaults: None,
         items: vec![
@@ -16,5 +48,5 @@ pub fn completions(_context: &CompletionEnvironment) -> Result<CompletionList, C
                 .insert_text_format(InsertTextFormat::PlainText)
                 .build(),
         ],
-    })
+    }
 }
@@ -0,0 +1,130 @@
+use super::super::{
+    CompletionEnvironment,
+    environment::CompletionLocation,
+    error::CompletionError,
+    utils::{CompletionTemplate, dispatch_completion_query},
+};
+use crate::server::{
+    Server,
+    lsp::{
+        CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat,
+        textdocument::TextEdit,
+    },
+};
+use futures::lock::Mutex;
+use std::rc::Rc;
+
+/// Common language tags, with the name of the language.
+const LANGUAGE_TAGS: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("ru", "Russian"),
+    ("zh", "Chinese"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("ar", "Arabic"),
+    ("hi", "Hindi"),
+    ("pl", "Polish"),
+    ("sv", "Swedish"),
+    ("da", "Danish"),
+    ("nb", "Norwegian Bokmål"),
+    ("fi", "Finnish"),
+    ("cs", "Czech"),
+    ("el", "Greek"),
+    ("he", "Hebrew"),
+    ("hu", "Hungarian"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("ro", "Romanian"),
+    ("bg", "Bulgarian"),
+    ("ca", "Catalan"),
+    ("fa", "Persian"),
+    ("id", "Indonesian"),
+    ("vi", "Vietnamese"),
+    ("th", "Thai"),
+    ("la", "Latin"),
+    ("mul", "Multiple languages"),
+    ("en-GB", "English (United Kingdom)"),
+    ("en-US", "English (United States)"),
+    ("pt-BR", "Portuguese (Brazil)"),
+    ("zh-Hans", "Chinese (Simplified)"),
+    ("zh-Hant", "Chinese (Traditional)"),
+];
+
+pub async fn completions(
+    server_rc: Rc<Mutex<Server>>,
+    environment: &CompletionEnvironment,
+) -> Result<CompletionList, CompletionError> {
+    let mut language_tags: Vec<(String, String)> = Vec::new();
+    // NOTE: the tags used with the predicate go first, most frequent first.
+    if let CompletionLocation::LanguageTag(Some(verb)) = &environment.location {
+        let mut template_context = environment.template_context().await;
+        template_context.insert("predicate", &verb.text().to_string());
+        match dispatch_completion_query(
+            server_rc,
+            environment,
+            template_context,
+            CompletionTemplate::LanguageTagCompletion,
+            false,
+        )
+        .await
+        {
+            Ok(completion_list) => {
+                language_tags.extend(completion_list.items.into_iter().map(|item| {
+                    (
+                        item.label.trim_matches('"').to_string(),
+                        format!("Used with {}", verb.text()),
+                    )
+                }))
+            }
+            Err(err) => {
+                tracing::info!("Language tag completion query failed:\n{:?}", err);
+            }
+        }
+    }
+    for (tag, language) in LANGUAGE_TAGS {
+        if !language_tags
+            .iter()
+            .any(|(used, _)| used.eq_ignore_ascii_case(tag))
+        {
+            language_tags.push((tag.to_string(), language.to_string()));
+        }
+    }
+    // NOTE: SPARQL 1.2 adds a base direction after the language tag.
+    if let Some(tag) = environment
+        .search_term
+        .as_deref()
+        .and_then(|search_term| search_term.split("--").next())
+        .filter(|tag| !tag.is_empty())
+    {
+        for direction in ["ltr", "rtl"] {
+            language_tags.push((
+                format!("{tag}--{direction}"),
+                format!("Base direction {direction}"),
+            ));
+        }
+    }
+    Ok(CompletionList {
+        is_incomplete: false,
+        item_defaults: None,
+        items: language_tags
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (tag, detail))| {
+                CompletionItemBuilder::new()
+                    .label(&tag)
+                    .kind(CompletionItemKind::Constant)
+                    .detail(&detail)
+                    .sort_text(&format!("{idx:0>5}"))
+                    .text_edit(TextEdit::new(environment.replace_range.clone(), &tag))
+                    .insert_text_format(InsertTextFormat::PlainText)
+                    .build()
+            })
+            .collect(),
+    })
+}
@@ -1,14 +1,18 @@
 pub(super) mod blank_node_object;
 pub(super) mod blank_node_property;
 pub(super) mod built_in_call;
+pub(super) mod datatype;
 pub(super) mod graph;
 pub(super) mod group_condition;
 pub(super) mod inline_data;
+pub(super) mod language_tag;
 pub(super) mod object;
 pub(super) mod order_condition;
 pub(super) mod predicate;
+pub(super) mod prefix_declaration;
 pub(super) mod select_binding;
 pub(super) mod service_url;
+pub(super) mod snippet;
 pub(super) mod solution_modifier;
 pub(super) mod start;
 pub(super) mod subject;
@@ -1,7 +1,15 @@
-use super::super::{CompletionEnvironment, error::CompletionError, utils::reduce_path};
+use super::super::{
+    CompletionEnvironment,
+    error::CompletionError,
+    utils::{compact_with_prefix, get_prefix_declarations, matches_search_term, reduce_path},
+};
 use crate::server::{
     Server,
-    lsp::CompletionList,
+    extension_catalog::{ExtensionKind, extensions},
+    lsp::{
+        CompletionItem, CompletionItemBuilder, CompletionItemKind, CompletionList,
+        InsertTextFormat, textdocument::TextEdit,
+    },
     message_handler::completion::{
         environment::CompletionLocation,
         utils::{CompletionTemplate, dispatch_completion_query},
@@ -20,6 +28,71 @@ use wasm_bindgen_futures::spawn_local;
 pub async fn completions(
     server_rc: Rc<Mutex<Server>>,
     environment: &CompletionEnvironment,
+) -> Result<CompletionList, CompletionError> {
+    let magic_predicates = magic_predicates(environment).await;
+    match backend_completions(server_rc, environment).await {
+        Ok(mut completion_list) => {
+            completion_list.items.extend(magic_predicates);
+            Ok(completion_list)
+        }
+        Err(_err) if !magic_predicates.is_empty() => Ok(CompletionList {
+            is_incomplete: true,
+            item_defaults: None,
+            items: magic_predicates,
+        }),
+        Err(err) => Err(err),
+    }
+}
+
+/// The magic predicates of the engine of the backend that match the search term.
+async fn magic_predicates(environment: &CompletionEnvironment) -> Vec<CompletionItem> {
+    let Some(engine) = environment
+        .backend
+        .as_ref()
+        .and_then(|backend| backend.engine.as_ref())
+    else {
+        return vec![];
+    };
+    let declarations = get_prefix_declarations(&environment.full_tree).await;
+    let search_term = environment.search_term.as_deref();
+    extensions(engine)
+        .filter(|extension| {
+            extension.kind == ExtensionKind::MagicPredicate
+                && (matches_search_term(&extension.curie(), search_term)
+                    || matches_search_term(extension.name, search_term))
+        })
+        .enumerate()
+        .map(|(idx, extension)| {
+            let (predicate, import_edit) = compact_with_prefix(
+                &declarations,
+                extension.namespace.prefix,
+                extension.namespace.uri,
+                extension.name,
+            );
+            let item = CompletionItemBuilder::new()
+                .label(&predicate)
+                .label_details(&format!("{engine:?} extension"))
+                .kind(CompletionItemKind::Function)
+                .documentation(extension.documentation)
+                // NOTE: after the results of the backend, those start at 100.
+                .sort_text(&format!("{:0>5}", 90000 + idx))
+                .text_edit(TextEdit::new(
+                    environment.replace_range.clone(),
+                    &format!("{predicate} "),
+                ))
+                .insert_text_format(InsertTextFormat::PlainText);
+            match import_edit {
+                Some(import_edit) => item.additional_text_edits(vec![import_edit]),
+                None => item,
+            }
+            .build()
+        })
+        .collect()
+}
+
+async fn backend_completions(
+    server_rc: Rc<Mutex<Server>>,
+    environment: &CompletionEnvironment,
 ) -> Result<CompletionList, CompletionError> {
     // NOTE: Compute template Context
     let mut template_context = environment.template_context().await;
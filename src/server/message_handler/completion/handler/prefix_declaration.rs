@@ -0,0 +1,46 @@
+use super::super::{CompletionEnvironment, error::CompletionError, utils::get_prefix_declarations};
+use crate::server::{
+    Server,
+    lsp::{CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat},
+};
+use futures::lock::Mutex;
+use std::rc::Rc;
+
+pub async fn completions(
+    server_rc: Rc<Mutex<Server>>,
+    environment: &CompletionEnvironment,
+) -> Result<CompletionList, CompletionError> {
+    let server = server_rc.lock().await;
+    let converter = environment
+        .backend
+        .as_ref()
+        .and_then(|backend| server.state.get_converter(&backend.name))
+        .unwrap_or_else(|| server.state.get_default_converter());
+    let declared: Vec<String> = get_prefix_declarations(&environment.full_tree)
+        .await
+        .into_iter()
+        .map(|(prefix, _)| prefix)
+        .collect();
+    let mut prefixes: Vec<(String, String)> = converter
+        .write_prefix_map()
+        .into_iter()
+        .filter(|(prefix, _)| !declared.contains(prefix))
+        .collect();
+    prefixes.sort();
+    Ok(CompletionList {
+        is_incomplete: false,
+        item_defaults: None,
+        items: prefixes
+            .into_iter()
+            .map(|(prefix, uri_prefix)| {
+                CompletionItemBuilder::new()
+                    .label(&format!("{prefix}:"))
+                    .kind(CompletionItemKind::Module)
+                    .detail(&uri_prefix)
+                    .insert_text(&format!("{prefix}: <{uri_prefix}>"))
+                    .insert_text_format(InsertTextFormat::PlainText)
+                    .build()
+            })
+            .collect(),
+    })
+}
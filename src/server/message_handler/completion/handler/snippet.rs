@@ -0,0 +1,140 @@
+use crate::server::{
+    Server,
+    configuration::{Snippet, SnippetLocation},
+    lsp::{
+        CompletionItem, CompletionItemBuilder, CompletionItemKind, CompletionList, InsertTextFormat,
+    },
+};
+
+use super::super::environment::{CompletionEnvironment, CompletionLocation};
+
+/// The user-defined snippets that apply to the location and backend of `environment`.
+pub fn completions(server: &Server, environment: &CompletionEnvironment) -> CompletionList {
+    let Some(location) = snippet_location(&environment.location) else {
+        return CompletionList::default();
+    };
+    let backend = environment.backend.as_ref().map(|backend| &backend.name);
+    let indent = server.settings.format.indent_unit();
+    CompletionList {
+        is_incomplete: false,
+        item_defaults: None,
+        items: server
+            .settings
+            .completion
+            .snippets
+            .iter()
+            .filter(|snippet| is_offered_at(snippet, location))
+            .filter(|snippet| {
+                snippet.backends.is_empty()
+                    || backend.is_some_and(|backend| snippet.backends.contains(backend))
+            })
+            .map(|snippet| to_completion_item(snippet, &indent))
+            .collect(),
+    }
+}
+
+/// Whether `snippet` is offered at `location`.
+///
+/// NOTE: a snippet without locations is not offered where a single token is
+/// expected, a datatype IRI, a language tag or a SERVICE URL. It has to list
+/// these locations explicitly.
+fn is_offered_at(snippet: &Snippet, location: SnippetLocation) -> bool {
+    if snippet.locations.is_empty() {
+        return !matches!(
+            location,
+            SnippetLocation::Datatype | SnippetLocation::LanguageTag | SnippetLocation::ServiceUrl
+        );
+    }
+    snippet.locations.contains(&location)
+}
+
+fn to_completion_item(snippet: &Snippet, indent: &str) -> CompletionItem {
+    let builder = CompletionItemBuilder::new()
+        .label(&snippet.name)
+        .detail("Snippet")
+        .filter_text(&snippet.name)
+        .kind(CompletionItemKind::Snippet)
+        .insert_text(&indent_body(&snippet.body, indent))
+        .insert_text_format(InsertTextFormat::Snippet);
+    match &snippet.documentation {
+        Some(documentation) => builder.documentation(documentation),
+        None => builder,
+    }
+    .build()
+}
+
+/// Replaces the leading tabs of every line of `body` with `indent`.
+fn indent_body(body: &str, indent: &str) -> String {
+    body.split('\n')
+        .map(|line| {
+            let content = line.trim_start_matches('\t');
+            format!("{}{}", indent.repeat(line.len() - content.len()), content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn snippet_location(location: &CompletionLocation) -> Option<SnippetLocation> {
+    Some(match location {
+        CompletionLocation::Unknown => return None,
+        CompletionLocation::Start => SnippetLocation::Start,
+        CompletionLocation::Subject => SnippetLocation::Subject,
+        CompletionLocation::Predicate(_) => SnippetLocation::Predicate,
+        CompletionLocation::Object(_) => SnippetLocation::Object,
+        CompletionLocation::SolutionModifier => SnippetLocation::SolutionModifier,
+        CompletionLocation::SelectBinding(_) => SnippetLocation::SelectBinding,
+        CompletionLocation::Graph => SnippetLocation::Graph,
+        CompletionLocation::BlankNodeProperty(_) => SnippetLocation::BlankNodeProperty,
+        CompletionLocation::BlankNodeObject(_) => SnippetLocation::BlankNodeObject,
+        CompletionLocation::ServiceUrl => SnippetLocation::ServiceUrl,
+        CompletionLocation::OrderCondition => SnippetLocation::OrderCondition,
+        CompletionLocation::GroupCondition(_) => SnippetLocation::GroupCondition,
+        CompletionLocation::InlineData(_) => SnippetLocation::InlineData,
+        CompletionLocation::BuiltInCall => SnippetLocation::BuiltInCall,
+        CompletionLocation::PrefixDeclaration => SnippetLocation::PrefixDeclaration,
+        CompletionLocation::Datatype => SnippetLocation::Datatype,
+        CompletionLocation::LanguageTag(_) => SnippetLocation::LanguageTag,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::configuration::{Snippet, SnippetLocation};
+
+    use super::{indent_body, is_offered_at};
+
+    fn snippet(locations: Vec<SnippetLocation>) -> Snippet {
+        Snippet {
+            name: "todo".to_string(),
+            body: "# TODO: $0".to_string(),
+            documentation: None,
+            locations,
+            backends: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn snippet_without_locations_skips_single_token_locations() {
+        let snippet = snippet(Vec::new());
+        assert!(is_offered_at(&snippet, SnippetLocation::Subject));
+        assert!(is_offered_at(&snippet, SnippetLocation::Start));
+        assert!(!is_offered_at(&snippet, SnippetLocation::Datatype));
+        assert!(!is_offered_at(&snippet, SnippetLocation::LanguageTag));
+        assert!(!is_offered_at(&snippet, SnippetLocation::ServiceUrl));
+    }
+
+    #[test]
+    fn snippet_with_locations_is_offered_only_there() {
+        let snippet = snippet(vec![SnippetLocation::LanguageTag]);
+        assert!(is_offered_at(&snippet, SnippetLocation::LanguageTag));
+        assert!(!is_offered_at(&snippet, SnippetLocation::Subject));
+    }
+
+    #[test]
+    fn leading_tabs_become_the_configured_indent() {
+        assert_eq!(
+            indent_body("FILTER(\n\t?x > 1 &&\n\t\t?y\t< 2\n)", "  "),
+            "FILTER(\n  ?x > 1 &&\n    ?y\t< 2\n)"
+        );
+    }
+}
@@ -7,6 +7,7 @@ use crate::server::{
         Command, CompletionItem, CompletionItemBuilder, CompletionItemKind, CompletionList,
         InsertTextFormat, ItemDefaults, textdocument::TextEdit,
     },
+    message_handler::common::{object_variable_name, to_sparql_variable},
 };
 use futures::lock::Mutex;
 use ll_sparql_parser::ast::{AstNode, PrefixedName, Var, VarOrTerm};
@@ -74,7 +75,7 @@ pub async fn completions(
         .and_then(PrefixedName::cast)
     {
         // NOTE: what should the variable be called based on the predicate.
-        let mut object_name = server
+        let object_name = server
             .state
             .label_memory
             .get(&prefixed_name.text())
@@ -86,10 +87,7 @@ pub async fn completions(
         // "has_author" -> "author"
         // These replacements are configurable.
         // snace_case conversion is build in.
-        if let Some(replacements) = server.settings.replacements.as_ref() {
-            object_name = replacements.apply_object_variable(&object_name);
-        }
-        let variable = to_sparql_variable(&object_name);
+        let variable = object_variable_name(server.settings.replacements.as_ref(), &object_name);
         suggestions.insert(
             0,
             CompletionItemBuilder::new()
@@ -175,130 +173,3 @@ pub async fn completions(
         items: suggestions,
     })
 }
-
-/// Transforms an arbitrary string into a valid SPARQL variable name.
-///
-/// SPARQL variable names must:
-/// - Start with a letter (A-Z, a-z) or underscore
-/// - Contain only letters, digits, underscores
-/// - Be prefixed with '?' or '$'
-///
-/// This function:
-/// - Removes the '?' or '$' prefix if present
-/// - Replaces invalid characters with underscores
-/// - Ensures the name starts with a valid character
-/// - Returns the variable name WITHOUT '?' or '$' prefix
-fn to_sparql_variable(s: &str) -> String {
-    if s.is_empty() {
-        return "var".to_string();
-    }
-
-    // NOTE: first pass: convert into snace_case
-    let s = &s
-        .trim()
-        .to_lowercase()
-        .replace([' ', '-', ',', '.', '(', ')', '$', '?'], " ")
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join("_");
-
-    let mut result = String::new();
-    let mut chars = s.chars();
-
-    // Handle first character - must be letter or underscore
-    if let Some(first) = chars.next() {
-        if first.is_ascii_alphabetic() || first == '_' {
-            result.push(first);
-        } else if first.is_ascii_digit() {
-            // If starts with digit, prefix with underscore
-            result.push('_');
-            result.push(first);
-        } else {
-            // Replace invalid first char with underscore
-            result.push('_');
-        }
-    }
-
-    // Process remaining characters
-    for c in chars {
-        if c.is_ascii_alphanumeric() || c == '_' {
-            result.push(c);
-        } else {
-            result.push('_');
-        }
-    }
-
-    // Ensure we have at least some content
-    if result.is_empty() {
-        result.push_str("var");
-    }
-    result
-}
-
-#[cfg(test)]
-mod tests {
-    use super::to_sparql_variable;
-    use crate::server::configuration::Replacements;
-
-    /// The full object variable name pipeline: configured replacements first,
-    /// then the built in snake_case conversion.
-    fn pipeline(name: &str) -> String {
-        to_sparql_variable(&Replacements::default().apply_object_variable(name))
-    }
-
-    #[test]
-    fn test_to_sparql_variable_converts_to_snake_case() {
-        assert_eq!(to_sparql_variable("BirthDate"), "birthdate");
-        assert_eq!(to_sparql_variable("birth date"), "birth_date");
-        assert_eq!(to_sparql_variable("part-of"), "part_of");
-        assert_eq!(to_sparql_variable("place.of.birth"), "place_of_birth");
-        assert_eq!(to_sparql_variable("label (english)"), "label_english");
-    }
-
-    #[test]
-    fn test_to_sparql_variable_strips_variable_prefix() {
-        assert_eq!(to_sparql_variable("?author"), "author");
-        assert_eq!(to_sparql_variable("$author"), "author");
-    }
-
-    #[test]
-    fn test_to_sparql_variable_produces_valid_names() {
-        assert_eq!(to_sparql_variable(""), "var");
-        assert_eq!(to_sparql_variable("   "), "var");
-        assert_eq!(to_sparql_variable("2020"), "_2020");
-        assert_eq!(to_sparql_variable("_private"), "_private");
-        assert_eq!(to_sparql_variable("a/b"), "a_b");
-    }
-
-    #[test]
-    fn test_to_sparql_variable_trims_and_collapses_whitespace() {
-        assert_eq!(to_sparql_variable("  date of  birth "), "date_of_birth");
-    }
-
-    #[test]
-    fn test_pipeline_with_default_replacements() {
-        assert_eq!(pipeline("hasAuthor"), "author");
-        assert_eq!(pipeline("authoredBy"), "author");
-        assert_eq!(pipeline("has author"), "author");
-        assert_eq!(pipeline("P31"), "p31");
-    }
-
-    #[test]
-    fn test_pipeline_keeps_word_boundaries_as_snake_case() {
-        // NOTE: multi word names keep their boundaries, whether they are
-        // separated by whitespace, punctuation or camelCase.
-        assert_eq!(pipeline("has birth date"), "birth_date");
-        assert_eq!(pipeline("place of birth"), "place_of_birth");
-        assert_eq!(pipeline("hasBirthDate"), "birth_date");
-        assert_eq!(pipeline("birthDate"), "birth_date");
-        assert_eq!(pipeline("place-of-birth"), "place_of_birth");
-        assert_eq!(pipeline("hasPlaceOfBirth"), "place_of_birth");
-    }
-
-    #[test]
-    fn test_pipeline_produces_valid_names_for_degenerate_input() {
-        assert_eq!(pipeline("///"), "var");
-        assert_eq!(pipeline(""), "var");
-        assert_eq!(pipeline("P31/P279*"), "p31_p279");
-    }
-}
@@ -0,0 +1,218 @@
+//! Completion from the in-memory index of a backend with local files.
+//!
+//! Instead of rendering and sending the query of a [`CompletionTemplate`], the
+//! candidates are looked up in the [`LocalIndex`] and returned as the bindings a
+//! completion query would have, so they are rendered like online completions.
+
+use std::collections::HashMap;
+
+use curies::Converter;
+use ll_sparql_parser::{ast::AstNode, syntax_kind::SyntaxKind};
+
+use super::{
+    environment::{CompletionEnvironment, CompletionLocation},
+    matcher::match_quality,
+    utils::CompletionTemplate,
+};
+use crate::{
+    server::local_index::LocalIndex,
+    sparql::results::{Binding, RDFTerm},
+};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// The bindings of `template`, at most `limit`, the most frequent first.
+pub(super) fn bindings(
+    index: &LocalIndex,
+    template: &CompletionTemplate,
+    environment: &CompletionEnvironment,
+    declarations: &[(String, String)],
+    converter: Option<&Converter>,
+    limit: usize,
+) -> Vec<Binding> {
+    let expand = |term: String| expand(&term, declarations, converter);
+    let candidates: Vec<(RDFTerm, usize)> = match template {
+        CompletionTemplate::SubjectCompletion => index
+            .subjects()
+            .map(|iri| (uri(iri), index.count(iri)))
+            .collect(),
+        CompletionTemplate::PredicateCompletionContextSensitive
+        | CompletionTemplate::PredicateCompletionContextInsensitive => {
+            let subject = match (&environment.location, template) {
+                (
+                    CompletionLocation::Predicate(triple),
+                    CompletionTemplate::PredicateCompletionContextSensitive,
+                ) if starts_at_subject(environment) => {
+                    triple.subject().and_then(|subject| expand(subject.text()))
+                }
+                _ => None,
+            };
+            index
+                .predicates(subject.as_deref())
+                .into_iter()
+                .map(|iri| (uri(iri), index.count(iri)))
+                .collect()
+        }
+        CompletionTemplate::ObjectCompletionContextSensitive
+        | CompletionTemplate::ObjectCompletionContextInsensitive => {
+            let predicate = match (&environment.location, template) {
+                (
+                    CompletionLocation::Object(triple),
+                    CompletionTemplate::ObjectCompletionContextSensitive,
+                ) => triple
+                    .properties_list_path()
+                    .and_then(|properties| properties.properties().pop())
+                    .and_then(|property| expand(property.verb.text())),
+                _ => None,
+            };
+            index.objects(predicate.as_deref())
+        }
+        CompletionTemplate::ValuesCompletionContextSensitive
+        | CompletionTemplate::ValuesCompletionContextInsensitive => index.objects(None),
+        CompletionTemplate::LanguageTagCompletion => match &environment.location {
+            CompletionLocation::LanguageTag(Some(verb)) => expand(verb.text().to_string())
+                .map(|predicate| {
+                    index
+                        .language_tags(&predicate)
+                        .into_iter()
+                        .map(|(tag, count)| {
+                            (
+                                RDFTerm::Literal {
+                                    value: tag,
+                                    lang: None,
+                                    datatype: None,
+                                },
+                                count,
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => vec![],
+        },
+        CompletionTemplate::GraphCompletion
+        | CompletionTemplate::Hover
+        | CompletionTemplate::UsageCheck
+        | CompletionTemplate::EntityDetails => vec![],
+    };
+
+    let search_term = environment
+        .search_term
+        .as_deref()
+        .filter(|search_term| !search_term.is_empty());
+    let search_term_uncompressed = search_term.and_then(|search_term| {
+        search_term
+            .contains(':')
+            .then(|| expand(search_term.to_string()))
+            .flatten()
+    });
+    let mut candidates: Vec<_> = candidates
+        .into_iter()
+        .filter(|(term, _)| match search_term {
+            Some(search_term) => matches(
+                index,
+                term,
+                search_term,
+                search_term_uncompressed.as_deref(),
+            ),
+            None => true,
+        })
+        .collect();
+    candidates.sort_by(|(a, a_count), (b, b_count)| {
+        b_count.cmp(a_count).then_with(|| a.value().cmp(b.value()))
+    });
+    candidates
+        .into_iter()
+        .take(limit)
+        .map(|(term, count)| {
+            let mut binding =
+                HashMap::from([("qls_count".to_string(), literal(&count.to_string()))]);
+            if let RDFTerm::Uri { value, .. } = &term {
+                if let Some(label) = index.label(value) {
+                    binding.insert("qls_label".to_string(), literal(label));
+                }
+                if let Some(alias) = index.alias(value) {
+                    binding.insert("qls_alias".to_string(), literal(alias));
+                }
+            }
+            binding.insert("qls_entity".to_string(), term);
+            binding
+        })
+        .collect()
+}
+
+/// Is the predicate at the cursor used with the subject of the triple?
+/// After a `/` or `^` of a property path it is not.
+fn starts_at_subject(environment: &CompletionEnvironment) -> bool {
+    let Some(anchor) = environment.anchor_token.as_ref() else {
+        return true;
+    };
+    anchor
+        .parent_ancestors()
+        .find(|ancestor| ancestor.kind() == SyntaxKind::VerbPath)
+        .is_none_or(|verb| {
+            !verb.descendants_with_tokens().any(|element| {
+                matches!(element.kind(), SyntaxKind::Slash | SyntaxKind::Zirkumflex)
+                    && element.text_range().end() <= anchor.text_range().end()
+            })
+        })
+}
+
+/// Does the search term match the label, the local name or the IRI of `term`?
+fn matches(
+    index: &LocalIndex,
+    term: &RDFTerm,
+    search_term: &str,
+    search_term_uncompressed: Option<&str>,
+) -> bool {
+    let value = term.value();
+    if search_term_uncompressed.is_some_and(|uncompressed| value.starts_with(uncompressed)) {
+        return true;
+    }
+    // NOTE: of a prefixed name, e.g. `ex:Ber`, the local part is matched.
+    let search_term = search_term
+        .rsplit_once(':')
+        .map_or(search_term, |(_, local)| local);
+    let local_name = value.rsplit(['/', '#']).next().unwrap_or(value);
+    [Some(value), Some(local_name), index.label(value)]
+        .into_iter()
+        .flatten()
+        .any(|candidate| search_term.is_empty() || match_quality(candidate, search_term).is_some())
+}
+
+/// The IRI of a term of the query: `a`, `<iri>` or a prefixed name declared in
+/// the query or known to the backend.
+fn expand(
+    term: &str,
+    declarations: &[(String, String)],
+    converter: Option<&Converter>,
+) -> Option<String> {
+    let term = term.trim();
+    if term == "a" {
+        return Some(RDF_TYPE.to_string());
+    }
+    if let Some(iri) = term.strip_prefix('<').and_then(|iri| iri.strip_suffix('>')) {
+        return Some(iri.to_string());
+    }
+    let (prefix, local) = term.split_once(':')?;
+    declarations
+        .iter()
+        .find(|(declared, _)| declared == prefix)
+        .map(|(_, namespace)| format!("{namespace}{local}"))
+        .or_else(|| converter.and_then(|converter| converter.expand(term).ok()))
+}
+
+fn uri(iri: &str) -> RDFTerm {
+    RDFTerm::Uri {
+        value: iri.to_string(),
+        curie: None,
+    }
+}
+
+fn literal(value: &str) -> RDFTerm {
+    RDFTerm::Literal {
+        value: value.to_string(),
+        lang: None,
+        datatype: None,
+    }
+}
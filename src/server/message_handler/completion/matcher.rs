@@ -0,0 +1,175 @@
+//! Fuzzy matching of completion candidates against the search term.
+//!
+//! A candidate matches if, ignoring case, the search term is
+//!
+//! - equal to it,
+//! - a prefix of it,
+//! - split into pieces that each start a different word of it, in any order,
+//!   e.g. `birthdat` matches `date of birth`, or
+//! - a subsequence of it, e.g. `dtbrth` matches `dateOfBirth`.
+//!
+//! Words are separated by non-alphanumeric characters, lower to upper case
+//! transitions and letter to digit transitions.
+
+use std::collections::HashSet;
+
+const EXACT: u32 = 100;
+const PREFIX: u32 = 90;
+const WORD_BOUNDARY: u32 = 75;
+const SUBSEQUENCE: u32 = 60;
+
+/// Words beyond this limit are not considered for word-boundary matches.
+const MAX_WORDS: usize = 16;
+
+/// The quality of the match of `search_term` in `candidate`,
+/// from 1 for a scattered subsequence up to 100 for an exact match.
+/// Returns `None` if `candidate` does not match.
+pub(super) fn match_quality(candidate: &str, search_term: &str) -> Option<u32> {
+    let candidate_lowercase = candidate.to_lowercase();
+    let search_term_lowercase = search_term.to_lowercase();
+    if candidate_lowercase == search_term_lowercase {
+        return Some(EXACT);
+    }
+    if candidate_lowercase.starts_with(&search_term_lowercase) {
+        return Some(PREFIX);
+    }
+    let term: Vec<char> = search_term_lowercase
+        .chars()
+        .filter(|char| char.is_alphanumeric())
+        .collect();
+    if term.is_empty() {
+        return None;
+    }
+    let words = split_words(candidate);
+    if matches_word_starts(&term, &words, 0, &mut HashSet::new()) {
+        return Some(WORD_BOUNDARY);
+    }
+    subsequence_quality(&term, &candidate_lowercase)
+}
+
+/// The lowercase words of `candidate`.
+fn split_words(candidate: &str) -> Vec<Vec<char>> {
+    let mut words: Vec<Vec<char>> = Vec::new();
+    let mut previous: Option<char> = None;
+    for char in candidate.chars() {
+        if !char.is_alphanumeric() {
+            previous = None;
+            continue;
+        }
+        let starts_word = previous.is_none_or(|previous| {
+            (previous.is_lowercase() && char.is_uppercase())
+                || (previous.is_numeric() != char.is_numeric())
+        });
+        if starts_word {
+            if words.len() == MAX_WORDS {
+                break;
+            }
+            words.push(Vec::new());
+        }
+        if let Some(word) = words.last_mut() {
+            word.extend(char.to_lowercase());
+        }
+        previous = Some(char);
+    }
+    words
+}
+
+/// Can `term` be split into pieces that are prefixes of distinct `words`?
+/// `used` is a bitmask of the words taken by earlier pieces, `failed` memoizes
+/// the states that do not lead to a match.
+fn matches_word_starts(
+    term: &[char],
+    words: &[Vec<char>],
+    used: u32,
+    failed: &mut HashSet<(usize, u32)>,
+) -> bool {
+    if term.is_empty() {
+        return true;
+    }
+    if failed.contains(&(term.len(), used)) {
+        return false;
+    }
+    for (idx, word) in words.iter().enumerate() {
+        if used & (1 << idx) != 0 {
+            continue;
+        }
+        let common = word
+            .iter()
+            .zip(term)
+            .take_while(|(word_char, term_char)| word_char == term_char)
+            .count();
+        // NOTE: longest pieces first, they usually lead to a match directly.
+        for len in (1..=common).rev() {
+            if matches_word_starts(&term[len..], words, used | (1 << idx), failed) {
+                return true;
+            }
+        }
+    }
+    failed.insert((term.len(), used));
+    false
+}
+
+/// Finds `term` as subsequence of `candidate`, rated by how close together
+/// the matched characters are.
+fn subsequence_quality(term: &[char], candidate: &str) -> Option<u32> {
+    let mut term_chars = term.iter().peekable();
+    let mut first = None;
+    let mut last = 0;
+    for (idx, char) in candidate.chars().enumerate() {
+        if term_chars
+            .peek()
+            .is_some_and(|term_char| **term_char == char)
+        {
+            term_chars.next();
+            first.get_or_insert(idx);
+            last = idx;
+        }
+    }
+    if term_chars.peek().is_some() {
+        return None;
+    }
+    let span = last - first? + 1;
+    Some((SUBSEQUENCE * term.len() as u32 / span as u32).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_quality, split_words};
+
+    #[test]
+    fn exact_beats_prefix_beats_word_boundary_beats_subsequence() {
+        let exact = match_quality("Birth", "birth").unwrap();
+        let prefix = match_quality("birthday", "birth").unwrap();
+        let word_boundary = match_quality("date of birth", "birth").unwrap();
+        let subsequence = match_quality("bathroom", "bath rm").unwrap();
+        assert!(exact > prefix);
+        assert!(prefix > word_boundary);
+        assert!(word_boundary > subsequence);
+    }
+
+    #[test]
+    fn pieces_match_word_starts_in_any_order() {
+        assert!(match_quality("date of birth", "birthdat").is_some());
+        assert!(match_quality("dateOfBirth", "birthdat").is_some());
+        assert!(match_quality("P569", "p56").is_some());
+        assert!(match_quality("date of birth", "birthx").is_none());
+    }
+
+    #[test]
+    fn subsequence_quality_drops_with_gaps() {
+        let close = match_quality("repopulate", "pop").unwrap();
+        let scattered = match_quality("place of publication", "ppn").unwrap();
+        assert!(close > scattered);
+        assert!(match_quality("country", "cty").is_some());
+        assert!(match_quality("country", "ytc").is_none());
+    }
+
+    #[test]
+    fn split_camel_case_and_digits() {
+        let words: Vec<String> = split_words("hasPart2/of")
+            .into_iter()
+            .map(|word| word.into_iter().collect())
+            .collect();
+        assert_eq!(words, vec!["has", "part", "2", "of"]);
+    }
+}
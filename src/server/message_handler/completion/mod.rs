@@ -1,11 +1,15 @@
 mod environment;
 mod error;
 mod handler;
+mod local;
+mod matcher;
+mod resolve;
 mod transformer;
 mod utils;
 use environment::{CompletionEnvironment, CompletionLocation};
 use error::{CompletionError, to_lsp_error};
 use futures::lock::Mutex;
+pub(super) use resolve::handle_completion_resolve_request;
 use std::rc::Rc;
 use tracing::instrument;
 
@@ -30,7 +34,7 @@ pub(super) async fn handle_completion_request(
         .map_err(to_lsp_error)?;
     // tracing::debug!("Completion env:\n{env}");
 
-    let mut completion_list = if (env.trigger_kind == CompletionTriggerKind::TriggerCharacter
+    let typing_variable = (env.trigger_kind == CompletionTriggerKind::TriggerCharacter
         && env.trigger_character.as_ref().is_some_and(|tc| tc == "?")
         || env
             .search_term
@@ -39,7 +43,8 @@ pub(super) async fn handle_completion_request(
         && !matches!(
             env.location,
             CompletionLocation::SelectBinding(_) | CompletionLocation::GroupCondition(_)
-        ) {
+        );
+    let mut completion_list = if typing_variable {
         Some(
             handler::variable::completions(server_rc.clone(), &env)
                 .await
@@ -76,7 +81,9 @@ pub(super) async fn handle_completion_request(
                 CompletionLocation::SolutionModifier => {
                     handler::solution_modifier::completions(&env)
                 }
-                CompletionLocation::Graph => handler::graph::completions(&env),
+                CompletionLocation::Graph => {
+                    handler::graph::completions(server_rc.clone(), &env).await
+                }
                 CompletionLocation::BlankNodeProperty(_) => {
                     handler::blank_node_property::completions(server_rc.clone(), &env).await
                 }
@@ -95,7 +102,16 @@ pub(super) async fn handle_completion_request(
                 CompletionLocation::InlineData(..) => {
                     handler::inline_data::completions(server_rc.clone(), &env).await
                 }
-                CompletionLocation::BuiltInCall => handler::built_in_call::completions().await,
+                CompletionLocation::BuiltInCall => handler::built_in_call::completions(&env).await,
+                CompletionLocation::PrefixDeclaration => {
+                    handler::prefix_declaration::completions(server_rc.clone(), &env).await
+                }
+                CompletionLocation::Datatype => {
+                    handler::datatype::completions(server_rc.clone(), &env).await
+                }
+                CompletionLocation::LanguageTag(_) => {
+                    handler::language_tag::completions(server_rc.clone(), &env).await
+                }
                 ref location => Err(CompletionError::Localization(format!(
                     "Unknown location \"{:?}\"",
                     location
@@ -114,6 +130,12 @@ pub(super) async fn handle_completion_request(
     if let Some(transformer) = SemicolonTransformer::try_from_env(&server, &env) {
         transformer.transform(&mut completion_list);
     }
+    // NOTE: a snippet never continues a variable name.
+    if !typing_variable {
+        completion_list
+            .items
+            .extend(handler::snippet::completions(&server, &env).items);
+    }
 
     server.send_message(CompletionResponse::new(request.get_id(), completion_list))
 }
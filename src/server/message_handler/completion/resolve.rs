@@ -0,0 +1,241 @@
+//! Lazy documentation for completion items.
+//!
+//! The completion query only fetches what the list needs. Items of a backend
+//! with an `entityDetails` query carry their entity and backend in `data`;
+//! once the editor highlights such an item, the query runs for this one
+//! entity and its description, types, usage count and image become the
+//! Markdown documentation of the item.
+
+use std::rc::Rc;
+
+use futures::lock::Mutex;
+use tera::Context;
+use tracing::instrument;
+
+use crate::{
+    server::{
+        Server,
+        completion_cache::CompletionQuery,
+        configuration::CompletionTemplate,
+        lsp::{
+            CompletionItem, CompletionResolveRequest, CompletionResolveResponse, Documentation,
+            base_types::LSPAny, errors::LSPError,
+        },
+        state::get_timestamp_ms,
+    },
+    sparql::results::Binding,
+};
+
+use super::{error::CompletionError, utils::fetch_bindings};
+
+#[instrument(skip(server_rc, request), fields(id = %request.get_id()))]
+pub(in super::super) async fn handle_completion_resolve_request(
+    server_rc: Rc<Mutex<Server>>,
+    request: CompletionResolveRequest,
+) -> Result<(), LSPError> {
+    let id = request.get_id().clone();
+    let mut item = request.params;
+    if let Some((entity, backend_name)) = resolve_target(&item) {
+        match entity_details(server_rc.clone(), entity, backend_name).await {
+            Ok(Some(markdown)) => item.documentation = Some(Documentation::markdown(markdown)),
+            Ok(None) => {}
+            // NOTE: the item keeps its documentation, a failed lookup is no reason to drop it.
+            Err(err) => tracing::warn!("Could not resolve details of {entity}: {err:?}"),
+        }
+    }
+    server_rc
+        .lock()
+        .await
+        .send_message(CompletionResolveResponse::new(&id, item))
+}
+
+/// The entity IRI and backend name stored in the `data` of `item`.
+fn resolve_target(item: &CompletionItem) -> Option<(&str, &str)> {
+    let Some(LSPAny::LSPObject(data)) = &item.data else {
+        return None;
+    };
+    match (data.get("entity")?, data.get("backend")?) {
+        (LSPAny::String(entity), LSPAny::String(backend)) => Some((entity, backend)),
+        _ => None,
+    }
+}
+
+/// Runs the `entityDetails` query of `backend_name` for `entity`.
+/// Returns `None` if the backend has no endpoint or no such query, or the query
+/// found nothing.
+async fn entity_details(
+    server_rc: Rc<Mutex<Server>>,
+    entity: &str,
+    backend_name: &str,
+) -> Result<Option<String>, CompletionError> {
+    let (url, completion_query, cached, timeout_ms, method) = {
+        let mut server = server_rc.lock().await;
+        let Some(backend) = server.state.get_backend(backend_name) else {
+            return Ok(None);
+        };
+        if !backend.has_endpoint()
+            || !backend
+                .queries
+                .contains_key(&CompletionTemplate::EntityDetails)
+        {
+            return Ok(None);
+        }
+        let url = backend.url.clone();
+        let template = format!("{}-{}", backend_name, CompletionTemplate::EntityDetails);
+        let mut context = Context::new();
+        context.insert("entity", &format!("<{entity}>"));
+        context.insert("prefixes", &Vec::<(String, String)>::new());
+        let query = server
+            .tools
+            .tera
+            .render(&template, &context)
+            .map_err(|err| CompletionError::Template(template.clone(), err))?;
+        let completion_query = CompletionQuery {
+            backend: backend_name.to_string(),
+            template,
+            query,
+            family: None,
+            search_term: None,
+            search_term_uncompressed: None,
+        };
+        let settings = &server.settings.completion;
+        let (ttl_ms, capacity, timeout_ms) = (
+            settings.cache_ttl_ms,
+            settings.cache_size,
+            settings.timeout_ms,
+        );
+        let cached = server.state.completion_cache.get(
+            &completion_query,
+            get_timestamp_ms(),
+            ttl_ms,
+            capacity,
+        );
+        let method = server.state.get_backend_request_method(backend_name);
+        (url, completion_query, cached, timeout_ms, method)
+    };
+    let bindings = match cached {
+        Some(bindings) => bindings,
+        None => {
+            fetch_bindings(
+                server_rc.clone(),
+                url,
+                &completion_query,
+                timeout_ms,
+                method,
+            )
+            .await?
+        }
+    };
+    let server = server_rc.lock().await;
+    Ok(to_markdown(entity, &bindings, |iri| {
+        server
+            .shorten_uri(iri, Some(backend_name))
+            .map_or_else(|| format!("<{iri}>"), |(_, _, curie)| curie)
+    }))
+}
+
+/// Renders the bindings of an `entityDetails` query.
+///
+/// Every row may bind `?qls_label`, `?qls_description`, `?qls_type`,
+/// `?qls_count` and `?qls_image`; the types of all rows are collected,
+/// the other variables are taken from the first row that binds them.
+fn to_markdown(
+    entity: &str,
+    bindings: &[Binding],
+    compact: impl Fn(&str) -> String,
+) -> Option<String> {
+    if bindings.is_empty() {
+        return None;
+    }
+    let first = |variable: &str| {
+        bindings
+            .iter()
+            .find_map(|binding| binding.get(variable))
+            .map(|rdf_term| rdf_term.value().to_string())
+    };
+    let mut types: Vec<String> = Vec::new();
+    for rdf_type in bindings
+        .iter()
+        .filter_map(|binding| binding.get("qls_type"))
+        .map(|rdf_term| compact(rdf_term.value()))
+    {
+        if !types.contains(&rdf_type) {
+            types.push(rdf_type);
+        }
+    }
+    let mut sections = vec![format!(
+        "### **{}**\n{}",
+        first("qls_label").unwrap_or_else(|| compact(entity)),
+        compact(entity)
+    )];
+    if let Some(description) = first("qls_description") {
+        sections.push(description);
+    }
+    if !types.is_empty() {
+        sections.push(format!("Type: {}", types.join(", ")));
+    }
+    if let Some(count) = first("qls_count") {
+        sections.push(format!("Used {count} times"));
+    }
+    if let Some(image) = first("qls_image") {
+        sections.push(format!("![{}]({image})", compact(entity)));
+    }
+    Some(sections.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sparql::results::{Binding, RDFTerm};
+
+    use super::to_markdown;
+
+    fn binding(variables: &[(&str, RDFTerm)]) -> Binding {
+        variables
+            .iter()
+            .map(|(variable, rdf_term)| (variable.to_string(), rdf_term.clone()))
+            .collect()
+    }
+
+    fn uri(value: &str) -> RDFTerm {
+        RDFTerm::Uri {
+            value: value.to_string(),
+            curie: None,
+        }
+    }
+
+    fn literal(value: &str) -> RDFTerm {
+        RDFTerm::Literal {
+            value: value.to_string(),
+            lang: None,
+            datatype: None,
+        }
+    }
+
+    #[test]
+    fn markdown_of_entity_details() {
+        let bindings = vec![
+            binding(&[
+                ("qls_label", literal("Douglas Adams")),
+                ("qls_description", literal("English author")),
+                ("qls_type", uri("http://ex.org/Human")),
+                ("qls_count", literal("42")),
+                ("qls_image", uri("http://img.org/adams.jpg")),
+            ]),
+            binding(&[("qls_type", uri("http://ex.org/Writer"))]),
+            binding(&[("qls_type", uri("http://ex.org/Human"))]),
+        ];
+        assert_eq!(
+            to_markdown("http://ex.org/Q42", &bindings, |iri| iri
+                .replace("http://ex.org/", "ex:")),
+            Some(
+                "### **Douglas Adams**\nex:Q42\n\nEnglish author\n\nType: ex:Human, ex:Writer\n\nUsed 42 times\n\n![ex:Q42](http://img.org/adams.jpg)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn no_markdown_without_bindings() {
+        assert_eq!(to_markdown("http://ex.org/Q42", &[], str::to_string), None);
+    }
+}
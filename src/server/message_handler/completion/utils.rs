@@ -1,28 +1,33 @@
 use futures::lock::Mutex;
 use ll_sparql_parser::{
     SyntaxNode,
-    ast::{AstNode, Path, Prologue, QueryUnit},
+    ast::{AstNode, Path, Prologue},
     syntax_kind::SyntaxKind,
 };
-use std::rc::Rc;
+use std::{cmp::Ordering, collections::HashMap, rc::Rc};
 use tera::Context;
 use text_size::TextSize;
 
 use crate::{
     server::{
         Server,
-        configuration::BackendConfiguration,
+        completion_cache::{CompletionQuery, SEARCH_TERM_PLACEHOLDER},
+        configuration::{BackendConfiguration, RequestMethod},
         lsp::{
             Command, CompletionItem, CompletionItemKind, CompletionItemLabelDetails,
-            CompletionList,
+            CompletionList, Documentation,
+            base_types::LSPAny,
             textdocument::{Range, TextEdit},
         },
         sparql_operations::execute_query,
+        state::get_timestamp_ms,
     },
-    sparql::results::{RDFTerm, SparqlResultsBody},
+    sparql::results::{Binding, RDFTerm, SparqlResultsBody},
 };
 
-use super::{environment::CompletionEnvironment, error::CompletionError};
+use super::{
+    environment::CompletionEnvironment, error::CompletionError, local, matcher::match_quality,
+};
 
 /// Returns true if the label matches the search term as a case-insensitive prefix.
 /// If no search term is provided (or it's empty), returns true to show all completions.
@@ -49,22 +54,39 @@ pub(super) async fn dispatch_completion_query(
 ) -> Result<CompletionList, CompletionError> {
     match environment.backend.as_ref() {
         Some(backend) => {
-            let query_unit = QueryUnit::cast(environment.truncated_tree.clone()).ok_or(
-                CompletionError::Resolve("Could not cast root to QueryUnit".to_string()),
-            )?;
+            // NOTE: the root is a `QueryUnit` or an `UpdateUnit`, both start with the prologue.
+            let declarations = get_prefix_declarations(&environment.truncated_tree).await;
+            let items = match local_completions(
+                server_rc.clone(),
+                environment,
+                &declarations,
+                backend,
+                &completion_template,
+            )
+            .await
+            {
+                Some(items) => items?,
+                None => {
+                    fetch_online_completions(
+                        server_rc.clone(),
+                        &declarations,
+                        backend,
+                        &format!("{}-{}", backend.name, completion_template),
+                        template_context,
+                    )
+                    .await?
+                }
+            };
             Ok(to_completion_items(
-                fetch_online_completions(
-                    server_rc.clone(),
-                    &query_unit,
-                    backend,
-                    &format!("{}-{}", backend.name, completion_template),
-                    template_context,
-                )
-                .await?,
+                items,
                 environment.replace_range.clone(),
                 trigger_on_accept.then_some("triggerNewCompletion"),
                 server_rc.lock().await.settings.completion.result_size_limit,
                 environment.search_term.as_deref(),
+                backend
+                    .queries
+                    .contains_key(&CompletionTemplate::EntityDetails)
+                    .then_some(backend.name.as_str()),
             ))
         }
         _ => {
@@ -78,40 +100,187 @@ pub(super) struct InternalCompletionItem {
     label: String,
     detail: Option<String>,
     value: String,
-    _filter_text: Option<String>,
+    /// The IRI of the completed entity, if it is one.
+    entity: Option<String>,
     score: Option<usize>,
     import_edit: Option<TextEdit>,
 }
 
 pub(super) async fn fetch_online_completions(
     server_rc: Rc<Mutex<Server>>,
-    query_unit: &QueryUnit,
+    declarations: &[(String, String)],
     backend: &BackendConfiguration,
     query_template: &str,
     mut query_template_context: Context,
 ) -> Result<Vec<InternalCompletionItem>, CompletionError> {
-    let (url, query, timeout_ms, method) = {
-        let server = server_rc.lock().await;
+    let (url, completion_query, cached, timeout_ms, method) = {
+        let mut server = server_rc.lock().await;
         query_template_context.insert("limit", &server.settings.completion.result_size_limit);
         query_template_context.insert("offset", &0);
-        let query = server
-            .tools
-            .tera
-            .render(query_template, &query_template_context)
-            .map_err(|err| CompletionError::Template(query_template.to_string(), err))?;
+        let render = |context: &Context| {
+            server
+                .tools
+                .tera
+                .render(query_template, context)
+                .map_err(|err| CompletionError::Template(query_template.to_string(), err))
+        };
+        let query = render(&query_template_context)?;
+        let search_term = query_template_context
+            .get("search_term")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        let search_term_uncompressed = query_template_context
+            .get("search_term_uncompressed")
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        let family = match search_term {
+            Some(_) => {
+                let mut family_context = query_template_context.clone();
+                family_context.insert("search_term", SEARCH_TERM_PLACEHOLDER);
+                if search_term_uncompressed.is_some() {
+                    family_context.insert("search_term_uncompressed", SEARCH_TERM_PLACEHOLDER);
+                }
+                Some(render(&family_context)?)
+            }
+            None => None,
+        };
+        let completion_query = CompletionQuery {
+            backend: backend.name.clone(),
+            template: query_template.to_string(),
+            query,
+            family,
+            search_term,
+            search_term_uncompressed,
+        };
+        let settings = &server.settings.completion;
+        let (ttl_ms, capacity) = (settings.cache_ttl_ms, settings.cache_size);
+        let cached = server.state.completion_cache.get(
+            &completion_query,
+            get_timestamp_ms(),
+            ttl_ms,
+            capacity,
+        );
 
         let url = backend.url.clone();
         let timeout_ms = server.settings.completion.timeout_ms;
         let method = server.state.get_backend_request_method(&backend.name);
-        (url, query, timeout_ms, method)
+        (url, completion_query, cached, timeout_ms, method)
+    };
+
+    let bindings = match cached {
+        Some(bindings) => {
+            tracing::debug!("Completion Query: \"{query_template}\" answered from cache");
+            bindings
+        }
+        None => {
+            fetch_bindings(
+                server_rc.clone(),
+                url,
+                &completion_query,
+                timeout_ms,
+                method,
+            )
+            .await?
+        }
     };
+    tracing::info!("Result size: {}", bindings.len());
+
+    let mut server = server_rc.lock().await;
+    to_internal_items(&mut server, declarations, &backend.name, &bindings)
+}
 
-    tracing::debug!("Completion Query: \"{query_template}\"\n{query}");
+/// Completion items from the index of a backend with local files,
+/// or `None` if `backend` has no files.
+async fn local_completions(
+    server_rc: Rc<Mutex<Server>>,
+    environment: &CompletionEnvironment,
+    declarations: &[(String, String)],
+    backend: &BackendConfiguration,
+    completion_template: &CompletionTemplate,
+) -> Option<Result<Vec<InternalCompletionItem>, CompletionError>> {
+    let mut server = server_rc.lock().await;
+    let bindings = local::bindings(
+        server.state.get_local_index(&backend.name)?,
+        completion_template,
+        environment,
+        declarations,
+        server.state.get_converter(&backend.name),
+        server.settings.completion.result_size_limit as usize,
+    );
+    tracing::info!("Local result size: {}", bindings.len());
+    Some(to_internal_items(
+        &mut server,
+        declarations,
+        &backend.name,
+        &bindings,
+    ))
+}
+
+fn to_internal_items(
+    server: &mut Server,
+    declarations: &[(String, String)],
+    backend_name: &str,
+    bindings: &[Binding],
+) -> Result<Vec<InternalCompletionItem>, CompletionError> {
+    bindings
+        .iter()
+        .map(|binding| {
+            let rdf_term = binding.get("qls_entity").ok_or_else(|| {
+                CompletionError::Request(
+                    "Completion query result is missing the `qls_entity` binding".to_string(),
+                )
+            })?;
+            let (value, import_edit) =
+                render_rdf_term(server, declarations, rdf_term, backend_name);
+            let entity = match rdf_term {
+                RDFTerm::Uri { value, .. } => Some(value.clone()),
+                _ => None,
+            };
+            let label = binding
+                .get("qls_label")
+                .map_or(String::new(), |rdf_term| rdf_term.value().to_string());
+            let detail = binding
+                .get("qls_alias")
+                .map(|rdf_term: &RDFTerm| rdf_term.value().to_string());
+            let score = binding
+                .get("qls_count")
+                .and_then(|rdf_term: &RDFTerm| rdf_term.value().parse().ok());
+            if !label.is_empty() {
+                server
+                    .state
+                    .label_memory
+                    .insert(value.clone(), label.clone());
+            }
+            Ok(InternalCompletionItem {
+                label,
+                detail,
+                value,
+                entity,
+                score,
+                import_edit,
+            })
+        })
+        .collect()
+}
+
+/// Sends a completion query to the backend and caches its bindings.
+pub(super) async fn fetch_bindings(
+    server_rc: Rc<Mutex<Server>>,
+    url: String,
+    completion_query: &CompletionQuery,
+    timeout_ms: u32,
+    method: RequestMethod,
+) -> Result<Rc<Vec<Binding>>, CompletionError> {
+    tracing::debug!(
+        "Completion Query: \"{}\"\n{}",
+        completion_query.template,
+        completion_query.query
+    );
 
     let result = execute_query(
         server_rc.clone(),
         url,
-        query,
+        completion_query.query.clone(),
         None,
         None,
         Some(timeout_ms),
@@ -152,79 +321,35 @@ pub(super) async fn fetch_online_completions(
         );
         return Err(CompletionError::Resolve(            "The SPARQL result of a completion query did not contain bindings. Likely because its not a SELECT query.".to_string()));
     };
-    tracing::info!("Result size: {}", bindings.len());
 
     let mut server = server_rc.lock().await;
-    bindings
-        .into_iter()
-        .map(|binding| {
-            let rdf_term = binding.get("qls_entity").ok_or_else(|| {
-                CompletionError::Request(
-                    "Completion query result is missing the `qls_entity` binding".to_string(),
-                )
-            })?;
-            let (value, import_edit) =
-                render_rdf_term(&server, query_unit, rdf_term, &backend.name);
-            let label = binding
-                .get("qls_label")
-                .map_or(String::new(), |rdf_term| rdf_term.value().to_string());
-            let detail = binding
-                .get("qls_alias")
-                .map(|rdf_term: &RDFTerm| rdf_term.value().to_string());
-            let score = binding
-                .get("qls_count")
-                .and_then(|rdf_term: &RDFTerm| rdf_term.value().parse().ok());
-            // NOTE: This is the text the in editor filter uses.
-            // If a compressed IRI is used as search term i.e. "wdt:p" the expanded iri is used as
-            // filter text. Otherwise the label and detail is used as filter text.
-            // This is currently not used, but should be redone at some point
-            let filter_text = query_template_context
-                .get("search_term_uncompressed")
-                .is_some()
-                .then_some(value.to_string())
-                .or((!label.is_empty()).then_some(format!(
-                    "{}{}",
-                    label,
-                    detail.as_ref().unwrap_or(&String::new())
-                )))
-                .or(Some(rdf_term.to_string()));
-            if !label.is_empty() {
-                server
-                    .state
-                    .label_memory
-                    .insert(value.clone(), label.clone());
-            }
-            Ok(InternalCompletionItem {
-                label,
-                detail,
-                value,
-                _filter_text: filter_text,
-                score,
-                import_edit,
-            })
-        })
-        .collect()
+    let bindings = Rc::new(bindings);
+    let settings = &server.settings.completion;
+    let truncated = bindings.len() >= settings.result_size_limit as usize;
+    let capacity = settings.cache_size;
+    server.state.completion_cache.insert(
+        completion_query,
+        bindings.clone(),
+        truncated,
+        get_timestamp_ms(),
+        capacity,
+    );
+    Ok(bindings)
 }
 
 fn render_rdf_term(
     server: &Server,
-    query_unit: &QueryUnit,
+    declarations: &[(String, String)],
     rdf_term: &RDFTerm,
     backend_name: &str,
 ) -> (String, Option<TextEdit>) {
     match rdf_term {
         RDFTerm::Uri { value, curie: _ } => match server.shorten_uri(value, Some(backend_name)) {
             Some((prefix, uri, curie)) => {
-                let prefix_decl_edit = if query_unit.prologue().as_ref().is_none_or(|prologue| {
-                    prologue
-                        .prefix_declarations()
-                        .iter()
-                        .all(|prefix_declaration| {
-                            prefix_declaration
-                                .prefix()
-                                .is_some_and(|declared_prefix| declared_prefix != prefix)
-                        })
-                }) {
+                let prefix_decl_edit = if declarations
+                    .iter()
+                    .all(|(declared_prefix, _)| *declared_prefix != prefix)
+                {
                     Some(TextEdit::new(
                         Range::new(0, 0, 0, 0),
                         &format!("PREFIX {}: <{}>\n", prefix, uri),
@@ -240,6 +365,34 @@ fn render_rdf_term(
     }
 }
 
+/// `local` in `namespace` as prefixed name: with the prefix the query declares for
+/// `namespace`, or else with `prefix` and the edit declaring it.
+///
+/// Falls back to the full IRI if `prefix` is declared for another namespace.
+pub(super) fn compact_with_prefix(
+    declarations: &[(String, String)],
+    prefix: &str,
+    namespace: &str,
+    local: &str,
+) -> (String, Option<TextEdit>) {
+    if let Some((declared, _)) = declarations
+        .iter()
+        .find(|(_, uri_prefix)| uri_prefix == namespace)
+    {
+        (format!("{declared}:{local}"), None)
+    } else if declarations.iter().any(|(declared, _)| declared == prefix) {
+        (format!("<{namespace}{local}>"), None)
+    } else {
+        (
+            format!("{prefix}:{local}"),
+            Some(TextEdit::new(
+                Range::new(0, 0, 0, 0),
+                &format!("PREFIX {prefix}: <{namespace}>\n"),
+            )),
+        )
+    }
+}
+
 pub(super) async fn get_prefix_declarations(root: &SyntaxNode) -> Vec<(String, String)> {
     root.first_child()
         .and_then(|child| child.first_child())
@@ -281,6 +434,15 @@ pub(super) fn reduce_path(
             )
         }
         SyntaxKind::PathAlternative => {
+            // NOTE: after a dangling "|" the cursor starts a new alternative,
+            // which connects the subject and the object like the whole path.
+            if path
+                .syntax()
+                .last_child_or_token()
+                .is_some_and(|elt| elt.kind() == SyntaxKind::Pipe)
+            {
+                return Some(format!("{} ?qls_entity {}", subject, object));
+            }
             reduce_path(subject, &path.sub_paths().last()?, object, offset)
         }
         SyntaxKind::PathSequence => {
@@ -318,12 +480,11 @@ pub(super) fn reduce_path(
         }
         SyntaxKind::PathEltOrInverse => {
             if path.syntax().first_child_or_token()?.kind() == SyntaxKind::Zirkumflex {
-                reduce_path(
-                    object,
-                    &Path::cast(path.syntax().last_child()?)?,
-                    subject,
-                    offset,
-                )
+                let Some(inverted) = path.syntax().last_child() else {
+                    // NOTE: a dangling "^", the inverted element is not typed yet.
+                    return Some(format!("{} ?qls_entity {}", object, subject));
+                };
+                reduce_path(object, &Path::cast(inverted)?, subject, offset)
             } else {
                 reduce_path(
                     subject,
@@ -353,14 +514,62 @@ pub(super) fn reduce_path(
     }
 }
 
+/// Orders the items by how well they match the search term, combined with their
+/// `qls_count`. Items without any match keep the order of the backend, after the
+/// matching ones.
+fn rank(
+    items: Vec<InternalCompletionItem>,
+    search_term: Option<&str>,
+) -> Vec<InternalCompletionItem> {
+    let Some(search_term) = search_term.filter(|search_term| !search_term.is_empty()) else {
+        return items;
+    };
+    let mut ranked: Vec<(Option<f64>, InternalCompletionItem)> = items
+        .into_iter()
+        .map(|item| {
+            let quality = [
+                Some(item.label.as_str()),
+                item.detail.as_deref(),
+                Some(item.value.as_str()),
+                Some(local_name(&item.value)),
+            ]
+            .into_iter()
+            .flatten()
+            .filter_map(|candidate| match_quality(candidate, search_term))
+            .max();
+            // NOTE: a popular entity can outrank a better match by a few levels,
+            // e.g. 10^6 occurrences are worth 30.
+            let popularity = item
+                .score
+                .map_or(0.0, |count| 5.0 * (count as f64 + 1.0).log10());
+            (quality.map(|quality| quality as f64 + popularity), item)
+        })
+        .collect();
+    // NOTE: the sort is stable, equal scores keep the order of the backend.
+    ranked.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+    ranked.into_iter().map(|(_, item)| item).collect()
+}
+
+/// The local name of a rendered IRI, e.g. `P569` for `wdt:P569` or
+/// `birthDate` for `<http://dbpedia.org/ontology/birthDate>`.
+fn local_name(value: &str) -> &str {
+    let iri = value.trim_start_matches('<').trim_end_matches('>');
+    iri.rsplit(['/', '#', ':']).next().unwrap_or(iri)
+}
+
+/// Converts the ranked items into LSP completion items.
+///
+/// With a `resolve_backend`, every entity item carries its IRI and the backend
+/// in `data`, so `completionItem/resolve` can run its `entityDetails` query.
 pub(super) fn to_completion_items(
     items: Vec<InternalCompletionItem>,
     range: Range,
     command: Option<&str>,
     _limit: u32,
     search_term: Option<&str>,
+    resolve_backend: Option<&str>,
 ) -> CompletionList {
-    let items: Vec<_> = items
+    let items: Vec<_> = rank(items, search_term)
         .into_iter()
         .enumerate()
         .map(
@@ -370,7 +579,7 @@ pub(super) fn to_completion_items(
                     label,
                     detail,
                     value,
-                    _filter_text,
+                    entity,
                     score,
                     import_edit,
                 },
@@ -387,11 +596,11 @@ pub(super) fn to_completion_items(
                         ),
                     }),
                     detail: None,
-                    documentation: Some(format!(
+                    documentation: Some(Documentation::String(format!(
                         "Label: {label}\nAlias: {}\nScore: {}",
                         detail.unwrap_or_default(),
                         score.map_or("None".to_string(), |score| score.to_string()),
-                    )),
+                    ))),
                     // NOTE: The first 100 ID's are reserved
                     sort_text: Some(format!("{:0>5}", idx + 100)),
                     insert_text: None,
@@ -411,6 +620,12 @@ pub(super) fn to_completion_items(
                         command: command.to_string(),
                         arguments: None,
                     }),
+                    data: resolve_backend.zip(entity).map(|(backend, entity)| {
+                        LSPAny::LSPObject(HashMap::from([
+                            ("entity".to_string(), LSPAny::String(entity)),
+                            ("backend".to_string(), LSPAny::String(backend.to_string())),
+                        ]))
+                    }),
                 }
             },
         )
@@ -774,4 +989,78 @@ mod test {
         .unwrap();
         assert_eq!(res, reduced);
     }
+
+    #[test]
+    fn reduce_dangling_alternative_path() {
+        //       012345678901234567890123
+        let s = "Select * { ?a <p0>/<p1>|";
+        let reduced = "?a ?qls_entity []";
+        let offset = 24;
+
+        let (tree, _) = parse_query(s);
+        let query_unit = QueryUnit::cast(tree).unwrap();
+        let triples = query_unit
+            .select_query()
+            .unwrap()
+            .where_clause()
+            .unwrap()
+            .group_graph_pattern()
+            .unwrap()
+            .triple_blocks()
+            .first()
+            .unwrap()
+            .triples();
+        let triple = triples.first().unwrap();
+        let res = reduce_path(
+            &triple.subject().unwrap().text(),
+            &triple
+                .properties_list_path()
+                .unwrap()
+                .properties()
+                .last()
+                .unwrap()
+                .verb,
+            "[]",
+            offset.into(),
+        )
+        .unwrap();
+        assert_eq!(res, reduced);
+    }
+
+    #[test]
+    fn reduce_dangling_inverse_path() {
+        //       01234567890123456789
+        let s = "Select * { ?a <p0>/^";
+        let reduced = "?a <p0> ?qls_inner . [] ?qls_entity ?qls_inner";
+        let offset = 20;
+
+        let (tree, _) = parse_query(s);
+        let query_unit = QueryUnit::cast(tree).unwrap();
+        let triples = query_unit
+            .select_query()
+            .unwrap()
+            .where_clause()
+            .unwrap()
+            .group_graph_pattern()
+            .unwrap()
+            .triple_blocks()
+            .first()
+            .unwrap()
+            .triples();
+        let triple = triples.first().unwrap();
+        let res = reduce_path(
+            &triple.subject().unwrap().text(),
+            &triple
+                .properties_list_path()
+                .unwrap()
+                .properties()
+                .last()
+                .unwrap()
+                .verb,
+            "[]",
+            offset.into(),
+        )
+        .unwrap();
+        assert_eq!(res, reduced);
+    }
 }
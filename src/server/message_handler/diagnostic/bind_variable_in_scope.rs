@@ -0,0 +1,78 @@
+//! Flags `BIND(... AS ?var)` where `?var` is already in scope.
+//!
+//! SPARQL forbids binding a variable that is already bound by the part of the
+//! group graph pattern preceding the `BIND`. Variables that only occur in
+//! `FILTER` or `MINUS` are not in scope and therefore not reported.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, Bind, GraphPatternNotTriples, QueryUnit, TriplesBlock},
+    syntax_kind::SyntaxKind,
+};
+use std::{collections::HashSet, sync::LazyLock};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("bind-var-in-scope".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter_map(Bind::cast)
+            .filter_map(|bind| {
+                let variable = bind.visible_variables().pop()?;
+                // NOTE: Bind -> GraphPatternNotTriples -> GroupGraphPatternSub
+                let group = bind.syntax().parent()?.parent()?;
+                let in_scope: HashSet<String> = group
+                    .children()
+                    .take_while(|child| {
+                        child.text_range().start() < bind.syntax().text_range().start()
+                    })
+                    .flat_map(|child| match child.kind() {
+                        SyntaxKind::TriplesBlock => TriplesBlock::cast(child)
+                            .map(|triples_block| triples_block.visible_variables())
+                            .unwrap_or_default(),
+                        SyntaxKind::GraphPatternNotTriples => {
+                            match GraphPatternNotTriples::cast(child) {
+                                Some(
+                                    GraphPatternNotTriples::Filter(_)
+                                    | GraphPatternNotTriples::MinusGraphPattern(_),
+                                )
+                                | None => vec![],
+                                Some(pattern) => pattern.visible_variables(),
+                            }
+                        }
+                        _ => vec![],
+                    })
+                    .map(|var| var.text())
+                    .collect();
+                in_scope.contains(&variable.text()).then(|| Diagnostic {
+                    code: Some((*CODE).clone()),
+                    range: Range::from_byte_offset_range(
+                        variable.syntax().text_range(),
+                        &document.text,
+                    )
+                    .unwrap(),
+                    severity: DiagnosticSeverity::Error,
+                    message: format!(
+                        "{} is already in scope and can not be bound by BIND",
+                        variable.text()
+                    ),
+                    data: None,
+                    source: None,
+                })
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,53 @@
+//! Flags variables that are declared more than once in a `VALUES` header,
+//! e.g. `VALUES (?x ?x) { ... }`.
+//!
+//! The first declaration is accepted, every repetition is reported as an
+//! error.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, QueryUnit, Var},
+    syntax_kind::SyntaxKind,
+};
+use std::{collections::HashSet, sync::LazyLock};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("duplicate-values-var".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::InlineDataFull)
+            .flat_map(|inline_data| {
+                let mut declared = HashSet::new();
+                inline_data
+                    .children_with_tokens()
+                    .take_while(|child| child.kind() != SyntaxKind::LCurly)
+                    .filter_map(|child| child.into_node().and_then(Var::cast))
+                    .filter(move |var| !declared.insert(var.text()))
+                    .collect::<Vec<_>>()
+            })
+            .map(|var| Diagnostic {
+                code: Some((*CODE).clone()),
+                range: Range::from_byte_offset_range(var.syntax().text_range(), &document.text)
+                    .unwrap(),
+                severity: DiagnosticSeverity::Error,
+                message: format!("{} is declared multiple times in this VALUES", var.text()),
+                data: None,
+                source: None,
+            })
+            .collect(),
+    )
+}
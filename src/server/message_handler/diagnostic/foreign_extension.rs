@@ -0,0 +1,69 @@
+//! Warns about extension functions and magic predicates of another engine.
+//!
+//! An IRI in the extension namespace of an engine, e.g. Virtuoso's `bif:`, is
+//! flagged when the backend it is sent to runs an engine without that
+//! namespace. Backends without a configured engine are not checked.
+
+use std::sync::LazyLock;
+
+use crate::server::{
+    Server,
+    extension_catalog::foreign_namespace,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+    message_handler::{common::expand_iri, misc::resolve_backend_at_token},
+};
+use ll_sparql_parser::{
+    ast::{AstNode, Iri, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("foreign-extension".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::iri)
+            .filter_map(Iri::cast)
+            .filter_map(|iri| {
+                let backend =
+                    resolve_backend_at_token(server, query_unit, &iri.syntax().first_token()?)?;
+                let engine = backend.engine.as_ref()?;
+                let (namespace, engines) =
+                    foreign_namespace(engine, &expand_iri(&iri, query_unit)?)?;
+                let supported_by = engines
+                    .iter()
+                    .map(|engine| format!("{engine:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                Some(Diagnostic {
+                    source: None,
+                    code: Some((*CODE).clone()),
+                    range: Range::from_byte_offset_range(
+                        iri.syntax().text_range(),
+                        &document.text,
+                    )?,
+                    severity: DiagnosticSeverity::Warning,
+                    message: format!(
+                        "\"{}\" is in the `{}:` namespace of {}, but backend \"{}\" runs {:?}",
+                        iri.text(),
+                        namespace.prefix,
+                        supported_by,
+                        backend.name,
+                        engine
+                    ),
+                    data: None,
+                })
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,110 @@
+//! Flags plain variables in the projection of an implicitly grouped query.
+//!
+//! A `SELECT` that uses an aggregate in its projection, `HAVING` or `ORDER BY`
+//! clause but has no `GROUP BY` clause groups all solutions into a single
+//! group. Projecting a plain variable is then illegal, e.g.
+//! `SELECT ?s (COUNT(?o) AS ?n)` or `SELECT ?s … HAVING (COUNT(?o) > 1)`.
+//! Sub-selects are checked as well.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, GroupClause, QueryUnit, SelectQuery, Var},
+    syntax_kind::SyntaxKind,
+};
+use std::{collections::HashMap, iter, sync::LazyLock};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("implicit-grouping".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter_map(SelectQuery::cast)
+            .flat_map(|select_query| ungrouped_projection(&select_query))
+            .map(|var| Diagnostic {
+                code: Some((*CODE).clone()),
+                range: Range::from_byte_offset_range(var.syntax().text_range(), &document.text)
+                    .unwrap(),
+                severity: DiagnosticSeverity::Error,
+                message: format!(
+                    "{} is not aggregated, but the query is implicitly grouped by an aggregate\nAdd a GROUP BY clause or aggregate {}.",
+                    var.text(),
+                    var.text()
+                ),
+                data: None,
+                source: None,
+            })
+            .collect(),
+    )
+}
+
+fn ungrouped_projection(select_query: &SelectQuery) -> Vec<Var> {
+    let Some(select_clause) = select_query.select_clause() else {
+        return vec![];
+    };
+    let modifier_clauses: Vec<_> = select_query
+        .syntax()
+        .children()
+        .filter(|child| child.kind() == SyntaxKind::SolutionModifier)
+        .flat_map(|solution_modifier| solution_modifier.children())
+        .collect();
+    let has_group_clause = modifier_clauses
+        .iter()
+        .any(|clause| GroupClause::can_cast(clause.kind()));
+    let has_aggregate = modifier_clauses
+        .iter()
+        .filter(|clause| {
+            matches!(
+                clause.kind(),
+                SyntaxKind::HavingClause | SyntaxKind::OrderClause
+            )
+        })
+        .chain(iter::once(select_clause.syntax()))
+        .any(|clause| {
+            clause
+                .descendants()
+                .any(|node| node.kind() == SyntaxKind::Aggregate)
+        });
+    if has_group_clause || !has_aggregate {
+        return vec![];
+    }
+    let assignments = select_clause.assignments();
+    // NOTE: an assignment may refer to the alias of a preceding assignment
+    let derived_variables: &HashMap<String, usize> = &assignments
+        .iter()
+        .enumerate()
+        .map(|(idx, assignment)| (assignment.variable.text(), idx))
+        .collect();
+    select_clause
+        .variables()
+        .into_iter()
+        .chain(
+            assignments
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, assignment)| {
+                    assignment
+                        .expression
+                        .unaggregated_variables()
+                        .into_iter()
+                        .filter(move |var| {
+                            derived_variables
+                                .get(&var.text())
+                                .is_none_or(|derived_idx| *derived_idx >= idx)
+                        })
+                }),
+        )
+        .collect()
+}
@@ -0,0 +1,61 @@
+//! Flags aggregates outside of the places SPARQL allows them in.
+//!
+//! Aggregates may only appear in `SELECT` expressions, `HAVING` and
+//! `ORDER BY`. An aggregate in a `FILTER`, `BIND`, `GROUP BY` or elsewhere in
+//! the `WHERE` clause is reported as an error, in sub-selects as well.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+use std::sync::LazyLock;
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("misplaced-aggregate".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::Aggregate)
+            .filter_map(|aggregate| {
+                let location = aggregate
+                    .ancestors()
+                    .find_map(|ancestor| match ancestor.kind() {
+                        SyntaxKind::SelectClause
+                        | SyntaxKind::HavingClause
+                        | SyntaxKind::OrderClause => Some(None),
+                        SyntaxKind::Filter => Some(Some("FILTER")),
+                        SyntaxKind::Bind => Some(Some("BIND")),
+                        SyntaxKind::GroupClause => Some(Some("GROUP BY")),
+                        SyntaxKind::GroupGraphPattern => Some(Some("the WHERE clause")),
+                        _ => None,
+                    })
+                    .flatten()?;
+                Some(Diagnostic {
+                    code: Some((*CODE).clone()),
+                    range: Range::from_byte_offset_range(aggregate.text_range(), &document.text)
+                        .unwrap(),
+                    severity: DiagnosticSeverity::Error,
+                    message: format!(
+                        "Aggregates are not allowed in {location}\nUse them in SELECT, HAVING or ORDER BY."
+                    ),
+                    data: None,
+                    source: None,
+                })
+            })
+            .collect(),
+    )
+}
@@ -1,13 +1,25 @@
 mod auto_fix_prefixes;
+pub mod bind_variable_in_scope;
 pub mod duplicate_prefix_declaration;
+pub mod duplicate_values_variable;
+pub mod foreign_extension;
 pub mod groupby_star_selection;
+pub mod implicit_grouping;
 pub mod invalid_projection_variable;
+pub mod misplaced_aggregate;
+pub mod nested_aggregate;
+pub mod performance_hint;
+pub mod reused_blank_node_label;
 pub mod same_subject;
 pub mod syntax_error;
+pub mod type_mismatch;
 pub mod uncompacted_uri;
 pub mod undeclared_prefix;
+pub mod ungrouped_modifier_variable;
 pub mod ungrouped_select_variable;
 pub mod unused_prefix_declaration;
+pub mod unused_term;
+pub mod values_row_length;
 
 use crate::server::{
     Server,
@@ -52,10 +64,25 @@ pub(super) async fn handle_diagnostic_request(
     add_diagnostic!(syntax_error::diagnostics);
     add_diagnostic!(duplicate_prefix_declaration::diagnostics);
     add_diagnostic!(groupby_star_selection::diagnostics);
+    add_diagnostic!(type_mismatch::diagnostics);
+    add_diagnostic!(bind_variable_in_scope::diagnostics);
+    add_diagnostic!(values_row_length::diagnostics);
+    add_diagnostic!(duplicate_values_variable::diagnostics);
+    add_diagnostic!(reused_blank_node_label::diagnostics);
+    add_diagnostic!(nested_aggregate::diagnostics);
+    add_diagnostic!(misplaced_aggregate::diagnostics);
+    add_diagnostic!(ungrouped_modifier_variable::diagnostics);
+    add_diagnostic!(implicit_grouping::diagnostics);
+    add_diagnostic!(performance_hint::diagnostics);
+    add_diagnostic!(foreign_extension::diagnostics);
 
     if client_support_workspace_edits(&server) {
         auto_fix_prefixes(&mut server, &request, &diagnostic_accu);
     }
-
+    diagnostic_accu.extend(unused_term::diagnostics(
+        server_rc.clone(),
+        &mut server,
+        &request.params.text_document.uri,
+    ));
     server.send_message(DiagnosticResponse::new(request.get_id(), diagnostic_accu))
 }
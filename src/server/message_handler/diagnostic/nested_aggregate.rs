@@ -0,0 +1,55 @@
+//! Flags aggregates that are nested inside other aggregates,
+//! e.g. `SUM(COUNT(?x))`.
+//!
+//! SPARQL does not allow aggregating over aggregated values within one query
+//! level. Each inner aggregate is reported as an error; the usual fix is to
+//! compute it in a sub-select.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+use std::sync::LazyLock;
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("nested-aggregate".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::Aggregate)
+            .filter(|aggregate| {
+                // NOTE: an aggregate in a sub-select (via EXISTS) starts a new query level
+                aggregate
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|ancestor| ancestor.kind() != SyntaxKind::GroupGraphPattern)
+                    .any(|ancestor| ancestor.kind() == SyntaxKind::Aggregate)
+            })
+            .map(|aggregate| Diagnostic {
+                code: Some((*CODE).clone()),
+                range: Range::from_byte_offset_range(aggregate.text_range(), &document.text)
+                    .unwrap(),
+                severity: DiagnosticSeverity::Error,
+                message:
+                    "Aggregates can not be nested\nCompute the inner aggregate in a sub-select."
+                        .to_string(),
+                data: None,
+                source: None,
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,299 @@
+//! Hints at query shapes that are known to be slow.
+//!
+//! Each hint is a [`PerformanceRule`]. Which rules are reported depends on the
+//! engine of the backend the pattern runs on, e.g. the endpoint of a `SERVICE`,
+//! since engines have different pitfalls, and can be configured with
+//! `diagnostics.performanceRules`. The hints are opt-in
+//! via `diagnostics.performanceHints`.
+
+use crate::server::{
+    Server,
+    configuration::PerformanceRule,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+    message_handler::misc::resolve_backend_at_token,
+};
+use ll_sparql_parser::{
+    SyntaxNode,
+    ast::{AstNode, PropertyListPath, QueryUnit, Triple},
+    syntax_kind::SyntaxKind,
+};
+use std::sync::LazyLock;
+use text_size::TextRange;
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("performance-hint".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    if !server.settings.diagnostics.performance_hints {
+        return None;
+    }
+    let root = query_unit.syntax();
+    // NOTE: a hint is reported if its rule applies to the engine of the backend
+    // the hinted pattern runs on.
+    let applies = |rule: &PerformanceRule, range: &TextRange| {
+        let engine = root
+            .token_at_offset(range.start())
+            .right_biased()
+            .and_then(|token| resolve_backend_at_token(server, query_unit, &token))
+            .and_then(|backend| backend.engine);
+        server
+            .settings
+            .diagnostics
+            .performance_rules_for(engine.as_ref())
+            .contains(rule)
+    };
+    Some(
+        PerformanceRule::ALL
+            .into_iter()
+            .flat_map(|rule| {
+                match rule {
+                    PerformanceRule::RegexScan => regex_scans(root),
+                    PerformanceRule::SubSelectOrderWithoutLimit => unlimited_orders(root),
+                    PerformanceRule::UnboundStarPath => unbound_star_paths(root),
+                    PerformanceRule::FilterOnlyOptional => filter_only_optionals(root),
+                    PerformanceRule::DistinctStar => distinct_stars(root),
+                    PerformanceRule::ConstantFilter => constant_filters(root),
+                }
+                .into_iter()
+                .filter(move |(range, _)| applies(&rule, range))
+            })
+            .map(|(range, message)| Diagnostic {
+                code: Some((*CODE).clone()),
+                range: Range::from_byte_offset_range(range, &document.text).unwrap(),
+                severity: DiagnosticSeverity::Information,
+                message,
+                data: None,
+                source: None,
+            })
+            .collect(),
+    )
+}
+
+/// The content of `expression` if it is nothing but a string literal.
+fn string_literal(expression: &SyntaxNode) -> Option<String> {
+    let string = expression
+        .descendants()
+        .find(|node| node.kind() == SyntaxKind::String)?;
+    if string.text_range() != expression.text_range() {
+        return None;
+    }
+    let text = string.text().to_string();
+    let quotes = if text.starts_with("\"\"\"") || text.starts_with("'''") {
+        3
+    } else {
+        1
+    };
+    text.get(quotes..text.len().checked_sub(quotes)?)
+        .map(str::to_string)
+}
+
+fn regex_scans(root: &SyntaxNode) -> Vec<(TextRange, String)> {
+    root.descendants()
+        .filter(|node| node.kind() == SyntaxKind::RegexExpression)
+        .filter_map(|regex| {
+            let arguments: Vec<_> = regex
+                .children()
+                .filter(|child| child.kind() == SyntaxKind::Expression)
+                .collect();
+            let pattern = arguments.get(1).and_then(string_literal);
+            let flags = arguments.get(2).and_then(string_literal);
+            let message = if pattern
+                .as_ref()
+                .is_some_and(|pattern| pattern.starts_with(".*") || pattern.starts_with(".+"))
+            {
+                "REGEX with a leading wildcard has to scan every value\nAnchor the pattern with `^` or use STRSTARTS/CONTAINS."
+            } else if flags.is_some_and(|flags| flags.contains('i')) {
+                "Case-insensitive REGEX has to scan every value\nMatch on LCASE(...) with STRSTARTS/CONTAINS instead."
+            } else {
+                return None;
+            };
+            Some((regex.text_range(), message.to_string()))
+        })
+        .collect()
+}
+
+fn unlimited_orders(root: &SyntaxNode) -> Vec<(TextRange, String)> {
+    root.descendants()
+        .filter(|node| node.kind() == SyntaxKind::SubSelect)
+        .filter_map(|sub_select| {
+            let solution_modifier = sub_select
+                .children()
+                .find(|child| child.kind() == SyntaxKind::SolutionModifier)?;
+            let order_clause = solution_modifier
+                .children()
+                .find(|child| child.kind() == SyntaxKind::OrderClause)?;
+            (!solution_modifier
+                .descendants()
+                .any(|node| node.kind() == SyntaxKind::LimitClause))
+            .then(|| {
+                (
+                    order_clause.text_range(),
+                    "ORDER BY without LIMIT in a sub-select sorts all solutions, but does not affect the result"
+                        .to_string(),
+                )
+            })
+        })
+        .collect()
+}
+
+fn unbound_star_paths(root: &SyntaxNode) -> Vec<(TextRange, String)> {
+    // NOTE: a variable that occurs only once in the patterns is not bound by
+    // anything else.
+    let occurrences = |var: &str| {
+        root.descendants()
+            .filter(|node| node.kind() == SyntaxKind::Var && node.text() == var)
+            .filter(|node| {
+                !node.ancestors().any(|ancestor| {
+                    matches!(
+                        ancestor.kind(),
+                        SyntaxKind::SelectClause | SyntaxKind::SolutionModifier
+                    )
+                })
+            })
+            .count()
+    };
+    let is_unbound_var = |node: &SyntaxNode| {
+        node.text_range().len() > 0.into()
+            && node
+                .descendants()
+                .find(|child| child.kind() == SyntaxKind::Var)
+                .is_some_and(|var| {
+                    var.text_range() == node.text_range()
+                        && occurrences(&var.text().to_string()) == 1
+                })
+    };
+    root.descendants()
+        .filter_map(Triple::cast)
+        .filter(|triple| {
+            triple
+                .subject()
+                .is_some_and(|subject| is_unbound_var(subject.syntax()))
+        })
+        .filter_map(|triple| triple.properties_list_path())
+        .flat_map(|property_list: PropertyListPath| property_list.properties())
+        .filter(|property| {
+            property.verb.syntax().descendants().any(|node| {
+                node.kind() == SyntaxKind::PathMod
+                    && node
+                        .first_token()
+                        .is_some_and(|token| token.kind() == SyntaxKind::Star)
+            }) && property.object.as_ref().is_some_and(|object| {
+                object
+                    .syntax()
+                    .children()
+                    .any(|object| is_unbound_var(&object))
+            })
+        })
+        .map(|property| {
+            (
+                property.verb.syntax().text_range(),
+                "A `*` path between two unbound variables computes the closure over the whole dataset\nBind the subject or the object."
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+fn filter_only_optionals(root: &SyntaxNode) -> Vec<(TextRange, String)> {
+    root.descendants()
+        .filter(|node| node.kind() == SyntaxKind::OptionalGraphPattern)
+        .filter(|optional| {
+            optional
+                .children()
+                .find(|child| child.kind() == SyntaxKind::GroupGraphPattern)
+                .and_then(|group| {
+                    group
+                        .children()
+                        .find(|child| child.kind() == SyntaxKind::GroupGraphPatternSub)
+                })
+                .is_some_and(|sub| {
+                    let mut children = sub.children().peekable();
+                    children.peek().is_some()
+                        && children.all(|child| {
+                            child.kind() == SyntaxKind::GraphPatternNotTriples
+                                && child
+                                    .first_child()
+                                    .is_some_and(|child| child.kind() == SyntaxKind::Filter)
+                        })
+                })
+        })
+        .map(|optional| {
+            (
+                optional.text_range(),
+                "This OPTIONAL only contains a FILTER and does not change the result".to_string(),
+            )
+        })
+        .collect()
+}
+
+fn distinct_stars(root: &SyntaxNode) -> Vec<(TextRange, String)> {
+    root.descendants()
+        .filter(|node| node.kind() == SyntaxKind::SelectClause)
+        .filter(|select_clause| {
+            let kinds: Vec<_> = select_clause
+                .children_with_tokens()
+                .map(|child| child.kind())
+                .collect();
+            kinds.contains(&SyntaxKind::DISTINCT) && kinds.contains(&SyntaxKind::Star)
+        })
+        .map(|select_clause| {
+            (
+                select_clause.text_range(),
+                "DISTINCT over SELECT * compares complete solutions\nSelect only the variables you need."
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+fn constant_filters(root: &SyntaxNode) -> Vec<(TextRange, String)> {
+    root.descendants()
+        .filter(|node| node.kind() == SyntaxKind::Filter)
+        .filter_map(|filter| {
+            let relational = filter
+                .descendants()
+                .find(|node| node.kind() == SyntaxKind::RelationalExpression)?;
+            // NOTE: only filters that consist of the comparison alone
+            let expression = relational
+                .ancestors()
+                .find(|node| node.kind() == SyntaxKind::Expression)?;
+            if expression.text_range() != relational.text_range()
+                || expression.parent()?.kind() != SyntaxKind::BrackettedExpression
+                || expression.parent()?.parent()?.parent()? != filter
+                || !relational
+                    .children_with_tokens()
+                    .any(|child| child.kind() == SyntaxKind::Equals)
+            {
+                return None;
+            }
+            let operand_kinds: Vec<_> = relational
+                .children()
+                .map(|operand| {
+                    operand
+                        .descendants()
+                        .find(|node| {
+                            node.text_range() == operand.text_range()
+                                && matches!(node.kind(), SyntaxKind::Var | SyntaxKind::iri)
+                        })
+                        .map(|node| (node.kind(), node.text().to_string()))
+                })
+                .collect();
+            let (var, iri) = match operand_kinds.as_slice() {
+                [Some((SyntaxKind::Var, var)), Some((SyntaxKind::iri, iri))]
+                | [Some((SyntaxKind::iri, iri)), Some((SyntaxKind::Var, var))] => (var, iri),
+                _ => return None,
+            };
+            Some((
+                filter.text_range(),
+                format!("Use {iri} in place of {var} instead of filtering for it"),
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,62 @@
+//! Flags blank node labels (`_:b`) that are used in more than one basic graph
+//! pattern.
+//!
+//! A blank node label is scoped to the basic graph pattern it occurs in, so
+//! SPARQL forbids reusing it in a different one. The first basic graph pattern
+//! using a label owns it, occurrences in any other pattern are errors.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+use std::{collections::HashMap, sync::LazyLock};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("reused-blank-node-label".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    let mut owners = HashMap::new();
+    Some(
+        query_unit
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| token.kind() == SyntaxKind::BLANK_NODE_LABEL)
+            .filter_map(|label| {
+                // NOTE: the outermost TriplesBlock of the enclosing group is the
+                // basic graph pattern.
+                let basic_graph_pattern = label
+                    .parent_ancestors()
+                    .take_while(|ancestor| ancestor.kind() != SyntaxKind::GroupGraphPattern)
+                    .filter(|ancestor| ancestor.kind() == SyntaxKind::TriplesBlock)
+                    .last()?;
+                let owner = owners
+                    .entry(label.text().to_string())
+                    .or_insert_with(|| basic_graph_pattern.clone());
+                (*owner != basic_graph_pattern).then(|| Diagnostic {
+                    code: Some((*CODE).clone()),
+                    range: Range::from_byte_offset_range(label.text_range(), &document.text)
+                        .unwrap(),
+                    severity: DiagnosticSeverity::Error,
+                    message: format!(
+                        "Blank node label {} is already used in another basic graph pattern",
+                        label.text()
+                    ),
+                    data: None,
+                    source: None,
+                })
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,553 @@
+//! Infers the types of SPARQL expressions and flags obvious type mismatches.
+//!
+//! Expressions in `FILTER`, `BIND`, `SELECT` projections, `HAVING` and
+//! `ORDER BY` are typed bottom-up from their constants. Variables are of
+//! unknown type, so only operands that are statically known to be wrong are
+//! reported, e.g. `"a" + 1`, `LANG(<iri>)`, `STRLEN(42)` or `1 && ?x`.
+//! Calls of built-in and XSD constructor functions with the wrong arity are
+//! errors.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    SyntaxNode, SyntaxToken,
+    ast::{AstNode, Iri, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+use std::{collections::HashMap, fmt::Display, iter, ops::RangeInclusive, sync::LazyLock};
+use text_size::{TextRange, TextSize};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("type-mismatch".to_string()));
+
+const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema#";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Boolean,
+    Numeric,
+    String,
+    DateTime,
+    /// A literal with an unknown datatype
+    Literal,
+    Iri,
+    Unknown,
+}
+
+impl Type {
+    /// Returns true if a value of this type may be used where `expected` is required.
+    fn conforms_to(self, expected: Type) -> bool {
+        match (self, expected) {
+            (_, Type::Unknown) | (Type::Unknown, _) => true,
+            (Type::Iri, _) | (_, Type::Iri) => self == expected,
+            (Type::Literal, _) | (_, Type::Literal) => true,
+            _ => self == expected,
+        }
+    }
+
+    fn is_known(self) -> bool {
+        !matches!(self, Type::Literal | Type::Unknown)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Boolean => write!(f, "boolean"),
+            Type::Numeric => write!(f, "numeric"),
+            Type::String => write!(f, "string"),
+            Type::DateTime => write!(f, "dateTime"),
+            Type::Literal => write!(f, "literal"),
+            Type::Iri => write!(f, "IRI"),
+            Type::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    let checker = TypeChecker {
+        document,
+        prefixes: query_unit
+            .prologue()
+            .map(|prologue| {
+                prologue
+                    .prefix_declarations()
+                    .into_iter()
+                    .filter_map(|declaration| {
+                        Some((declaration.prefix()?, declaration.raw_uri_prefix()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .flat_map(|node| checker.check(&node))
+            .collect(),
+    )
+}
+
+struct TypeChecker<'a> {
+    document: &'a TextDocumentItem,
+    prefixes: HashMap<String, String>,
+}
+
+impl TypeChecker<'_> {
+    fn check(&self, node: &SyntaxNode) -> Vec<Diagnostic> {
+        match node.kind() {
+            SyntaxKind::ConditionalOrExpression | SyntaxKind::ConditionalAndExpression
+                if node.children().count() > 1 =>
+            {
+                let operator = if node.kind() == SyntaxKind::ConditionalOrExpression {
+                    "||"
+                } else {
+                    "&&"
+                };
+                self.check_operands(node.children(), Type::Boolean, |found| {
+                    format!("`{operator}` expects boolean operands, found {found}")
+                })
+            }
+            SyntaxKind::AdditiveExpression | SyntaxKind::MultiplicativeExpression
+                if node.children().count() > 1 =>
+            {
+                self.check_operands(node.children(), Type::Numeric, |found| {
+                    format!("Arithmetic expects numeric operands, found {found}")
+                })
+            }
+            SyntaxKind::UnaryExpression => match first_token_kind(node) {
+                Some(SyntaxKind::ExclamationMark) => {
+                    self.check_operands(node.children(), Type::Boolean, |found| {
+                        format!("`!` expects a boolean operand, found {found}")
+                    })
+                }
+                Some(SyntaxKind::Plus | SyntaxKind::Minus) => {
+                    self.check_operands(node.children(), Type::Numeric, |found| {
+                        format!("Unary sign expects a numeric operand, found {found}")
+                    })
+                }
+                _ => vec![],
+            },
+            SyntaxKind::RelationalExpression => self.check_comparison(node).into_iter().collect(),
+            SyntaxKind::BuiltInCall => self.check_built_in_call(node),
+            SyntaxKind::iriOrFunction | SyntaxKind::FunctionCall => {
+                self.check_function_call(node).into_iter().collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn check_operands(
+        &self,
+        operands: impl Iterator<Item = SyntaxNode>,
+        expected: Type,
+        message: impl Fn(Type) -> String,
+    ) -> Vec<Diagnostic> {
+        operands
+            .filter_map(|operand| {
+                let found = self.infer(&operand);
+                (!found.conforms_to(expected))
+                    .then(|| self.diagnostic(&operand, DiagnosticSeverity::Warning, message(found)))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Ordering operators (`<`, `>`, `<=`, `>=`) are only defined between
+    /// values of the same kind and never for IRIs.
+    fn check_comparison(&self, node: &SyntaxNode) -> Option<Diagnostic> {
+        let operator = node
+            .children_with_tokens()
+            .filter_map(|child| child.into_token())
+            .find(|token| !token.kind().is_trivia())?;
+        if !matches!(
+            operator.kind(),
+            SyntaxKind::Less | SyntaxKind::More | SyntaxKind::LessEquals | SyntaxKind::MoreEquals
+        ) {
+            return None;
+        }
+        let mut operands = node.children();
+        let lhs = self.infer(&operands.next()?);
+        let rhs = self.infer(&operands.next()?);
+        (lhs.is_known() && rhs.is_known() && (lhs != rhs || lhs == Type::Iri))
+            .then(|| {
+                self.diagnostic(
+                    node,
+                    DiagnosticSeverity::Warning,
+                    format!(
+                        "`{}` can not order a {lhs} and a {rhs} value",
+                        operator.text()
+                    ),
+                )
+            })
+            .flatten()
+    }
+
+    fn check_built_in_call(&self, node: &SyntaxNode) -> Vec<Diagnostic> {
+        let call = built_in_call_body(node);
+        let Some(keyword) = call.first_token() else {
+            return vec![];
+        };
+        if let Some(diagnostic) = self.check_arity(&keyword) {
+            return vec![diagnostic];
+        }
+        let Some((params, _)) = signature(keyword.kind()) else {
+            return vec![];
+        };
+        let Some(last_param) = params.last() else {
+            return vec![];
+        };
+        // NOTE: variadic functions (CONCAT, COALESCE) repeat their last parameter
+        call_arguments(&call)
+            .into_iter()
+            .zip(params.iter().chain(iter::repeat(last_param)))
+            .filter_map(|(argument, expected)| {
+                let found = self.infer(&argument);
+                (!found.conforms_to(*expected)).then(|| {
+                    self.diagnostic(
+                        &argument,
+                        DiagnosticSeverity::Warning,
+                        format!(
+                            "`{}` expects a {expected} argument, found {found}",
+                            keyword.text().to_uppercase()
+                        ),
+                    )
+                })
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// A call with the wrong number of arguments does not parse into a
+    /// complete `BuiltInCall`, so the arguments are counted on the tokens.
+    fn check_arity(&self, keyword: &SyntaxToken) -> Option<Diagnostic> {
+        let accepted = arity(keyword.kind())?;
+        let (argument_count, end) = count_arguments(keyword)?;
+        if accepted.contains(&argument_count) {
+            return None;
+        }
+        let expected = if accepted.start() == accepted.end() {
+            format!("exactly {}", accepted.start())
+        } else {
+            format!("{} to {}", accepted.start(), accepted.end())
+        };
+        Some(Diagnostic {
+            range: Range::from_byte_offset_range(
+                TextRange::new(keyword.text_range().start(), end),
+                &self.document.text,
+            )?,
+            severity: DiagnosticSeverity::Error,
+            code: Some((*CODE).clone()),
+            source: None,
+            message: format!(
+                "`{}` expects {expected} argument{}, found {argument_count}",
+                keyword.text().to_uppercase(),
+                if *accepted.end() == 1 { "" } else { "s" }
+            ),
+            data: None,
+        })
+    }
+
+    fn check_function_call(&self, node: &SyntaxNode) -> Option<Diagnostic> {
+        let iri = node.first_child().and_then(Iri::cast)?;
+        let arg_list = node
+            .children()
+            .find(|child| child.kind() == SyntaxKind::ArgList)?;
+        self.xsd_local_name(&iri)?;
+        let argument_count = arg_list
+            .children()
+            .filter(|child| child.kind() == SyntaxKind::Expression)
+            .count();
+        (argument_count != 1)
+            .then(|| {
+                self.diagnostic(
+                    node,
+                    DiagnosticSeverity::Error,
+                    format!(
+                        "`{}` expects exactly 1 argument, found {argument_count}",
+                        iri.text()
+                    ),
+                )
+            })
+            .flatten()
+    }
+
+    fn infer(&self, node: &SyntaxNode) -> Type {
+        let pass_through = || {
+            node.first_child()
+                .map_or(Type::Unknown, |child| self.infer(&child))
+        };
+        match node.kind() {
+            SyntaxKind::Expression
+            | SyntaxKind::ValueLogical
+            | SyntaxKind::NumericExpression
+            | SyntaxKind::PrimaryExpression
+            | SyntaxKind::BrackettedExpression
+            | SyntaxKind::Constraint => pass_through(),
+            SyntaxKind::ConditionalOrExpression | SyntaxKind::ConditionalAndExpression => {
+                if node.children().count() > 1 {
+                    Type::Boolean
+                } else {
+                    pass_through()
+                }
+            }
+            SyntaxKind::AdditiveExpression | SyntaxKind::MultiplicativeExpression => {
+                if node.children().count() > 1 {
+                    Type::Numeric
+                } else {
+                    pass_through()
+                }
+            }
+            SyntaxKind::RelationalExpression => {
+                if node.children_with_tokens().any(|child| {
+                    child
+                        .as_token()
+                        .is_some_and(|token| !token.kind().is_trivia())
+                }) {
+                    Type::Boolean
+                } else {
+                    pass_through()
+                }
+            }
+            SyntaxKind::UnaryExpression => match first_token_kind(node) {
+                Some(SyntaxKind::ExclamationMark) => Type::Boolean,
+                Some(SyntaxKind::Plus | SyntaxKind::Minus) => Type::Numeric,
+                _ => pass_through(),
+            },
+            SyntaxKind::NumericLiteral
+            | SyntaxKind::NumericLiteralPositive
+            | SyntaxKind::NumericLiteralNegative => Type::Numeric,
+            SyntaxKind::BooleanLiteral => Type::Boolean,
+            SyntaxKind::RDFLiteral => match node.children().find_map(Iri::cast) {
+                Some(datatype) => self
+                    .xsd_local_name(&datatype)
+                    .map_or(Type::Literal, |local_name| xsd_type(&local_name)),
+                None => Type::String,
+            },
+            SyntaxKind::iriOrFunction | SyntaxKind::FunctionCall => {
+                let is_call = node
+                    .children()
+                    .any(|child| child.kind() == SyntaxKind::ArgList);
+                match node.first_child().and_then(Iri::cast) {
+                    Some(_) if !is_call => Type::Iri,
+                    Some(iri) => self
+                        .xsd_local_name(&iri)
+                        .map_or(Type::Unknown, |local_name| xsd_type(&local_name)),
+                    None => Type::Unknown,
+                }
+            }
+            SyntaxKind::iri => Type::Iri,
+            SyntaxKind::BuiltInCall => built_in_call_body(node)
+                .first_token()
+                .and_then(|keyword| signature(keyword.kind()))
+                .map_or(Type::Unknown, |(_, returns)| returns),
+            _ => Type::Unknown,
+        }
+    }
+
+    /// Returns the local name of `iri` if it lies in the XSD namespace.
+    fn xsd_local_name(&self, iri: &Iri) -> Option<String> {
+        let full_iri = match iri.prefixed_name() {
+            Some(prefixed_name) => format!(
+                "{}{}",
+                self.prefixes.get(&prefixed_name.prefix())?,
+                prefixed_name.name()
+            ),
+            None => iri.raw_iri()?,
+        };
+        full_iri
+            .strip_prefix(XSD_NAMESPACE)
+            .map(|local_name| local_name.to_string())
+    }
+
+    fn diagnostic(
+        &self,
+        node: &SyntaxNode,
+        severity: DiagnosticSeverity,
+        message: String,
+    ) -> Option<Diagnostic> {
+        Some(Diagnostic {
+            range: Range::from_byte_offset_range(node.text_range(), &self.document.text)?,
+            severity,
+            code: Some((*CODE).clone()),
+            source: None,
+            message,
+            data: None,
+        })
+    }
+}
+
+fn first_token_kind(node: &SyntaxNode) -> Option<SyntaxKind> {
+    node.first_child_or_token()?
+        .as_token()
+        .map(|token| token.kind())
+}
+
+/// Some built-in calls wrap their tokens in a dedicated child node
+/// (aggregates, `SUBSTR`, `REPLACE`, `REGEX`, `EXISTS`).
+fn built_in_call_body(node: &SyntaxNode) -> SyntaxNode {
+    node.first_child()
+        .filter(|child| {
+            matches!(
+                child.kind(),
+                SyntaxKind::Aggregate
+                    | SyntaxKind::SubstringExpression
+                    | SyntaxKind::StrReplaceExpression
+                    | SyntaxKind::RegexExpression
+                    | SyntaxKind::ExistsFunc
+                    | SyntaxKind::NotExistsFunc
+            )
+        })
+        .unwrap_or_else(|| node.clone())
+}
+
+fn call_arguments(call: &SyntaxNode) -> Vec<SyntaxNode> {
+    call.children()
+        .flat_map(|child| match child.kind() {
+            SyntaxKind::Expression => vec![child],
+            SyntaxKind::ExpressionList => child
+                .children()
+                .filter(|expression| expression.kind() == SyntaxKind::Expression)
+                .collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Counts the arguments of the call that starts with `keyword`, by the commas
+/// between its parentheses. Returns the count and the end of the call, or
+/// `None` if the call is not closed.
+fn count_arguments(keyword: &SyntaxToken) -> Option<(usize, TextSize)> {
+    let mut tokens = iter::successors(keyword.next_token(), |token| token.next_token())
+        .filter(|token| !token.kind().is_trivia());
+    let open = tokens.next()?;
+    match open.kind() {
+        SyntaxKind::NIL => return Some((0, open.text_range().end())),
+        SyntaxKind::LParen => {}
+        _ => return None,
+    }
+    let (mut depth, mut commas, mut empty) = (1, 0, true);
+    for token in tokens {
+        match token.kind() {
+            SyntaxKind::LParen => depth += 1,
+            SyntaxKind::RParen if depth == 1 => {
+                let count = if empty { 0 } else { commas + 1 };
+                return Some((count, token.text_range().end()));
+            }
+            SyntaxKind::RParen => depth -= 1,
+            SyntaxKind::Comma if depth == 1 => commas += 1,
+            _ => {}
+        }
+        empty = false;
+    }
+    None
+}
+
+fn xsd_type(local_name: &str) -> Type {
+    match local_name {
+        "string" => Type::String,
+        "boolean" => Type::Boolean,
+        "dateTime" => Type::DateTime,
+        "integer" | "decimal" | "float" | "double" | "int" | "long" | "short" | "byte"
+        | "nonNegativeInteger" | "positiveInteger" | "negativeInteger" | "nonPositiveInteger"
+        | "unsignedInt" | "unsignedLong" | "unsignedShort" | "unsignedByte" => Type::Numeric,
+        _ => Type::Literal,
+    }
+}
+
+/// The number of arguments the built-in functions accept, keyed by their keyword.
+/// Aggregates and `EXISTS` take no expression list and are not checked.
+fn arity(keyword: SyntaxKind) -> Option<RangeInclusive<usize>> {
+    match keyword {
+        SyntaxKind::CONCAT | SyntaxKind::COALESCE => None,
+        SyntaxKind::REGEX | SyntaxKind::SUBSTR => Some(2..=3),
+        SyntaxKind::REPLACE => Some(3..=4),
+        SyntaxKind::BOUND => Some(1..=1),
+        SyntaxKind::EXISTS
+        | SyntaxKind::NOT
+        | SyntaxKind::COUNT
+        | SyntaxKind::SUM
+        | SyntaxKind::AVG
+        | SyntaxKind::MIN
+        | SyntaxKind::MAX
+        | SyntaxKind::SAMPLE
+        | SyntaxKind::GROUP_CONCAT => None,
+        _ => signature(keyword).map(|(params, _)| params.len()..=params.len()),
+    }
+}
+
+/// Parameter and return types of the built-in functions, keyed by their keyword.
+fn signature(keyword: SyntaxKind) -> Option<(&'static [Type], Type)> {
+    use Type::*;
+    Some(match keyword {
+        SyntaxKind::STR => (&[Unknown], String),
+        SyntaxKind::LANG => (&[Literal], String),
+        SyntaxKind::LANGMATCHES => (&[String, String], Boolean),
+        SyntaxKind::DATATYPE => (&[Literal], Iri),
+        SyntaxKind::BOUND => (&[], Boolean),
+        SyntaxKind::IRI | SyntaxKind::URI => (&[Unknown], Iri),
+        SyntaxKind::RAND => (&[], Numeric),
+        SyntaxKind::ABS | SyntaxKind::CEIL | SyntaxKind::FLOOR | SyntaxKind::ROUND => {
+            (&[Numeric], Numeric)
+        }
+        SyntaxKind::CONCAT => (&[String], String),
+        SyntaxKind::STRLEN => (&[String], Numeric),
+        SyntaxKind::UCASE
+        | SyntaxKind::LCASE
+        | SyntaxKind::ENCODE_FOR_URI
+        | SyntaxKind::MD5
+        | SyntaxKind::SHA1
+        | SyntaxKind::SHA256
+        | SyntaxKind::SHA384
+        | SyntaxKind::SHA512 => (&[String], String),
+        SyntaxKind::CONTAINS | SyntaxKind::STRSTARTS | SyntaxKind::STRENDS => {
+            (&[String, String], Boolean)
+        }
+        SyntaxKind::STRBEFORE | SyntaxKind::STRAFTER | SyntaxKind::STRLANG => {
+            (&[String, String], String)
+        }
+        SyntaxKind::YEAR
+        | SyntaxKind::MONTH
+        | SyntaxKind::DAY
+        | SyntaxKind::HOURS
+        | SyntaxKind::MINUTES
+        | SyntaxKind::SECONDS => (&[DateTime], Numeric),
+        SyntaxKind::TIMEZONE => (&[DateTime], Literal),
+        SyntaxKind::TZ => (&[DateTime], String),
+        SyntaxKind::NOW => (&[], DateTime),
+        SyntaxKind::UUID => (&[], Iri),
+        SyntaxKind::STRUUID => (&[], String),
+        SyntaxKind::COALESCE => (&[Unknown], Unknown),
+        SyntaxKind::IF => (&[Boolean, Unknown, Unknown], Unknown),
+        SyntaxKind::STRDT => (&[String, Iri], Literal),
+        SyntaxKind::sameTerm => (&[Unknown, Unknown], Boolean),
+        SyntaxKind::isIRI
+        | SyntaxKind::isURI
+        | SyntaxKind::isBLANK
+        | SyntaxKind::isLITERAL
+        | SyntaxKind::isNUMERIC
+        | SyntaxKind::isTRIPLE
+        | SyntaxKind::hasLANG
+        | SyntaxKind::hasLANGDIR => (&[Unknown], Boolean),
+        SyntaxKind::LANGDIR => (&[Literal], String),
+        SyntaxKind::REGEX => (&[String, String, String], Boolean),
+        SyntaxKind::SUBSTR => (&[String, Numeric, Numeric], String),
+        SyntaxKind::REPLACE => (&[String, String, String, String], String),
+        SyntaxKind::EXISTS | SyntaxKind::NOT => (&[], Boolean),
+        SyntaxKind::COUNT => (&[Unknown], Numeric),
+        SyntaxKind::SUM | SyntaxKind::AVG => (&[Numeric], Numeric),
+        SyntaxKind::MIN | SyntaxKind::MAX | SyntaxKind::SAMPLE => (&[Unknown], Unknown),
+        SyntaxKind::GROUP_CONCAT => (&[Unknown], String),
+        _ => return None,
+    })
+}
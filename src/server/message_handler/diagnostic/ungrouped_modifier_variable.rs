@@ -0,0 +1,122 @@
+//! Flags variables in `HAVING` and `ORDER BY` that are illegal in a grouped
+//! query.
+//!
+//! Once a query is grouped, either by `GROUP BY` or implicitly by using an
+//! aggregate, its `HAVING` and `ORDER BY` conditions may only refer to grouped
+//! variables, aliases of the projection or aggregated variables. Every
+//! `SELECT` level, including sub-selects, is checked separately.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    SyntaxNode,
+    ast::{AstNode, GroupClause, QueryUnit, SelectQuery, Var},
+    syntax_kind::SyntaxKind,
+};
+use std::{collections::HashSet, sync::LazyLock};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("ungrouped-modifier-var".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    Some(
+        query_unit
+            .syntax()
+            .descendants()
+            .filter_map(SelectQuery::cast)
+            .flat_map(|select_query| ungrouped_variables(&select_query))
+            .map(|(clause, var)| Diagnostic {
+                code: Some((*CODE).clone()),
+                range: Range::from_byte_offset_range(var.syntax().text_range(), &document.text)
+                    .unwrap(),
+                severity: DiagnosticSeverity::Error,
+                message: format!(
+                    "{} is used in {clause}, but is not aggregated or part of the Group by Clause",
+                    var.text()
+                ),
+                data: None,
+                source: None,
+            })
+            .collect(),
+    )
+}
+
+fn ungrouped_variables(select_query: &SelectQuery) -> Vec<(&'static str, Var)> {
+    let Some(solution_modifier) = select_query
+        .syntax()
+        .children()
+        .find(|child| child.kind() == SyntaxKind::SolutionModifier)
+    else {
+        return vec![];
+    };
+    let group_clause = solution_modifier.children().find_map(GroupClause::cast);
+    let select_clause = select_query.select_clause();
+    let is_grouped = group_clause.is_some()
+        || select_clause
+            .iter()
+            .map(|select_clause| select_clause.syntax())
+            .chain(std::iter::once(&solution_modifier))
+            .any(|node| {
+                node.descendants()
+                    .any(|node| node.kind() == SyntaxKind::Aggregate)
+            });
+    if !is_grouped {
+        return vec![];
+    }
+    let allowed: HashSet<String> = group_clause
+        .map(|group_clause| group_clause.visible_variables())
+        .unwrap_or_default()
+        .into_iter()
+        .chain(
+            select_clause
+                .map(|select_clause| select_clause.assignments())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|assignment| assignment.variable),
+        )
+        .map(|var| var.text())
+        .collect();
+    solution_modifier
+        .children()
+        .filter_map(|clause| match clause.kind() {
+            SyntaxKind::HavingClause => Some(("HAVING", clause)),
+            SyntaxKind::OrderClause => Some(("ORDER BY", clause)),
+            _ => None,
+        })
+        .flat_map(|(name, clause)| {
+            unaggregated_variables(&clause)
+                .into_iter()
+                .map(move |var| (name, var))
+        })
+        .filter(|(_, var)| !allowed.contains(&var.text()))
+        .collect()
+}
+
+/// Variables of `node` that are not inside an aggregate.
+/// Group graph patterns (`EXISTS { ... }`) are not entered.
+fn unaggregated_variables(node: &SyntaxNode) -> Vec<Var> {
+    let mut variables = vec![];
+    let mut stack = vec![node.clone()];
+    while let Some(node) = stack.pop() {
+        if !matches!(
+            node.kind(),
+            SyntaxKind::Aggregate | SyntaxKind::GroupGraphPattern
+        ) {
+            stack.extend(node.children());
+        }
+        if let Some(var) = Var::cast(node) {
+            variables.push(var);
+        }
+    }
+    variables.sort_by_key(|var| var.syntax().text_range().start());
+    variables
+}
@@ -11,9 +11,10 @@ use crate::server::{
         diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
         textdocument::{Range, TextDocumentItem},
     },
+    message_handler::common::used_prefixes,
 };
-use ll_sparql_parser::ast::{AstNode, PrefixedName, QueryUnit};
-use std::{collections::HashSet, sync::LazyLock};
+use ll_sparql_parser::ast::{AstNode, QueryUnit};
+use std::sync::LazyLock;
 
 pub static CODE: LazyLock<DiagnosticCode> =
     LazyLock::new(|| DiagnosticCode::String("unused-prefix-declaration".to_string()));
@@ -24,15 +25,7 @@ pub(super) fn diagnostics(
     _server: &Server,
 ) -> Option<Vec<Diagnostic>> {
     let prefix_declarations = query_unit.prologue()?.prefix_declarations();
-    let used_prefixes: HashSet<String> =
-        query_unit.strip_prologue().map_or(HashSet::new(), |query| {
-            HashSet::from_iter(
-                query
-                    .descendants()
-                    .flat_map(&PrefixedName::cast)
-                    .map(|node| node.prefix()),
-            )
-        });
+    let used_prefixes = used_prefixes(query_unit.syntax());
     Some(
         prefix_declarations
             .into_iter()
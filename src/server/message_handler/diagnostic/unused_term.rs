@@ -0,0 +1,347 @@
+//! Flags constant predicates and classes that never occur in the dataset of the
+//! backend, e.g. a typo like `wdt:P3l`.
+//!
+//! In contrast to the other providers this one asks the backend: all terms of a
+//! document are checked with one `usageCheck` query per backend. The check runs
+//! in the background, the diagnostics only report the answers that are already
+//! kept in [`ServerState::usage_memory`](crate::server::state::ServerState).
+//! Once new answers arrive the client is asked to pull the diagnostics again.
+//! New lookups are rate limited by `diagnostics.usageCheckIntervalMs`.
+//! The check is opt-in via `diagnostics.usageCheck`.
+
+use crate::{
+    server::{
+        Server,
+        configuration::{CompletionTemplate, RequestMethod},
+        lsp::{
+            DiagnosticRefreshRequest,
+            diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+            textdocument::Range,
+        },
+        message_handler::misc::resolve_backend_at_token,
+        sparql_operations::execute_query,
+        state::get_timestamp_ms,
+        usage_memory::TermKind,
+    },
+    sparql::results::SparqlResultsBody,
+};
+use futures::lock::Mutex;
+use ll_sparql_parser::{
+    ast::{AstNode, Iri, PropertyListPath, QueryUnit, ServiceGraphPattern},
+    syntax_kind::SyntaxKind,
+};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    rc::Rc,
+    sync::LazyLock,
+};
+use tera::Context;
+use text_size::TextRange;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::task::spawn_local;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_futures::spawn_local;
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("unused-term".to_string()));
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+#[derive(Debug)]
+struct Term {
+    kind: TermKind,
+    /// The expanded IRI, without angle brackets.
+    iri: String,
+    text: String,
+    range: TextRange,
+    backend: String,
+}
+
+/// A `usageCheck` query of one backend, with the terms it checks.
+struct UsageCheck {
+    backend: String,
+    url: String,
+    method: RequestMethod,
+    timeout_ms: u32,
+    query: String,
+    terms: Vec<(TermKind, String)>,
+}
+
+pub(super) fn diagnostics(
+    server_rc: Rc<Mutex<Server>>,
+    server: &mut Server,
+    document_uri: &str,
+) -> Vec<Diagnostic> {
+    if !server.settings.diagnostics.usage_check {
+        return vec![];
+    }
+    let Ok(text) = server
+        .state
+        .get_document(document_uri)
+        .map(|document| document.text.clone())
+    else {
+        return vec![];
+    };
+    let Some(query_unit) = server
+        .state
+        .get_cached_parse_tree(document_uri)
+        .ok()
+        .and_then(|parse_result| QueryUnit::cast(parse_result.tree))
+    else {
+        return vec![];
+    };
+    let terms = collect_terms(server, &query_unit);
+    let now = get_timestamp_ms();
+    let answers: Vec<Option<bool>> = terms
+        .iter()
+        .map(|term| {
+            server
+                .state
+                .usage_memory
+                .get(&term.backend, term.kind, &term.iri, now)
+        })
+        .collect();
+
+    let mut unchecked: HashMap<&str, Vec<&Term>> = HashMap::new();
+    for (term, _) in terms
+        .iter()
+        .zip(&answers)
+        .filter(|(_, answer)| answer.is_none())
+        .filter(|(term, _)| {
+            server
+                .state
+                .get_backend(&term.backend)
+                .is_some_and(|backend| backend.has_endpoint())
+        })
+    {
+        unchecked.entry(&term.backend).or_default().push(term);
+    }
+    let interval_ms = server.settings.diagnostics.usage_check_interval_ms;
+    if !unchecked.is_empty() && server.state.start_usage_check(interval_ms) {
+        let checks: Vec<UsageCheck> = unchecked
+            .into_iter()
+            .filter_map(|(backend_name, terms)| {
+                Some(UsageCheck {
+                    query: render_query(server, backend_name, &terms)?,
+                    url: server.state.get_backend(backend_name)?.url.clone(),
+                    method: server.state.get_backend_request_method(backend_name),
+                    timeout_ms: server.settings.completion.timeout_ms,
+                    backend: backend_name.to_string(),
+                    terms: terms
+                        .iter()
+                        .map(|term| (term.kind, term.iri.clone()))
+                        .collect(),
+                })
+            })
+            .collect();
+        if !checks.is_empty() {
+            spawn_local(check_usage(server_rc, checks));
+        }
+    }
+
+    terms
+        .iter()
+        .zip(answers)
+        .filter(|(_, answer)| *answer == Some(false))
+        .map(|(term, _)| Diagnostic {
+            code: Some((*CODE).clone()),
+            range: Range::from_byte_offset_range(term.range, &text).unwrap(),
+            severity: DiagnosticSeverity::Warning,
+            message: match term.kind {
+                TermKind::Predicate => {
+                    format!("{} is a predicate never used in this dataset", term.text)
+                }
+                TermKind::Class => {
+                    format!("{} is a class without instances in this dataset", term.text)
+                }
+            },
+            data: None,
+            source: None,
+        })
+        .collect()
+}
+
+/// Runs the `usageCheck` queries and keeps their answers.
+/// Asks the client to pull the diagnostics again if any query was answered.
+async fn check_usage(server_rc: Rc<Mutex<Server>>, checks: Vec<UsageCheck>) {
+    let mut answered = false;
+    for check in checks {
+        let result = execute_query(
+            server_rc.clone(),
+            check.url,
+            check.query,
+            None,
+            None,
+            Some(check.timeout_ms),
+            check.method,
+            None,
+            0,
+            false,
+        )
+        .await;
+        let used_iris: HashSet<String> = match result {
+            Ok(Some(result)) => match result.body {
+                SparqlResultsBody::Results { bindings } => bindings
+                    .iter()
+                    .filter_map(|binding| binding.get("qls_entity"))
+                    .map(|rdf_term| rdf_term.value().to_string())
+                    .collect(),
+                _ => {
+                    tracing::warn!("The result of the usage check query did not contain bindings");
+                    continue;
+                }
+            },
+            Ok(None) => continue,
+            Err(err) => {
+                tracing::warn!("Usage check query failed: {:?}", err);
+                continue;
+            }
+        };
+        let mut server = server_rc.lock().await;
+        let now = get_timestamp_ms();
+        for (kind, iri) in check.terms {
+            let used = used_iris.contains(&iri);
+            server
+                .state
+                .usage_memory
+                .insert(&check.backend, kind, &iri, used, now);
+        }
+        answered = true;
+    }
+    let mut server = server_rc.lock().await;
+    if answered && client_supports_diagnostic_refresh(&server) {
+        let request_id = server.bump_request_id();
+        if let Err(err) = server.send_message(DiagnosticRefreshRequest::new(request_id)) {
+            tracing::error!("Could not request a diagnostic refresh: {:?}", err);
+        }
+    }
+}
+
+fn client_supports_diagnostic_refresh(server: &Server) -> bool {
+    server
+        .client_capabilities
+        .as_ref()
+        .and_then(|client_capabilities| client_capabilities.workspace.as_ref())
+        .and_then(|workspace_capabilities| workspace_capabilities.diagnostics.as_ref())
+        .and_then(|diagnostics| diagnostics.refresh_support)
+        .unwrap_or(false)
+}
+
+/// Renders the `usageCheck` query of `backend_name` for the given terms.
+/// Returns `None` if the backend has no such query.
+fn render_query(server: &Server, backend_name: &str, terms: &[&Term]) -> Option<String> {
+    server
+        .state
+        .get_backend(backend_name)?
+        .queries
+        .contains_key(&CompletionTemplate::UsageCheck)
+        .then_some(())?;
+    let iris_of_kind = |kind: TermKind| {
+        terms
+            .iter()
+            .filter(|term| term.kind == kind)
+            .map(|term| format!("<{}>", term.iri))
+            .collect::<BTreeSet<_>>()
+    };
+    let mut context = Context::new();
+    context.insert("predicates", &iris_of_kind(TermKind::Predicate));
+    context.insert("classes", &iris_of_kind(TermKind::Class));
+    context.insert("prefixes", &Vec::<(String, String)>::new());
+    server
+        .tools
+        .tera
+        .render(
+            &format!("{}-{}", backend_name, CompletionTemplate::UsageCheck),
+            &context,
+        )
+        .inspect_err(|err| tracing::error!("{}", err))
+        .ok()
+}
+
+/// Collects the constant predicates and the classes (objects of `a` or
+/// `rdf:type`) of the where clauses, together with their backend.
+fn collect_terms(server: &Server, query_unit: &QueryUnit) -> Vec<Term> {
+    let prefixes: HashMap<String, String> = query_unit
+        .prologue()
+        .map(|prologue| {
+            prologue
+                .prefix_declarations()
+                .iter()
+                .filter_map(|declaration| {
+                    Some((declaration.prefix()?, declaration.raw_uri_prefix()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let expand = |iri: &Iri| {
+        iri.raw_iri().or_else(|| {
+            let prefixed_name = iri.prefixed_name()?;
+            Some(format!(
+                "{}{}",
+                prefixes.get(&prefixed_name.prefix())?,
+                prefixed_name.name()
+            ))
+        })
+    };
+
+    let default_backend = server
+        .state
+        .get_default_backend()
+        .map(|backend| &backend.name);
+    let to_term = |kind: TermKind, iri: Iri| {
+        let token = iri.syntax().first_token()?;
+        let backend = resolve_backend_at_token(server, query_unit, &token)?;
+        // NOTE: a SERVICE with an unknown endpoint falls back to the default
+        // backend, which would report terms of another dataset.
+        if iri
+            .syntax()
+            .ancestors()
+            .any(|ancestor| ServiceGraphPattern::can_cast(ancestor.kind()))
+            && Some(&backend.name) == default_backend
+        {
+            return None;
+        }
+        Some(Term {
+            kind,
+            iri: expand(&iri)?,
+            text: iri.text(),
+            range: iri.syntax().text_range(),
+            backend: backend.name,
+        })
+    };
+
+    let predicates = query_unit
+        .syntax()
+        .descendants()
+        .filter(|node| {
+            node.parent()
+                .is_some_and(|parent| parent.kind() == SyntaxKind::PathPrimary)
+        })
+        .filter_map(Iri::cast)
+        .filter_map(|iri| to_term(TermKind::Predicate, iri));
+    let classes = query_unit
+        .syntax()
+        .descendants()
+        .filter_map(PropertyListPath::cast)
+        .flat_map(|property_list| property_list.properties())
+        .filter(|property| {
+            let verb = property.verb.syntax();
+            verb.text() == "a"
+                || verb.descendants().find_map(Iri::cast).is_some_and(|iri| {
+                    verb.text().to_string() == iri.text()
+                        && expand(&iri).as_deref() == Some(RDF_TYPE)
+                })
+        })
+        .filter_map(|property| property.object)
+        .flat_map(|object_list| {
+            // NOTE: ObjectPath > GraphNodePath > VarOrTerm > iri
+            object_list
+                .syntax()
+                .children()
+                .filter_map(|object| object.first_child()?.first_child()?.first_child())
+                .filter_map(Iri::cast)
+                .collect::<Vec<_>>()
+        })
+        .filter_map(|iri| to_term(TermKind::Class, iri));
+    predicates.chain(classes).collect()
+}
@@ -0,0 +1,80 @@
+//! Flags `VALUES` rows whose number of values differs from the number of
+//! declared variables.
+//!
+//! Applies to inline `VALUES` blocks and to the trailing `VALUES` clause of a
+//! query. Each offending row, including its parentheses, is reported as an
+//! error.
+
+use crate::server::{
+    Server,
+    lsp::{
+        diagnostic::{Diagnostic, DiagnosticCode, DiagnosticSeverity},
+        textdocument::{Range, TextDocumentItem},
+    },
+};
+use ll_sparql_parser::{
+    ast::{AstNode, QueryUnit},
+    syntax_kind::SyntaxKind,
+};
+use std::sync::LazyLock;
+use text_size::{TextRange, TextSize};
+
+pub static CODE: LazyLock<DiagnosticCode> =
+    LazyLock::new(|| DiagnosticCode::String("values-row-length".to_string()));
+
+pub(super) fn diagnostics(
+    document: &TextDocumentItem,
+    query_unit: &QueryUnit,
+    _server: &Server,
+) -> Option<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    for inline_data in query_unit
+        .syntax()
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::InlineDataFull)
+    {
+        let mut variable_count = 0;
+        let mut in_body = false;
+        let mut row: Option<(TextSize, usize)> = None;
+        let mut rows: Vec<(TextRange, usize)> = Vec::new();
+        for child in inline_data.children_with_tokens() {
+            match child.kind() {
+                SyntaxKind::LCurly => in_body = true,
+                SyntaxKind::Var if !in_body => variable_count += 1,
+                SyntaxKind::NIL if in_body => rows.push((child.text_range(), 0)),
+                SyntaxKind::LParen if in_body => row = Some((child.text_range().start(), 0)),
+                SyntaxKind::DataBlockValue => {
+                    if let Some((_, count)) = row.as_mut() {
+                        *count += 1;
+                    }
+                }
+                SyntaxKind::RParen if in_body => {
+                    if let Some((start, count)) = row.take() {
+                        rows.push((TextRange::new(start, child.text_range().end()), count));
+                    }
+                }
+                _ => {}
+            }
+        }
+        diagnostics.extend(
+            rows.into_iter()
+                .filter(|(_, value_count)| *value_count != variable_count)
+                .map(|(range, value_count)| Diagnostic {
+                    code: Some((*CODE).clone()),
+                    range: Range::from_byte_offset_range(range, &document.text).unwrap(),
+                    severity: DiagnosticSeverity::Error,
+                    message: format!(
+                        "This row has {} value{}, but {} variable{} {} declared",
+                        value_count,
+                        if value_count == 1 { "" } else { "s" },
+                        variable_count,
+                        if variable_count == 1 { "" } else { "s" },
+                        if variable_count == 1 { "is" } else { "are" },
+                    ),
+                    data: None,
+                    source: None,
+                }),
+        );
+    }
+    Some(diagnostics)
+}
@@ -134,10 +134,7 @@ async fn handle_normal_query(
                 for binding in bindings.iter_mut() {
                     for (_, rdf_term) in binding.iter_mut() {
                         if let RDFTerm::Uri { value, curie } = rdf_term {
-                            *curie = server
-                                .state
-                                .get_default_converter()
-                                .and_then(|converer| converer.compress(value).ok());
+                            *curie = server.state.get_default_converter().compress(value).ok();
                         }
                     }
                 }
@@ -108,7 +108,7 @@ fn merge_empty_lines(
             .cmp(&b.range.start())
             .then_with(|| a.range.end().cmp(&b.range.end()))
     });
-    simplified_empty_lines.sort_by(|a, b| a.position.cmp(&b.position));
+    simplified_empty_lines.sort_by_key(|a| a.position);
     let mut empty_lines_itter = simplified_empty_lines.into_iter().peekable();
     for idx in 0..simplified_edits.len() {
         if let Some(next) = empty_lines_itter.peek()
@@ -1180,7 +1180,7 @@ fn transform_comments(
     if comments.is_empty() {
         return vec![];
     }
-    comments.sort_by(|a, b| a.position.cmp(&b.position));
+    comments.sort_by_key(|a| a.position);
     let mut position = Position::new(0, 0);
     let mut byte_offset = 0;
     let mut comments = comments.into_iter();
@@ -8,7 +8,10 @@ use crate::server::{
         errors::LSPError,
         textdocument::{Range, TextDocumentItem, TextEdit},
     },
-    message_handler::code_action::same_subject::contract_all_triple_groups,
+    message_handler::code_action::{
+        organize_prefixes::organize_prefixes, same_subject::contract_all_triple_groups,
+    },
+    prefix_catalog::catalog_converter,
 };
 use core::format_document as format_document_core;
 use futures::lock::Mutex;
@@ -109,6 +112,35 @@ pub fn format_raw(text: String) -> Result<String, String> {
     Ok(document.text)
 }
 
+/// Organize the prefix declarations of SPARQL text, see the "Organize prefixes" code action.
+///
+/// Without a backend, missing prefixes are declared from the bundled prefix catalog.
+pub fn organize_prefixes_raw(text: String) -> String {
+    let settings = Settings::new();
+    let mut document = TextDocumentItem::new("tmp", &text);
+    let (root, _) = parse(&text);
+    let catalog = catalog_converter();
+    if let Some(edit) = organize_prefixes(
+        &document,
+        root,
+        |prefixed_name| {
+            catalog
+                .find_by_prefix(&prefixed_name.prefix())
+                .ok()
+                .map(|record| record.uri_prefix.clone())
+        },
+        settings
+            .prefixes
+            .as_ref()
+            .and_then(|prefixes| prefixes.order.as_deref())
+            .unwrap_or_default(),
+        settings.format.align_prefixes,
+    ) {
+        document.apply_text_edits(vec![edit]);
+    }
+    document.text
+}
+
 /// Format SPARQL text with custom settings.
 ///
 /// This function is intended for testing and allows specifying custom format settings.
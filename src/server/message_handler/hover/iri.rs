@@ -3,8 +3,9 @@ use std::rc::Rc;
 use crate::{
     server::{
         Server,
+        extension_catalog::find_extension,
         lsp::errors::{ErrorCode, LSPError},
-        message_handler::misc::resolve_backend_at_token,
+        message_handler::{common::expand_iri, misc::resolve_backend_at_token},
         sparql_operations::execute_query,
     },
     sparql::results::SparqlResultsBody,
@@ -37,7 +38,21 @@ pub(super) async fn hover(
             ErrorCode::InternalError,
             "Could not determine backend for hover location",
         ))?;
-    if let Some(label) = server.state.label_memory.get(&iri.text()) {
+    if let Some(extension) = backend.engine.as_ref().and_then(|engine| {
+        expand_iri(&iri, &query_unit)
+            .and_then(|full_iri| find_extension(engine, &full_iri))
+            .map(|extension| extension.markdown(engine))
+    }) {
+        Ok(Some(extension))
+    } else if let Some(index) = server.state.get_local_index(&backend.name) {
+        Ok(expand_iri(&iri, &query_unit).and_then(|full_iri| {
+            index.markdown(&full_iri, |iri| {
+                server
+                    .shorten_uri(iri, Some(&backend.name))
+                    .map_or_else(|| format!("<{iri}>"), |(_, _, curie)| curie)
+            })
+        }))
+    } else if let Some(label) = server.state.label_memory.get(&iri.text()) {
         Ok(Some(label.clone()))
     } else {
         let converter = server
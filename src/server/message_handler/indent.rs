@@ -58,10 +58,8 @@ pub(crate) fn brace_nesting_depth(token: &SyntaxToken) -> usize {
             // NOTE: ConstructQuery's short form 'WHERE' '{' TriplesTemplate? '}' embeds
             // braces directly without a named wrapper node. Detected by the child on the
             // path being TriplesTemplate (vs. ConstructTemplate or WhereClause).
-            SyntaxKind::ConstructQuery => {
-                if prev_kind == Some(SyntaxKind::TriplesTemplate) {
-                    depth += 1;
-                }
+            SyntaxKind::ConstructQuery if prev_kind == Some(SyntaxKind::TriplesTemplate) => {
+                depth += 1
             }
 
             _ => {}
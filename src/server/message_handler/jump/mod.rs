@@ -175,6 +175,6 @@ fn relevant_positions(
             res.push((offset, Some("\n"), None));
         }
     }
-    res.sort_by(|a, b| a.0.cmp(&b.0));
+    res.sort_by_key(|a| a.0);
     res
 }
@@ -94,6 +94,18 @@ pub(super) async fn handle_initialize_request(
                         .state
                         .load_prefix_map(backend_config.name.clone(), &backend_config.prefix_map)?;
                     server.load_templates(&backend_config.name, backend_config.queries.clone())?;
+                    // NOTE: unreadable files only cost this backend its local index,
+                    // `load_local_index` already logs why.
+                    if server
+                        .state
+                        .load_local_index(&backend_config.name, &backend_config.files)
+                        .is_err()
+                    {
+                        tracing::warn!(
+                            "Starting backend \"{}\" without its local files",
+                            backend_config.name
+                        );
+                    }
                     server.state.add_backend(backend_config.clone());
                     if backend_config.default {
                         server
@@ -65,7 +65,7 @@ use backend::{
     handle_update_backend_default_notification,
 };
 use code_action::handle_codeaction_request;
-use completion::handle_completion_request;
+use completion::{handle_completion_request, handle_completion_resolve_request};
 use diagnostic::handle_diagnostic_request;
 use futures::lock::Mutex;
 use hover::handle_hover_request;
@@ -152,6 +152,7 @@ pub(super) async fn dispatch(
         "textDocument/codeAction" => call!(handle_codeaction_request),
         "textDocument/hover" => call_async!(handle_hover_request),
         "textDocument/completion" => call_async!(handle_completion_request),
+        "completionItem/resolve" => call_async!(handle_completion_resolve_request),
         "textDocument/foldingRange" => call!(handle_folding_range_request),
         "textDocument/semanticTokens/full" => call!(handle_semantic_tokens_full_request),
         "textDocument/semanticTokens/range" => call!(handle_semantic_tokens_range_request),
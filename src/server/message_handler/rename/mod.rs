@@ -54,7 +54,7 @@ pub(super) async fn handle_rename_request(
     let new_name = raw_new_name
         .strip_prefix(['?', '$'])
         .unwrap_or(raw_new_name);
-    if !is_valid_variable_name(&new_name) {
+    if !is_valid_variable_name(new_name) {
         return Err(LSPError::new(
             ErrorCode::InvalidParams,
             &format!("\"{new_name}\" is not a valid variable name."),
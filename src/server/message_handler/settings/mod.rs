@@ -67,6 +67,18 @@ fn merge_settings(settings: &Settings, patch: Value) -> Result<Settings, LSPErro
             )
         })?;
     }
+    for backend in merged
+        .backends
+        .iter()
+        .flat_map(|backends| backends.backends.values())
+    {
+        backend.validate().map_err(|error| {
+            LSPError::new(
+                ErrorCode::InvalidParams,
+                &format!("Could not apply the received settings: {}", error),
+            )
+        })?;
+    }
     Ok(merged)
 }
 
@@ -284,6 +296,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_backend_without_url_or_files_is_rejected() {
+        let error = merge_settings(
+            &Settings::default(),
+            json!({
+                "backends": { "backends": { "empty": { "name": "empty" } } }
+            }),
+        )
+        .expect_err("a backend without url or files should be rejected");
+
+        assert!(
+            error.message.contains("needs a url or files"),
+            "the error should name the missing fields, got: {}",
+            error.message
+        );
+    }
+
     #[test]
     fn test_rejected_patch_leaves_the_settings_alone() {
         let settings = Settings::default();
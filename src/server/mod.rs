@@ -25,15 +25,22 @@
 //! - [`message_handler`]: Request/notification dispatch
 //! - [`lsp`]: Protocol types and JSON-RPC serialization
 //! - [`analysis`]: Semantic analysis (completions, hover, etc.)
+//! - [`prefix_catalog`]: Bundled prefixes of common vocabularies
 
 mod analysis;
 mod capabilities;
 mod common;
+mod completion_cache;
 pub(crate) mod configuration;
+mod extension_catalog;
+mod local_index;
 mod lsp;
+mod prefix_catalog;
 mod sparql_operations;
 mod state;
 mod tools;
+mod ttl_cache;
+mod usage_memory;
 
 pub(crate) mod message_handler;
 
@@ -129,7 +136,7 @@ impl Server {
     ///   - `prefix`: A `String` representing the prefix associated with the URI.
     ///   - `uri_prefix`: A `String` representing the URI namespace prefix.
     ///   - `curie`: A `String` representing the compact CURIE form of the URI.
-    /// - `None` if no converter is available, or the URI cannot be found or shortened.
+    /// - `None` if the URI cannot be found or shortened.
     pub(crate) fn shorten_uri(
         &self,
         uri: &str,
@@ -137,7 +144,7 @@ impl Server {
     ) -> Option<(String, String, String)> {
         let converter = backend_name
             .and_then(|name| self.state.get_converter(name))
-            .or(self.state.get_default_converter())?;
+            .unwrap_or_else(|| self.state.get_default_converter());
         let record = converter.find_by_uri(uri).ok()?;
         let curie = converter.compress(uri).ok()?;
         Some((record.prefix.clone(), record.uri_prefix.clone(), curie))
@@ -146,8 +153,19 @@ impl Server {
     pub(crate) fn load_templates(
         &mut self,
         backend_name: &str,
-        templates: HashMap<CompletionTemplate, String>,
+        mut templates: HashMap<CompletionTemplate, String>,
     ) -> Result<(), LSPError> {
+        // NOTE: templates with a default query work without being configured.
+        for template in [
+            CompletionTemplate::GraphCompletion,
+            CompletionTemplate::LanguageTagCompletion,
+        ] {
+            if let Some(query) = template.default_query() {
+                templates
+                    .entry(template)
+                    .or_insert_with(|| query.to_string());
+            }
+        }
         for (key, value) in templates {
             self.tools
                 .tera
@@ -0,0 +1,137 @@
+//! A curated catalog of common prefixes, in the spirit of a prefix.cc snapshot.
+//!
+//! The catalog works without any backend: it declares unknown prefixes in
+//! quickfixes, completes `PREFIX` declarations and backs every URI converter
+//! with the lowest priority, i.e. after the `prefixMap` of the backend.
+
+use curies::Converter;
+
+/// Prefix and namespace of well known vocabularies and datasets.
+pub(crate) const PREFIX_CATALOG: &[(&str, &str)] = &[
+    // W3C
+    ("rdf", "http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("rdfs", "http://www.w3.org/2000/01/rdf-schema#"),
+    ("owl", "http://www.w3.org/2002/07/owl#"),
+    ("xsd", "http://www.w3.org/2001/XMLSchema#"),
+    ("skos", "http://www.w3.org/2004/02/skos/core#"),
+    ("skosxl", "http://www.w3.org/2008/05/skos-xl#"),
+    ("sh", "http://www.w3.org/ns/shacl#"),
+    ("prov", "http://www.w3.org/ns/prov#"),
+    ("dcat", "http://www.w3.org/ns/dcat#"),
+    ("org", "http://www.w3.org/ns/org#"),
+    ("time", "http://www.w3.org/2006/time#"),
+    ("vcard", "http://www.w3.org/2006/vcard/ns#"),
+    ("wgs84", "http://www.w3.org/2003/01/geo/wgs84_pos#"),
+    ("sosa", "http://www.w3.org/ns/sosa/"),
+    ("ssn", "http://www.w3.org/ns/ssn/"),
+    ("ldp", "http://www.w3.org/ns/ldp#"),
+    ("oa", "http://www.w3.org/ns/oa#"),
+    ("odrl", "http://www.w3.org/ns/odrl/2/"),
+    ("adms", "http://www.w3.org/ns/adms#"),
+    ("locn", "http://www.w3.org/ns/locn#"),
+    ("fn", "http://www.w3.org/2005/xpath-functions#"),
+    ("math", "http://www.w3.org/2005/xpath-functions/math#"),
+    // Dublin Core
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    ("dcterms", "http://purl.org/dc/terms/"),
+    ("dcmitype", "http://purl.org/dc/dcmitype/"),
+    // Common vocabularies
+    ("schema", "http://schema.org/"),
+    ("foaf", "http://xmlns.com/foaf/0.1/"),
+    ("void", "http://rdfs.org/ns/void#"),
+    ("sioc", "http://rdfs.org/sioc/ns#"),
+    ("doap", "http://usefulinc.com/ns/doap#"),
+    ("cc", "http://creativecommons.org/ns#"),
+    ("bibo", "http://purl.org/ontology/bibo/"),
+    ("qb", "http://purl.org/linked-data/cube#"),
+    ("gr", "http://purl.org/goodrelations/v1#"),
+    ("mo", "http://purl.org/ontology/mo/"),
+    ("event", "http://purl.org/NET/c4dm/event.owl#"),
+    ("qudt", "http://qudt.org/schema/qudt/"),
+    ("unit", "http://qudt.org/vocab/unit/"),
+    ("gn", "http://www.geonames.org/ontology#"),
+    ("obo", "http://purl.obolibrary.org/obo/"),
+    ("up", "http://purl.uniprot.org/core/"),
+    ("ex", "http://example.org/"),
+    // GeoSPARQL
+    ("geo", "http://www.opengis.net/ont/geosparql#"),
+    ("geof", "http://www.opengis.net/def/function/geosparql/"),
+    ("sf", "http://www.opengis.net/ont/sf#"),
+    // Wikidata
+    ("wd", "http://www.wikidata.org/entity/"),
+    ("wds", "http://www.wikidata.org/entity/statement/"),
+    ("wdv", "http://www.wikidata.org/value/"),
+    ("wdt", "http://www.wikidata.org/prop/direct/"),
+    ("p", "http://www.wikidata.org/prop/"),
+    ("ps", "http://www.wikidata.org/prop/statement/"),
+    ("pq", "http://www.wikidata.org/prop/qualifier/"),
+    ("pr", "http://www.wikidata.org/prop/reference/"),
+    ("wikibase", "http://wikiba.se/ontology#"),
+    ("bd", "http://www.bigdata.com/rdf#"),
+    // DBpedia
+    ("dbo", "http://dbpedia.org/ontology/"),
+    ("dbr", "http://dbpedia.org/resource/"),
+    ("dbp", "http://dbpedia.org/property/"),
+    // OpenStreetMap
+    ("osm", "https://www.openstreetmap.org/"),
+    ("osmkey", "https://www.openstreetmap.org/wiki/Key:"),
+    ("osmrel", "https://www.openstreetmap.org/relation/"),
+    ("osmway", "https://www.openstreetmap.org/way/"),
+    ("osmnode", "https://www.openstreetmap.org/node/"),
+    // Others
+    ("yago", "http://yago-knowledge.org/resource/"),
+    ("ql", "http://qlever.cs.uni-freiburg.de/builtin-functions/"),
+    // Engine extensions
+    ("bif", "http://www.openlinksw.com/schemas/bif#"),
+    ("apf", "http://jena.apache.org/ARQ/property#"),
+    ("afn", "http://jena.apache.org/ARQ/function#"),
+    ("text", "http://jena.apache.org/text#"),
+    ("luc", "http://www.ontotext.com/owlim/lucene#"),
+    ("bds", "http://www.bigdata.com/rdf/search#"),
+];
+
+/// A converter that knows all prefixes of the [`PREFIX_CATALOG`].
+pub(crate) fn catalog_converter() -> Converter {
+    let mut converter = Converter::default();
+    extend_with_catalog(&mut converter);
+    converter
+}
+
+/// Adds the catalog prefixes to `converter`, without overriding any prefix or
+/// namespace it already knows.
+pub(crate) fn extend_with_catalog(converter: &mut Converter) {
+    for (prefix, uri_prefix) in PREFIX_CATALOG {
+        // NOTE: conflicts are expected, the existing record wins.
+        let _ = converter.add_prefix(prefix, uri_prefix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PREFIX_CATALOG, catalog_converter, extend_with_catalog};
+    use curies::Converter;
+
+    #[test]
+    fn catalog_has_no_conflicts() {
+        assert_eq!(catalog_converter().len(), PREFIX_CATALOG.len());
+    }
+
+    #[test]
+    fn catalog_has_lowest_priority() {
+        let mut converter = Converter::default();
+        converter
+            .add_prefix("schema", "https://schema.org/")
+            .unwrap();
+        extend_with_catalog(&mut converter);
+        assert_eq!(
+            converter.find_by_prefix("schema").unwrap().uri_prefix,
+            "https://schema.org/"
+        );
+        assert_eq!(
+            converter
+                .compress("http://xmlns.com/foaf/0.1/name")
+                .unwrap(),
+            "foaf:name"
+        );
+    }
+}
@@ -13,7 +13,12 @@
 //! - **Documents**: Open text documents keyed by URI, with incremental sync support
 //! - **Backends**: SPARQL endpoints with associated prefix maps and request methods
 //! - **Parse tree cache**: Single-entry cache to avoid re-parsing unchanged documents
-//! - **URI converters**: CURIE/prefix converters for URI compression per backend
+//! - **URI converters**: CURIE/prefix converters for URI compression per backend,
+//!   backed by the [prefix catalog](super::prefix_catalog)
+//! - **Usage memory**: Which IRIs occur in the dataset of a backend
+//! - **Local indexes**: In-memory [`LocalIndex`] of the files of local backends
+//! - **Completion cache**: Recent results of completion queries, see
+//!   [`CompletionCache`]
 //!
 //! # Parse Tree Caching
 //!
@@ -26,7 +31,13 @@
 //! - [`super::Server`]: Owns the `ServerState` instance
 //! - [`super::lsp::textdocument`]: `TextDocumentItem` stored in documents map
 
-use crate::server::configuration::{BackendConfiguration, RequestMethod};
+use crate::server::{
+    completion_cache::CompletionCache,
+    configuration::{BackendConfiguration, RequestMethod},
+    local_index::LocalIndex,
+    prefix_catalog::{catalog_converter, extend_with_catalog},
+    usage_memory::UsageMemory,
+};
 
 use super::lsp::{
     TextDocumentContentChangeEvent,
@@ -64,12 +75,19 @@ pub struct ServerState {
     pub client_type: Option<ClientType>,
     documents: HashMap<String, TextDocumentItem>,
     backends: HashMap<String, BackendConfiguration>,
+    local_indexes: HashMap<String, LocalIndex>,
     uri_converter: HashMap<String, Converter>,
+    /// Converter used when there is no default backend.
+    prefix_catalog: Converter,
     default_backend: Option<String>,
     parse_tree_cache: RefCell<Option<CachedParseTree>>,
     request_id_counter: u32,
     running_sparql_requests: HashMap<String, Box<dyn Fn()>>,
     pub label_memory: HashMap<String, String>,
+    /// Whether an IRI occurs in the dataset of a backend, as predicate or class.
+    pub usage_memory: UsageMemory,
+    last_usage_check_ms: Option<f64>,
+    pub completion_cache: CompletionCache,
 }
 
 impl ServerState {
@@ -79,12 +97,17 @@ impl ServerState {
             client_type: None,
             documents: HashMap::new(),
             backends: HashMap::new(),
+            local_indexes: HashMap::new(),
             uri_converter: HashMap::new(),
+            prefix_catalog: catalog_converter(),
             default_backend: None,
             parse_tree_cache: RefCell::new(None),
             request_id_counter: 0,
             running_sparql_requests: HashMap::new(),
             label_memory: HashMap::new(),
+            usage_memory: UsageMemory::default(),
+            last_usage_check_ms: None,
+            completion_cache: CompletionCache::default(),
         }
     }
 
@@ -115,6 +138,9 @@ impl ServerState {
     }
 
     pub fn add_backend(&mut self, backend: BackendConfiguration) {
+        // NOTE: a re-registered backend may come with another url or templates.
+        self.completion_cache.invalidate_backend(&backend.name);
+        self.usage_memory.invalidate_backend(&backend.name);
         self.backends.insert(backend.name.clone(), backend);
     }
 
@@ -165,10 +191,29 @@ impl ServerState {
                 }
             }
         }
+        extend_with_catalog(&mut converter);
         self.uri_converter.insert(backend, converter);
         Ok(())
     }
 
+    /// Indexes the local files of a backend, see [`BackendConfiguration::files`].
+    pub fn load_local_index(&mut self, backend: &str, files: &[String]) -> Result<(), LSPError> {
+        if files.is_empty() {
+            self.local_indexes.remove(backend);
+            return Ok(());
+        }
+        let index = LocalIndex::load(files).map_err(|err| {
+            tracing::error!("Could not index the files of backend \"{backend}\"\n{err}");
+            LSPError::new(ErrorCode::InvalidParams, &err)
+        })?;
+        self.local_indexes.insert(backend.to_string(), index);
+        Ok(())
+    }
+
+    pub(crate) fn get_local_index(&self, backend_name: &str) -> Option<&LocalIndex> {
+        self.local_indexes.get(backend_name)
+    }
+
     pub fn get_backend(&self, backend_name: &str) -> Option<&BackendConfiguration> {
         self.backends.get(backend_name)
     }
@@ -233,8 +278,12 @@ impl ServerState {
         })
     }
 
-    pub(crate) fn get_default_converter(&self) -> Option<&Converter> {
-        self.uri_converter.get(self.default_backend.as_ref()?)
+    /// The converter of the default backend, or the prefix catalog if there is none.
+    pub(crate) fn get_default_converter(&self) -> &Converter {
+        self.default_backend
+            .as_ref()
+            .and_then(|backend| self.uri_converter.get(backend))
+            .unwrap_or(&self.prefix_catalog)
     }
 
     pub(crate) fn get_converter(&self, backend_name: &str) -> Option<&Converter> {
@@ -245,6 +294,20 @@ impl ServerState {
         self.backends.values().collect()
     }
 
+    /// Claims the slot for the next usage check.
+    /// Returns `false` if the previous check started less than `interval_ms` ago.
+    pub(super) fn start_usage_check(&mut self, interval_ms: u32) -> bool {
+        let now = get_timestamp_ms();
+        if self
+            .last_usage_check_ms
+            .is_some_and(|last_check| now - last_check < interval_ms as f64)
+        {
+            return false;
+        }
+        self.last_usage_check_ms = Some(now);
+        true
+    }
+
     #[cfg(target_arch = "wasm32")]
     pub(crate) fn add_running_request(&mut self, id: String, cancel_fn: Box<dyn Fn()>) {
         self.running_sparql_requests.insert(id, cancel_fn);
@@ -262,7 +325,7 @@ pub struct TimedParseResult {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn get_timestamp_ms() -> f64 {
+pub(crate) fn get_timestamp_ms() -> f64 {
     use std::time::SystemTime;
     SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
@@ -272,7 +335,7 @@ fn get_timestamp_ms() -> f64 {
 }
 
 #[cfg(target_arch = "wasm32")]
-fn get_timestamp_ms() -> f64 {
+pub(crate) fn get_timestamp_ms() -> f64 {
     use wasm_bindgen::JsCast;
     use web_sys::WorkerGlobalScope;
     let worker_global: WorkerGlobalScope = js_sys::global().unchecked_into();
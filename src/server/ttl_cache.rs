@@ -0,0 +1,107 @@
+//! A cache whose entries expire and whose least recently used entry is evicted
+//! once it is full.
+//!
+//! The entries are kept in the order of their last use, so lookups, insertions
+//! and evictions take constant time. An expired entry is dropped when it is
+//! looked up, or evicted once it is the least recently used one.
+
+use std::hash::Hash;
+
+use hashlink::LinkedHashMap;
+
+struct Entry<V> {
+    value: V,
+    created_ms: f64,
+}
+
+pub(crate) struct TtlCache<K, V> {
+    /// Ordered from the least to the most recently used entry.
+    entries: LinkedHashMap<K, Entry<V>>,
+}
+
+impl<K: Hash + Eq, V> Default for TtlCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: LinkedHashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> TtlCache<K, V> {
+    /// The value of `key`, unless it is `ttl_ms` or older.
+    /// Marks the entry as the most recently used one.
+    pub(crate) fn get(&mut self, key: &K, now_ms: f64, ttl_ms: f64) -> Option<&V> {
+        if now_ms - self.entries.get(key)?.created_ms >= ttl_ms {
+            self.entries.remove(key);
+            return None;
+        }
+        self.entries.to_back(key).map(|entry| &entry.value)
+    }
+
+    /// When the entry of `key` was created.
+    pub(crate) fn created_ms(&self, key: &K) -> Option<f64> {
+        self.entries.get(key).map(|entry| entry.created_ms)
+    }
+
+    /// Stores `value` as the most recently used entry and evicts the least
+    /// recently used entries to keep at most `capacity` entries.
+    pub(crate) fn insert(&mut self, key: K, value: V, created_ms: f64, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        self.entries.remove(&key);
+        while self.entries.len() >= capacity {
+            self.entries.pop_front();
+        }
+        self.entries.insert(key, Entry { value, created_ms });
+    }
+
+    /// The entries younger than `ttl_ms`, from the least to the most recently used.
+    pub(crate) fn iter(&self, now_ms: f64, ttl_ms: f64) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .filter(move |(_, entry)| now_ms - entry.created_ms < ttl_ms)
+            .map(|(key, entry)| (key, &entry.value))
+    }
+
+    /// Keeps only the entries for which `keep` returns `true`.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&K, &V) -> bool) {
+        self.entries.retain(|key, entry| keep(key, &entry.value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TtlCache;
+
+    #[test]
+    fn entries_expire() {
+        let mut cache = TtlCache::default();
+        cache.insert("a", 1, 0.0, 10);
+        assert_eq!(cache.get(&"a", 999.0, 1000.0), Some(&1));
+        assert_eq!(cache.get(&"a", 1000.0, 1000.0), None);
+        assert_eq!(cache.iter(0.0, f64::INFINITY).count(), 0);
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let mut cache = TtlCache::default();
+        cache.insert("a", 1, 0.0, 2);
+        cache.insert("b", 2, 0.0, 2);
+        cache.get(&"a", 0.0, 1000.0);
+        cache.insert("c", 3, 0.0, 2);
+        assert_eq!(cache.get(&"a", 0.0, 1000.0), Some(&1));
+        assert_eq!(cache.get(&"b", 0.0, 1000.0), None);
+        assert_eq!(cache.get(&"c", 0.0, 1000.0), Some(&3));
+    }
+
+    #[test]
+    fn replacing_an_entry_does_not_evict_another() {
+        let mut cache = TtlCache::default();
+        cache.insert("a", 1, 0.0, 2);
+        cache.insert("b", 2, 0.0, 2);
+        cache.insert("a", 3, 0.0, 2);
+        assert_eq!(cache.get(&"a", 0.0, 1000.0), Some(&3));
+        assert_eq!(cache.get(&"b", 0.0, 1000.0), Some(&2));
+    }
+}
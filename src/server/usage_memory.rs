@@ -0,0 +1,134 @@
+//! Memory of the answers of `usageCheck` queries.
+//!
+//! An answer says whether an IRI occurs in the dataset of a backend in the
+//! role it was checked for: as a predicate, or as a class with instances.
+//! Answers expire after [`TTL_MS`] and the least recently used answer is
+//! evicted once [`CAPACITY`] answers are stored.
+
+use super::ttl_cache::TtlCache;
+
+/// Answers older than this are asked again, the dataset may have changed.
+const TTL_MS: f64 = 60.0 * 60.0 * 1000.0;
+/// The number of answers kept at most.
+const CAPACITY: usize = 10_000;
+
+/// The role an IRI is checked for.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub(crate) enum TermKind {
+    Predicate,
+    Class,
+}
+
+#[derive(Default)]
+pub(crate) struct UsageMemory {
+    answers: TtlCache<(String, TermKind, String), bool>,
+}
+
+impl UsageMemory {
+    /// Whether `iri` is used as `kind` in the dataset of `backend`,
+    /// or `None` if that is not known (anymore).
+    pub(crate) fn get(
+        &mut self,
+        backend: &str,
+        kind: TermKind,
+        iri: &str,
+        now_ms: f64,
+    ) -> Option<bool> {
+        self.answers
+            .get(
+                &(backend.to_string(), kind, iri.to_string()),
+                now_ms,
+                TTL_MS,
+            )
+            .copied()
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        backend: &str,
+        kind: TermKind,
+        iri: &str,
+        used: bool,
+        now_ms: f64,
+    ) {
+        self.answers.insert(
+            (backend.to_string(), kind, iri.to_string()),
+            used,
+            now_ms,
+            CAPACITY,
+        );
+    }
+
+    /// Forgets all answers of `backend`.
+    pub(crate) fn invalidate_backend(&mut self, backend: &str) {
+        self.answers
+            .retain(|(answer_backend, _, _), _| answer_backend != backend);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CAPACITY, TTL_MS, TermKind, UsageMemory};
+
+    #[test]
+    fn answers_are_kept_per_kind() {
+        let mut memory = UsageMemory::default();
+        memory.insert("test", TermKind::Predicate, "http://ex.org/a", true, 0.0);
+        assert_eq!(
+            memory.get("test", TermKind::Predicate, "http://ex.org/a", 1.0),
+            Some(true)
+        );
+        assert_eq!(
+            memory.get("test", TermKind::Class, "http://ex.org/a", 1.0),
+            None
+        );
+        memory.insert("test", TermKind::Class, "http://ex.org/a", false, 1.0);
+        assert_eq!(
+            memory.get("test", TermKind::Predicate, "http://ex.org/a", 2.0),
+            Some(true)
+        );
+        assert_eq!(
+            memory.get("test", TermKind::Class, "http://ex.org/a", 2.0),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn answers_expire() {
+        let mut memory = UsageMemory::default();
+        memory.insert("test", TermKind::Class, "http://ex.org/a", true, 0.0);
+        assert_eq!(
+            memory.get("test", TermKind::Class, "http://ex.org/a", TTL_MS),
+            None
+        );
+    }
+
+    #[test]
+    fn least_recently_used_answer_is_evicted() {
+        let mut memory = UsageMemory::default();
+        for idx in 0..CAPACITY {
+            memory.insert("test", TermKind::Class, &idx.to_string(), true, 0.0);
+        }
+        memory.get("test", TermKind::Class, "0", 0.0);
+        memory.insert("test", TermKind::Class, "new", true, 0.0);
+        assert_eq!(memory.answers.iter(0.0, TTL_MS).count(), CAPACITY);
+        assert_eq!(memory.get("test", TermKind::Class, "0", 0.0), Some(true));
+        assert_eq!(memory.get("test", TermKind::Class, "1", 0.0), None);
+    }
+
+    #[test]
+    fn answers_of_a_backend_are_invalidated() {
+        let mut memory = UsageMemory::default();
+        memory.insert("a", TermKind::Class, "http://ex.org/a", true, 0.0);
+        memory.insert("b", TermKind::Class, "http://ex.org/a", true, 0.0);
+        memory.invalidate_backend("a");
+        assert_eq!(
+            memory.get("a", TermKind::Class, "http://ex.org/a", 0.0),
+            None
+        );
+        assert_eq!(
+            memory.get("b", TermKind::Class, "http://ex.org/a", 0.0),
+            Some(true)
+        );
+    }
+}
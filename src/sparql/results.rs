@@ -55,9 +55,9 @@ pub enum SparqlResultsBody {
     Results { bindings: Vec<Binding> },
 }
 
-type Binding = HashMap<String, RDFTerm>;
+pub type Binding = HashMap<String, RDFTerm>;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum RDFTerm {
     Uri {
@@ -7,6 +7,10 @@ mod harness;
 use harness::TestClient;
 use harness::runtime::run_lsp_test;
 use serde_json::{Value, json};
+use wiremock::{
+    Mock, MockServer, ResponseTemplate,
+    matchers::{method, query_param_contains},
+};
 
 /// Helper to extract completion labels from a completion response
 fn get_completion_labels(response: &Value) -> Vec<String> {
@@ -1281,3 +1285,970 @@ fn test_object_variable_completion_in_blank_node() {
         );
     });
 }
+
+// ========== graph completion ==========
+
+#[test]
+fn test_graph_completion_lists_named_graphs_of_the_backend() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        // NOTE: without a configured `graphCompletion` query the default one is sent.
+        Mock::given(method("GET"))
+            .and(query_param_contains("query", "GRAPH ?qls_entity"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_entity", "qls_count"] },
+                "results": { "bindings": [
+                    {
+                        "qls_entity": { "type": "uri", "value": "http://ex.org/graphs/people" },
+                        "qls_count": { "type": "literal", "value": "42" }
+                    },
+                    {
+                        "qls_entity": { "type": "uri", "value": "http://other.org/graph" },
+                        "qls_count": { "type": "literal", "value": "7" }
+                    }
+                ]}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "graphs": "http://ex.org/graphs/" },
+                "queries": {}
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { GRAPH  }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 23).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert_eq!(
+            get_completion_labels(&response),
+            vec!["graphs:people", "<http://other.org/graph>"]
+        );
+        assert!(!has_completion_label(&response, "<graph>"));
+    });
+}
+
+#[test]
+fn test_graph_completion_without_backend_suggests_placeholder() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * FROM  WHERE {}")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 14).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert_eq!(get_completion_labels(&response), vec!["<graph>"]);
+    });
+}
+
+/// Helper to find a completion item by its label
+fn completion_item<'a>(response: &'a Value, label: &str) -> Option<&'a Value> {
+    response["result"]["items"]
+        .as_array()?
+        .iter()
+        .find(|item| item["label"] == label)
+}
+
+#[test]
+fn test_datatype_completion_declares_missing_prefix() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        client
+            .open_document("file:///test.sparql", r#"SELECT * WHERE { ?s ?p "42"^^ }"#)
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 29).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        let item = completion_item(&response, "xsd:integer").expect("Should suggest xsd:integer");
+        assert_eq!(
+            item["additionalTextEdits"][0]["newText"],
+            "PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>\n"
+        );
+        assert!(has_completion_label(&response, "rdf:langString"));
+    });
+}
+
+#[test]
+fn test_datatype_completion_uses_declared_prefix() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let input =
+            "PREFIX x: <http://www.w3.org/2001/XMLSchema#>\nSELECT * WHERE { ?s ?p \"1\"^^x:d }";
+        client.open_document("file:///test.sparql", input).await;
+        let id = client.complete("file:///test.sparql", 1, 31).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        let item = completion_item(&response, "x:decimal").expect("Should suggest x:decimal");
+        assert!(item["additionalTextEdits"].is_null());
+        assert_eq!(item["textEdit"]["range"]["start"]["character"], 28);
+    });
+}
+
+#[test]
+fn test_language_tag_completion_without_backend_suggests_common_tags() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        client
+            .open_document(
+                "file:///test.sparql",
+                r#"SELECT * WHERE { ?s ?p "Berlin"@en }"#,
+            )
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 34).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert!(has_completion_label(&response, "en"));
+        assert!(has_completion_label(&response, "de"));
+        assert!(has_completion_label(&response, "en--ltr"));
+        assert!(has_completion_label(&response, "en--rtl"));
+    });
+}
+
+#[test]
+fn test_language_tag_completion_lists_tags_of_the_predicate_first() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param_contains("query", "[] rdfs:label ?qls_literal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_entity", "qls_count"] },
+                "results": { "bindings": [
+                    {
+                        "qls_entity": { "type": "literal", "value": "gsw" },
+                        "qls_count": { "type": "literal", "value": "9" }
+                    },
+                    {
+                        "qls_entity": { "type": "literal", "value": "de" },
+                        "qls_count": { "type": "literal", "value": "3" }
+                    }
+                ]}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "queries": {}
+            }))
+            .await;
+
+        let input = "PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>\nSELECT * WHERE { ?s rdfs:label \"Zürich\"@ }";
+        client.open_document("file:///test.sparql", input).await;
+        let id = client.complete("file:///test.sparql", 1, 40).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        let labels = get_completion_labels(&response);
+        assert_eq!(labels[..3], ["gsw", "de", "en"]);
+        assert_eq!(labels.iter().filter(|label| *label == "de").count(), 1);
+    });
+}
+
+#[test]
+fn test_built_in_call_completion_includes_engine_extension_functions() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "qlever",
+                "url": "http://localhost/sparql",
+                "engine": "QLever",
+                "default": true,
+                "prefixMap": {}
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { FILTER() }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 24).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert!(has_completion_label(&response, "STRSTARTS"));
+        let item =
+            completion_item(&response, "geof:distance").expect("Should suggest geof:distance");
+        assert_eq!(
+            item["insertText"],
+            "geof:distance(${1:geometry1}, ${2:geometry2}, ${0:unit})"
+        );
+        assert_eq!(
+            item["additionalTextEdits"][0]["newText"],
+            "PREFIX geof: <http://www.opengis.net/def/function/geosparql/>\n"
+        );
+        assert!(!has_completion_label(&response, "bif:st_distance"));
+        // NOTE: magic predicates are no functions.
+        assert!(!has_completion_label(&response, "ql:contains-word"));
+    });
+}
+
+#[test]
+fn test_predicate_completion_includes_magic_predicates_of_the_engine() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "virtuoso",
+                "url": "http://localhost:1/sparql",
+                "engine": "Virtuoso",
+                "default": true,
+                "prefixMap": {}
+            }))
+            .await;
+
+        let input = "PREFIX b: <http://www.openlinksw.com/schemas/bif#>\nSELECT * WHERE { ?o  }";
+        client.open_document("file:///test.sparql", input).await;
+        let id = client.complete("file:///test.sparql", 1, 20).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        let item = completion_item(&response, "b:contains").expect("Should suggest b:contains");
+        assert!(item["additionalTextEdits"].is_null());
+    });
+}
+
+// ========== completion cache ==========
+
+const CITY_COMPLETION_QUERY: &str = "SELECT ?qls_entity ?qls_label WHERE { ?qls_entity <http://ex.org/name> ?qls_label FILTER(STRSTARTS(?qls_label, \"{{ search_term }}\")) } LIMIT {{ limit }}";
+
+async fn mount_cities(mock_server: &MockServer, expected_requests: u64) {
+    Mock::given(method("GET"))
+        .and(query_param_contains("query", "STRSTARTS"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "head": { "vars": ["qls_entity", "qls_label"] },
+            "results": { "bindings": [
+                {
+                    "qls_entity": { "type": "uri", "value": "http://ex.org/Berlin" },
+                    "qls_label": { "type": "literal", "value": "Berlin" }
+                },
+                {
+                    "qls_entity": { "type": "uri", "value": "http://ex.org/Bern" },
+                    "qls_label": { "type": "literal", "value": "Bern" }
+                }
+            ]}
+        })))
+        .expect(expected_requests)
+        .mount(mock_server)
+        .await;
+}
+
+#[test]
+fn test_narrowed_search_term_is_answered_from_cache() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        mount_cities(&mock_server, 1).await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "ex": "http://ex.org/" },
+                "queries": { "subjectCompletion": CITY_COMPLETION_QUERY }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { Ber }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 20).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        assert!(has_completion_label(&response, "ex:Berlin"));
+        assert!(has_completion_label(&response, "ex:Bern"));
+
+        client
+            .change_document("file:///test.sparql", 2, "SELECT * WHERE { Berl }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 21).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        assert!(has_completion_label(&response, "ex:Berlin"));
+        assert!(!has_completion_label(&response, "ex:Bern"));
+    });
+}
+
+#[test]
+fn test_truncated_result_is_not_narrowed() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        mount_cities(&mock_server, 2).await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .change_settings(json!({ "completion": { "resultSizeLimit": 2 } }))
+            .await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "ex": "http://ex.org/" },
+                "queries": { "subjectCompletion": CITY_COMPLETION_QUERY }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { Ber }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 20).await;
+        client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        client
+            .change_document("file:///test.sparql", 2, "SELECT * WHERE { Berl }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 21).await;
+        client
+            .get_response(id)
+            .expect("Should receive completion response");
+    });
+}
+
+// ========== fuzzy matching ==========
+
+#[test]
+fn test_fuzzy_match_outranks_popular_mismatches() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_entity", "qls_label", "qls_count"] },
+                "results": { "bindings": [
+                    {
+                        "qls_entity": { "type": "uri", "value": "http://www.wikidata.org/prop/direct/P19" },
+                        "qls_label": { "type": "literal", "value": "place of birth" },
+                        "qls_count": { "type": "literal", "value": "1000000" }
+                    },
+                    {
+                        "qls_entity": { "type": "uri", "value": "http://www.wikidata.org/prop/direct/P570" },
+                        "qls_label": { "type": "literal", "value": "date of death" },
+                        "qls_count": { "type": "literal", "value": "900000" }
+                    },
+                    {
+                        "qls_entity": { "type": "uri", "value": "http://www.wikidata.org/prop/direct/P569" },
+                        "qls_label": { "type": "literal", "value": "date of birth" },
+                        "qls_count": { "type": "literal", "value": "10" }
+                    }
+                ]}
+            })))
+            .mount(&mock_server)
+            .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "wikidata",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "wdt": "http://www.wikidata.org/prop/direct/" },
+                "queries": { "subjectCompletion": "SELECT ?qls_entity ?qls_label ?qls_count WHERE {} LIMIT {{ limit }}" }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { birthdat }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 25).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        let mut items = response["result"]["items"].as_array().unwrap().clone();
+        items.sort_by_key(|item| item["sortText"].as_str().unwrap().to_string());
+        let labels: Vec<_> = items
+            .iter()
+            .map(|item| item["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels, vec!["wdt:P569", "wdt:P19", "wdt:P570"]);
+    });
+}
+
+// ========== local files ==========
+
+const LOCAL_ONTOLOGY: &str = r#"
+@prefix ex: <http://ex.org/> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+ex:City a rdfs:Class .
+ex:Person a rdfs:Class .
+ex:bornIn rdfs:label "born in" ; rdfs:domain ex:Person ; rdfs:range ex:City .
+ex:Berlin a ex:City ; rdfs:label "Berlin" .
+ex:Bern a ex:City ; rdfs:label "Bern" .
+ex:alice a ex:Person ; ex:name "Alice" .
+"#;
+
+/// Adds a backend that is answered from a Turtle file with `LOCAL_ONTOLOGY`.
+async fn add_local_backend(client: &TestClient, file_name: &str) {
+    let path = std::env::temp_dir().join(file_name);
+    std::fs::write(&path, LOCAL_ONTOLOGY).expect("Should write the ontology file");
+    client
+        .add_backend_with(json!({
+            "name": "local",
+            "default": true,
+            "prefixMap": { "ex": "http://ex.org/" },
+            "files": [path.to_str().unwrap()]
+        }))
+        .await;
+}
+
+#[test]
+fn test_local_files_answer_subject_completion() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        add_local_backend(&client, "qlue-ls-subject-completion.ttl").await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { Ber }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 20).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert!(has_completion_label(&response, "ex:Berlin"));
+        assert!(has_completion_label(&response, "ex:Bern"));
+        assert!(!has_completion_label(&response, "ex:alice"));
+    });
+}
+
+#[test]
+fn test_local_files_answer_predicate_completion_with_domains() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        add_local_backend(&client, "qlue-ls-predicate-completion.ttl").await;
+
+        let input = "PREFIX ex: <http://ex.org/>\nSELECT * WHERE { ex:alice  }";
+        client.open_document("file:///test.sparql", input).await;
+        let id = client.complete("file:///test.sparql", 1, 26).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert!(has_completion_label(&response, "ex:bornIn"));
+        assert!(has_completion_label(&response, "ex:name"));
+        assert!(!has_completion_label(&response, "rdfs:range"));
+    });
+}
+
+#[test]
+fn test_local_files_answer_object_completion_with_ranges() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        add_local_backend(&client, "qlue-ls-object-completion.ttl").await;
+
+        let input = "PREFIX ex: <http://ex.org/>\nSELECT * WHERE { ?person ex:bornIn  }";
+        client.open_document("file:///test.sparql", input).await;
+        let id = client.complete("file:///test.sparql", 1, 35).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert!(has_completion_label(&response, "ex:Berlin"));
+        assert!(has_completion_label(&response, "ex:Bern"));
+        assert!(!has_completion_label(&response, "ex:alice"));
+    });
+}
+
+#[test]
+fn test_backend_without_endpoint_is_no_service_url() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        add_local_backend(&client, "qlue-ls-service-url.ttl").await;
+        client
+            .add_backend_with(json!({
+                "name": "wikidata",
+                "url": "https://qlever.dev/api/wikidata",
+                "default": true
+            }))
+            .await;
+        client
+            .add_backend_with(json!({
+                "name": "osm",
+                "url": "https://qlever.dev/api/osm-planet"
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { SERVICE  }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 25).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        let labels = get_completion_labels(&response);
+
+        assert!(labels.contains(&"osm".to_string()), "got: {:?}", labels);
+        assert!(!labels.contains(&"local".to_string()), "got: {:?}", labels);
+    });
+}
+
+#[test]
+fn test_local_files_do_not_restrict_predicates_after_a_path_sequence() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        add_local_backend(&client, "qlue-ls-path-completion.ttl").await;
+
+        let input = "PREFIX ex: <http://ex.org/>\nSELECT * WHERE { ex:alice ex:bornIn/ }";
+        client.open_document("file:///test.sparql", input).await;
+        let id = client.complete("file:///test.sparql", 1, 36).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        // NOTE: `rdfs:label` is not used with `ex:alice`, but with the cities she is born in.
+        assert!(has_completion_label(&response, "rdfs:label"));
+    });
+}
+
+#[test]
+fn test_user_snippets_respect_location_backend_and_indentation() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        client
+            .change_settings(json!({
+                "format": { "tabSize": 4, "insertSpaces": true },
+                "completion": {
+                    "snippets": [
+                        {
+                            "name": "bounding box",
+                            "body": "FILTER(\n\t?${1:lat} > ${2:0} &&\n\t?$1 < ${3:1}\n)",
+                            "documentation": "Keeps points inside a bounding box.",
+                            "locations": ["subject"]
+                        },
+                        {
+                            "name": "label service",
+                            "body": "SERVICE wikibase:label { $0 }",
+                            "backends": ["wikidata"]
+                        },
+                        {
+                            "name": "count query",
+                            "body": "SELECT (COUNT(*) AS ?count) WHERE {\n\t$0\n}",
+                            "locations": ["start"]
+                        }
+                    ]
+                }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE {\n  \n}")
+            .await;
+
+        let id = client.complete("file:///test.sparql", 1, 2).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        let labels = get_completion_labels(&response);
+
+        assert!(!labels.contains(&"label service".to_string()));
+        assert!(!labels.contains(&"count query".to_string()));
+        let item = response["result"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["label"] == "bounding box")
+            .unwrap_or_else(|| panic!("Should offer the snippet, got: {:?}", labels));
+        assert_eq!(
+            item["insertText"],
+            "FILTER(\n    ?${1:lat} > ${2:0} &&\n    ?$1 < ${3:1}\n)"
+        );
+        assert_eq!(item["insertTextFormat"], 2);
+        assert_eq!(item["documentation"], "Keeps points inside a bounding box.");
+    });
+}
+
+#[test]
+fn test_user_snippets_are_not_offered_while_typing_a_variable() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        client
+            .change_settings(json!({
+                "completion": {
+                    "snippets": [{ "name": "todo", "body": "# TODO: $0" }]
+                }
+            }))
+            .await;
+
+        client
+            .open_document(
+                "file:///test.sparql",
+                "SELECT * WHERE {\n  ?s ?p ?o .\n  ?\n}",
+            )
+            .await;
+
+        let id = client.complete("file:///test.sparql", 2, 3).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        let labels = get_completion_labels(&response);
+
+        assert!(labels.contains(&"?s".to_string()), "got: {:?}", labels);
+        assert!(!labels.contains(&"todo".to_string()), "got: {:?}", labels);
+    });
+}
+
+// ========== property paths ==========
+
+async fn mount_predicates(mock_server: &MockServer, local_context: &str, predicate: &str) {
+    Mock::given(method("GET"))
+        .and(query_param_contains("query", local_context))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "head": { "vars": ["qls_entity"] },
+            "results": { "bindings": [
+                { "qls_entity": { "type": "uri", "value": predicate } }
+            ]}
+        })))
+        .expect(1)
+        .mount(mock_server)
+        .await;
+}
+
+#[test]
+fn test_predicate_completion_after_path_operators() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        // NOTE: after "/" the predicate starts at the objects of the preceding
+        // segment, after "|" at the subject and after "^" it is inverted.
+        mount_predicates(
+            &mock_server,
+            "{ ?a ex:p0 ?qls_inner . ?qls_inner ?qls_entity [] }",
+            "http://ex.org/next",
+        )
+        .await;
+        mount_predicates(
+            &mock_server,
+            "{ ?a ?qls_entity [] }",
+            "http://ex.org/alternative",
+        )
+        .await;
+        mount_predicates(
+            &mock_server,
+            "{ ?a ex:p0 ?qls_inner . [] ?qls_entity ?qls_inner }",
+            "http://ex.org/inverse",
+        )
+        .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "ex": "http://ex.org/" },
+                "queries": {
+                    "predicateCompletionContextSensitive":
+                        "SELECT ?qls_entity WHERE { {{ local_context }} } LIMIT {{ limit }}",
+                    "predicateCompletionContextInsensitive":
+                        "SELECT ?qls_entity WHERE { [] ?qls_entity [] } LIMIT {{ limit }}"
+                }
+            }))
+            .await;
+
+        for (version, text, expected) in [
+            (1, "SELECT * WHERE { ?a ex:p0/", "ex:next"),
+            (2, "SELECT * WHERE { ?a ex:p0|", "ex:alternative"),
+            (3, "SELECT * WHERE { ?a ex:p0/^", "ex:inverse"),
+        ] {
+            if version == 1 {
+                client.open_document("file:///test.sparql", text).await;
+            } else {
+                client
+                    .change_document("file:///test.sparql", version, text)
+                    .await;
+            }
+            let id = client
+                .complete("file:///test.sparql", 0, text.len() as u32)
+                .await;
+            let response = client
+                .get_response(id)
+                .expect("Should receive completion response");
+            assert_eq!(
+                get_completion_labels(&response),
+                vec![expected],
+                "after {text:?}"
+            );
+        }
+    });
+}
+
+// ========== SPARQL Update ==========
+
+#[test]
+fn test_subject_completion_in_insert_data() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        mount_cities(&mock_server, 1).await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "ex": "http://ex.org/" },
+                "queries": { "subjectCompletion": CITY_COMPLETION_QUERY }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "INSERT DATA { Ber }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 17).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+
+        assert!(has_completion_label(&response, "ex:Berlin"));
+        assert!(has_completion_label(&response, "ex:Bern"));
+    });
+}
+
+#[test]
+fn test_graph_completion_for_update_targets() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param_contains("query", "GRAPH ?qls_entity"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_entity"] },
+                "results": { "bindings": [
+                    { "qls_entity": { "type": "uri", "value": "http://ex.org/graphs/people" } }
+                ]}
+            })))
+            .mount(&mock_server)
+            .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "graphs": "http://ex.org/graphs/" },
+                "queries": {}
+            }))
+            .await;
+
+        for (version, text) in [
+            (1, "CLEAR GRAPH "),
+            (2, "DROP SILENT GRAPH "),
+            (3, "LOAD <http://ex.org/dump.nt> INTO GRAPH "),
+            (4, "WITH "),
+        ] {
+            if version == 1 {
+                client.open_document("file:///test.sparql", text).await;
+            } else {
+                client
+                    .change_document("file:///test.sparql", version, text)
+                    .await;
+            }
+            let id = client
+                .complete("file:///test.sparql", 0, text.len() as u32)
+                .await;
+            let response = client
+                .get_response(id)
+                .expect("Should receive completion response");
+            assert_eq!(
+                get_completion_labels(&response),
+                vec!["graphs:people"],
+                "after {text:?}"
+            );
+        }
+    });
+}
+
+// ========== completion resolve ==========
+
+#[test]
+fn test_resolve_completion_item_with_entity_details() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param_contains("query", "qls_entity ?qls_label"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_entity", "qls_label"] },
+                "results": { "bindings": [
+                    {
+                        "qls_entity": { "type": "uri", "value": "http://www.wikidata.org/entity/Q42" },
+                        "qls_label": { "type": "literal", "value": "Douglas Adams" }
+                    },
+                    {
+                        "qls_entity": { "type": "literal", "value": "not an entity" }
+                    }
+                ]}
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param_contains(
+                "query",
+                "<http://www.wikidata.org/entity/Q42> schema:description",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_description", "qls_type", "qls_count", "qls_image"] },
+                "results": { "bindings": [
+                    {
+                        "qls_description": { "type": "literal", "value": "English author" },
+                        "qls_type": { "type": "uri", "value": "http://www.wikidata.org/entity/Q5" },
+                        "qls_count": { "type": "literal", "value": "1337" },
+                        "qls_image": { "type": "uri", "value": "http://commons.wikimedia.org/adams.jpg" }
+                    }
+                ]}
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "wikidata",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "wd": "http://www.wikidata.org/entity/" },
+                "queries": {
+                    "subjectCompletion": "SELECT ?qls_entity ?qls_label WHERE {} LIMIT {{ limit }}",
+                    "entityDetails": "SELECT * WHERE { {{ entity }} schema:description ?qls_description }"
+                }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { Dougl }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 22).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        let items = response["result"]["items"].as_array().unwrap();
+        let entity = items
+            .iter()
+            .find(|item| item["label"] == "wd:Q42")
+            .expect("Should complete the entity");
+        assert_eq!(
+            entity["data"],
+            json!({ "entity": "http://www.wikidata.org/entity/Q42", "backend": "wikidata" })
+        );
+        let literal = items
+            .iter()
+            .find(|item| item["label"] == "\"not an entity\"")
+            .expect("Should complete the literal");
+        assert!(literal.get("data").is_none());
+
+        // NOTE: the second resolve is answered from the completion cache.
+        for _ in 0..2 {
+            let id = client.resolve_completion(entity.clone()).await;
+            let resolved = client
+                .get_response(id)
+                .expect("Should receive resolve response")["result"]
+                .clone();
+            assert_eq!(resolved["label"], "wd:Q42");
+            assert_eq!(resolved["documentation"]["kind"], "markdown");
+            assert_eq!(
+                resolved["documentation"]["value"],
+                "### **wd:Q42**\nwd:Q42\n\nEnglish author\n\nType: wd:Q5\n\nUsed 1337 times\n\n![wd:Q42](http://commons.wikimedia.org/adams.jpg)"
+            );
+        }
+
+        let id = client.resolve_completion(literal.clone()).await;
+        let resolved = client
+            .get_response(id)
+            .expect("Should receive resolve response");
+        assert_eq!(&resolved["result"], literal);
+    });
+}
+
+#[test]
+fn test_completion_items_carry_no_data_without_entity_details_query() {
+    run_lsp_test(|| async {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "head": { "vars": ["qls_entity"] },
+                "results": { "bindings": [
+                    { "qls_entity": { "type": "uri", "value": "http://www.wikidata.org/entity/Q42" } }
+                ]}
+            })))
+            .mount(&mock_server)
+            .await;
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "wikidata",
+                "url": mock_server.uri(),
+                "default": true,
+                "prefixMap": { "wd": "http://www.wikidata.org/entity/" },
+                "queries": { "subjectCompletion": "SELECT ?qls_entity WHERE {} LIMIT {{ limit }}" }
+            }))
+            .await;
+
+        client
+            .open_document("file:///test.sparql", "SELECT * WHERE { Dougl }")
+            .await;
+        let id = client.complete("file:///test.sparql", 0, 22).await;
+        let response = client
+            .get_response(id)
+            .expect("Should receive completion response");
+        let item = response["result"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|item| item["label"] == "wd:Q42")
+            .expect("Should complete the entity")
+            .clone();
+        assert!(item.get("data").is_none());
+    });
+}
@@ -7,7 +7,8 @@ mod harness;
 use harness::TestClient;
 use harness::runtime::run_lsp_test;
 use indoc::indoc;
-use serde_json::Value;
+use serde_json::{Value, json};
+use wiremock::{Mock, MockServer, ResponseTemplate, matchers::method};
 
 /// Extract all diagnostics with the given code from a diagnostic response.
 fn diagnostics_with_code(response: &Value, code: &str) -> Vec<Value> {
@@ -154,3 +155,950 @@ fn test_star_selection_in_grouped_sub_select_is_flagged() {
         assert_eq!(diagnostic["range"]["end"]["character"], 12);
     });
 }
+
+// ========== type-mismatch ==========
+
+const TYPE_MISMATCH_CODE: &str = "type-mismatch";
+
+#[test]
+fn test_arithmetic_on_string_literal_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            r#"SELECT * WHERE {
+                 ?s ?p ?o
+                 BIND("a" + 1 AS ?x)
+               }"#
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, TYPE_MISMATCH_CODE);
+        assert_eq!(diagnostics.len(), 1);
+
+        let diagnostic = &diagnostics[0];
+        // NOTE: severity 2 = Warning
+        assert_eq!(diagnostic["severity"], 2);
+        // NOTE: the range should cover the string operand
+        assert_eq!(diagnostic["range"]["start"]["line"], 2);
+        assert_eq!(diagnostic["range"]["start"]["character"], 7);
+        assert_eq!(diagnostic["range"]["end"]["character"], 10);
+    });
+}
+
+#[test]
+fn test_built_in_argument_mismatches_are_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT (STRLEN(42) AS ?len) WHERE {
+               ?s ?p ?o
+               FILTER(LANG(<http://example.org/a>) = \"en\")
+             }
+             ORDER BY ABS(\"x\")"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, TYPE_MISMATCH_CODE);
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`STRLEN` expects a string argument, found numeric",
+                "`LANG` expects a literal argument, found IRI",
+                "`ABS` expects a numeric argument, found string",
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_boolean_operator_on_constant_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT ?s WHERE {
+               ?s ?p ?o
+             }
+             GROUP BY ?s
+             HAVING(COUNT(?o) > 1 && 5)"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, TYPE_MISMATCH_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0]["message"],
+            "`&&` expects boolean operands, found numeric"
+        );
+    });
+}
+
+#[test]
+fn test_xsd_cast_with_wrong_arity_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+             SELECT * WHERE {
+               ?s ?p ?o
+               FILTER(xsd:integer(?o, ?s) > 3)
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, TYPE_MISMATCH_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        // NOTE: severity 1 = Error
+        assert_eq!(diagnostics[0]["severity"], 1);
+        assert_eq!(
+            diagnostics[0]["message"],
+            "`xsd:integer` expects exactly 1 argument, found 2"
+        );
+    });
+}
+
+#[test]
+fn test_built_in_call_with_wrong_arity_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        // NOTE: a wrong arity is a syntax error that derails the rest of the
+        // group, so every call gets its own document.
+        let cases = [
+            (
+                "FILTER(STRLEN(?o, ?s) > 3)",
+                "`STRLEN` expects exactly 1 argument, found 2",
+            ),
+            (
+                "BIND(SUBSTR(?o) AS ?x)",
+                "`SUBSTR` expects 2 to 3 arguments, found 1",
+            ),
+            (
+                "BIND(NOW(?o) AS ?x)",
+                "`NOW` expects exactly 0 arguments, found 1",
+            ),
+        ];
+        for (idx, (pattern, message)) in cases.into_iter().enumerate() {
+            let uri = format!("file:///test{idx}.sparql");
+            client
+                .open_document(&uri, &format!("SELECT * WHERE {{ ?s ?p ?o {pattern} }}"))
+                .await;
+
+            let id = client.diagnostics(&uri).await;
+            let response = client.get_response(id).expect("Should receive response");
+
+            let diagnostics = diagnostics_with_code(&response, TYPE_MISMATCH_CODE);
+            assert_eq!(diagnostics.len(), 1, "{pattern}");
+            assert_eq!(diagnostics[0]["message"], message);
+            // NOTE: severity 1 = Error
+            assert_eq!(diagnostics[0]["severity"], 1);
+        }
+
+        // NOTE: the range covers the whole call
+        let id = client.diagnostics("file:///test0.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+        let diagnostics = diagnostics_with_code(&response, TYPE_MISMATCH_CODE);
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 33);
+        assert_eq!(diagnostics[0]["range"]["end"]["character"], 47);
+    });
+}
+
+#[test]
+fn test_well_typed_expressions_are_not_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            r#"PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+               SELECT (STRLEN(?label) + 1 AS ?len) WHERE {
+                 ?s ?p ?label
+                 FILTER(LANG(?label) = "en" && !BOUND(?x))
+                 FILTER(?o * "2"^^xsd:integer > xsd:integer("1"))
+                 BIND(CONCAT(STR(?s), "-", ?label) AS ?key)
+                 BIND(REPLACE(SUBSTR(?label, 1, 3), "a", "b") AS ?short)
+                 BIND(IF(BOUND(?x), NOW(), RAND()) AS ?z)
+               }
+               ORDER BY DESC(?len)"#
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        assert!(diagnostics_with_code(&response, TYPE_MISMATCH_CODE).is_empty());
+    });
+}
+
+// ========== bind-var-in-scope ==========
+
+const BIND_IN_SCOPE_CODE: &str = "bind-var-in-scope";
+
+#[test]
+fn test_bind_to_variable_in_scope_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT * WHERE {
+               ?s ?p ?o
+               BIND(42 AS ?o)
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, BIND_IN_SCOPE_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        // NOTE: the range should cover the bound variable
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic["severity"], 1);
+        assert_eq!(diagnostic["range"]["start"]["line"], 2);
+        assert_eq!(diagnostic["range"]["start"]["character"], 13);
+        assert_eq!(diagnostic["range"]["end"]["character"], 15);
+    });
+}
+
+#[test]
+fn test_bind_to_variable_out_of_scope_is_not_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT * WHERE {
+               FILTER(?a > 1)
+               MINUS { ?s ?p ?b }
+               { ?s ?p ?c }
+               BIND(1 AS ?a)
+               BIND(2 AS ?b)
+               BIND(3 AS ?d)
+               ?s ?p ?d
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        assert!(diagnostics_with_code(&response, BIND_IN_SCOPE_CODE).is_empty());
+    });
+}
+
+// ========== values-row-length ==========
+
+const VALUES_ROW_LENGTH_CODE: &str = "values-row-length";
+
+#[test]
+fn test_values_rows_with_wrong_length_are_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT * WHERE {
+               VALUES (?a ?b) { (1 2) (3) () (4 5 6) (UNDEF 7) }
+             }
+             VALUES (?c) { (1) (1 2) }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, VALUES_ROW_LENGTH_CODE);
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "This row has 1 value, but 2 variables are declared",
+                "This row has 0 values, but 2 variables are declared",
+                "This row has 3 values, but 2 variables are declared",
+                "This row has 2 values, but 1 variable is declared",
+            ]
+        );
+        // NOTE: the range should cover the row including its parentheses
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 25);
+        assert_eq!(diagnostics[0]["range"]["end"]["character"], 28);
+    });
+}
+
+// ========== duplicate-values-var ==========
+
+const DUPLICATE_VALUES_VAR_CODE: &str = "duplicate-values-var";
+
+#[test]
+fn test_duplicate_values_variable_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT * WHERE {
+               VALUES (?a ?b ?a) { (1 2 3) }
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, DUPLICATE_VALUES_VAR_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        // NOTE: only the repeated declaration is flagged
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 16);
+        assert_eq!(diagnostics[0]["range"]["end"]["character"], 18);
+    });
+}
+
+// ========== reused-blank-node-label ==========
+
+const REUSED_BLANK_NODE_LABEL_CODE: &str = "reused-blank-node-label";
+
+#[test]
+fn test_blank_node_label_reused_across_patterns_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT * WHERE {
+               _:b ?p ?o .
+               ?s ?q _:b .
+               OPTIONAL { _:b ?x ?y }
+               MINUS { _:c ?x ?y }
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, REUSED_BLANK_NODE_LABEL_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 3);
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 13);
+        assert_eq!(diagnostics[0]["range"]["end"]["character"], 16);
+    });
+}
+
+// ========== aggregate misuse ==========
+
+#[test]
+fn test_nested_aggregate_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT (SUM(COUNT(?o)) AS ?n) WHERE {
+               ?s ?p ?o
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, "nested-aggregate");
+        assert_eq!(diagnostics.len(), 1);
+        // NOTE: the inner aggregate is flagged
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 12);
+        assert_eq!(diagnostics[0]["range"]["end"]["character"], 21);
+    });
+}
+
+#[test]
+fn test_aggregate_in_where_clause_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT ?s WHERE {
+               ?s ?p ?o
+               FILTER(COUNT(?o) > 1)
+               BIND(MAX(?o) AS ?max)
+               {
+                 SELECT ?s (COUNT(?o) AS ?n) WHERE {
+                   ?s ?p ?o
+                 }
+                 GROUP BY ?s
+                 HAVING (COUNT(?o) > 2)
+                 ORDER BY DESC(COUNT(?o))
+               }
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, "misplaced-aggregate");
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Aggregates are not allowed in FILTER\nUse them in SELECT, HAVING or ORDER BY.",
+                "Aggregates are not allowed in BIND\nUse them in SELECT, HAVING or ORDER BY.",
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_ungrouped_variables_in_having_and_order_by_are_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT ?s (COUNT(?o) AS ?n) WHERE {
+               {
+                 SELECT (COUNT(?x) AS ?m) WHERE {
+                   ?x ?y ?z
+                 }
+                 ORDER BY ?z
+               }
+               ?s ?p ?o
+             }
+             GROUP BY ?s
+             HAVING (?n > 1 && ?p)
+             ORDER BY ?s ?o DESC(?n) MAX(?o)"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, "ungrouped-modifier-var");
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "?p is used in HAVING, but is not aggregated or part of the Group by Clause",
+                "?o is used in ORDER BY, but is not aggregated or part of the Group by Clause",
+                "?z is used in ORDER BY, but is not aggregated or part of the Group by Clause",
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_implicit_grouping_with_plain_variable_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = indoc!(
+            "SELECT ?s (COUNT(?o) AS ?n) (?n + ?p AS ?m) WHERE {
+               ?s ?p ?o
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, "implicit-grouping");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0]["range"]["start"]["character"], 7);
+        assert_eq!(diagnostics[1]["range"]["start"]["character"], 34);
+        // NOTE: explicit grouping is left to `ungrouped-select-var`
+        assert!(diagnostics_with_code(&response, "ungrouped-select-var").is_empty());
+    });
+}
+
+#[test]
+fn test_implicit_grouping_by_having_or_order_by_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        for (idx, modifier) in ["HAVING (COUNT(?o) > 1)", "ORDER BY DESC(COUNT(?o))"]
+            .into_iter()
+            .enumerate()
+        {
+            let uri = format!("file:///test{idx}.sparql");
+            client
+                .open_document(&uri, &format!("SELECT ?s WHERE {{ ?s ?p ?o }} {modifier}"))
+                .await;
+
+            let id = client.diagnostics(&uri).await;
+            let response = client.get_response(id).expect("Should receive response");
+
+            let diagnostics = diagnostics_with_code(&response, "implicit-grouping");
+            assert_eq!(diagnostics.len(), 1, "{modifier}");
+            assert_eq!(diagnostics[0]["range"]["start"]["character"], 7);
+        }
+    });
+}
+
+// ========== unused-term ==========
+
+const UNUSED_TERM_CODE: &str = "unused-term";
+
+const USAGE_CHECK_QUERY: &str = indoc!(
+    "SELECT ?qls_entity WHERE {
+       { VALUES ?qls_entity { {% for predicate in predicates %}{{ predicate }} {% endfor %} } [] ?qls_entity [] }
+       UNION
+       { VALUES ?qls_entity { {% for class in classes %}{{ class }} {% endfor %} } [] a ?qls_entity }
+     }"
+);
+
+async fn mock_usage_check_backend(used_iris: &[&str], expected_requests: u64) -> MockServer {
+    let mock_server = MockServer::start().await;
+    let bindings: Vec<Value> = used_iris
+        .iter()
+        .map(|iri| json!({ "qls_entity": { "type": "uri", "value": iri } }))
+        .collect();
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "head": { "vars": ["qls_entity"] },
+            "results": { "bindings": bindings }
+        })))
+        .expect(expected_requests)
+        .mount(&mock_server)
+        .await;
+    mock_server
+}
+
+/// Waits until the server sent a `workspace/diagnostic/refresh` request.
+async fn wait_for_diagnostic_refresh(client: &TestClient) {
+    for _ in 0..200 {
+        if !client
+            .captured
+            .find_notifications("workspace/diagnostic/refresh")
+            .is_empty()
+        {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("The server did not request a diagnostic refresh");
+}
+
+async fn add_usage_check_backend(client: &TestClient, url: &str) {
+    client
+        .add_backend_with(json!({
+            "name": "test",
+            "url": url,
+            "default": true,
+            "prefixMap": {},
+            "queries": { "usageCheck": USAGE_CHECK_QUERY }
+        }))
+        .await;
+}
+
+#[test]
+fn test_unused_predicate_and_class_are_flagged() {
+    run_lsp_test(|| async {
+        let mock_server = mock_usage_check_backend(&["http://ex.org/knows"], 1).await;
+        let client = TestClient::new();
+        client
+            .initialize_with(json!({
+                "processId": null,
+                "capabilities": {
+                    "workspace": { "diagnostics": { "refreshSupport": true } }
+                },
+                "rootUri": "file:///test"
+            }))
+            .await;
+        add_usage_check_backend(&client, &mock_server.uri()).await;
+        client
+            .change_settings(json!({ "diagnostics": { "usageCheck": true } }))
+            .await;
+
+        let query = indoc!(
+            "PREFIX ex: <http://ex.org/>
+             SELECT * WHERE {
+               ?s ex:knows ?o ;
+                  ex:knwos ?p ;
+                  a ex:Persn
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        // NOTE: the response does not wait for the backend, the check runs in the background
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+        assert!(diagnostics_with_code(&response, UNUSED_TERM_CODE).is_empty());
+        wait_for_diagnostic_refresh(&client).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+        let diagnostics = diagnostics_with_code(&response, UNUSED_TERM_CODE);
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "ex:knwos is a predicate never used in this dataset",
+                "ex:Persn is a class without instances in this dataset",
+            ]
+        );
+        assert_eq!(diagnostics[0]["severity"], 2);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 3);
+
+        // NOTE: the answers are cached, a second request does not query the backend again
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+        assert_eq!(diagnostics_with_code(&response, UNUSED_TERM_CODE).len(), 2);
+    });
+}
+
+#[test]
+fn test_usage_check_is_disabled_by_default() {
+    run_lsp_test(|| async {
+        let mock_server = mock_usage_check_backend(&[], 0).await;
+        let client = TestClient::new();
+        client.initialize().await;
+        add_usage_check_backend(&client, &mock_server.uri()).await;
+
+        let query = "SELECT * WHERE { ?s <http://ex.org/knwos> ?o }";
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        assert!(diagnostics_with_code(&response, UNUSED_TERM_CODE).is_empty());
+    });
+}
+
+// ========== performance-hint ==========
+
+const PERFORMANCE_HINT_CODE: &str = "performance-hint";
+
+#[test]
+fn test_performance_hints_are_reported() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .change_settings(json!({ "diagnostics": { "performanceHints": true } }))
+            .await;
+
+        let query = indoc!(
+            r#"SELECT DISTINCT * WHERE {
+                 ?a <knows>* ?b .
+                 ?s <name> ?name .
+                 OPTIONAL { FILTER(?name != "") }
+                 FILTER REGEX(?name, ".*son")
+                 FILTER REGEX(?name, "^and", "i")
+                 FILTER(?s = <alice>)
+                 { SELECT ?s WHERE { ?s <age> ?age } ORDER BY ?age }
+               }"#
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, PERFORMANCE_HINT_CODE);
+        let lines: Vec<u64> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic["range"]["start"]["line"].as_u64().unwrap())
+            .collect();
+        // NOTE: one hint per rule, in rule order
+        assert_eq!(lines, vec![4, 5, 7, 1, 3, 0, 6]);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic["severity"] == 3)
+        );
+    });
+}
+
+#[test]
+fn test_performance_hints_respect_engine_rules() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "virtuoso",
+                "url": "http://localhost/sparql",
+                "engine": "Virtuoso",
+                "default": true,
+                "prefixMap": {}
+            }))
+            .await;
+        client
+            .change_settings(json!({
+                "diagnostics": {
+                    "performanceHints": true,
+                    "performanceRules": { "QLever": ["constantFilter"] }
+                }
+            }))
+            .await;
+
+        let query = "SELECT DISTINCT * WHERE { ?s <name> ?name FILTER(?s = <alice>) }";
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        // NOTE: Virtuoso rewrites constant filters itself
+        let diagnostics = diagnostics_with_code(&response, PERFORMANCE_HINT_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0]["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("DISTINCT over SELECT *")
+        );
+    });
+}
+
+#[test]
+fn test_performance_hints_follow_service_backend() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "qlever",
+                "url": "http://localhost/qlever",
+                "engine": "QLever",
+                "default": true,
+                "prefixMap": {}
+            }))
+            .await;
+        client
+            .add_backend_with(json!({
+                "name": "virtuoso",
+                "url": "http://localhost/virtuoso",
+                "engine": "Virtuoso",
+                "prefixMap": {}
+            }))
+            .await;
+        client
+            .change_settings(json!({ "diagnostics": { "performanceHints": true } }))
+            .await;
+
+        let query = indoc!(
+            "SELECT ?s WHERE {
+               ?s <name> ?name FILTER(?s = <alice>)
+               SERVICE <http://localhost/virtuoso> {
+                 ?t <name> ?other FILTER(?t = <bob>)
+               }
+             }"
+        );
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        // NOTE: only the filter QLever runs is hinted, Virtuoso rewrites its own
+        let diagnostics = diagnostics_with_code(&response, PERFORMANCE_HINT_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+    });
+}
+
+#[test]
+fn test_performance_hints_are_disabled_by_default() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = "SELECT DISTINCT * WHERE { ?s ?p ?o }";
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        assert!(diagnostics_with_code(&response, PERFORMANCE_HINT_CODE).is_empty());
+    });
+}
+
+// ========== foreign-extension ==========
+
+const FOREIGN_EXTENSION_CODE: &str = "foreign-extension";
+
+#[test]
+fn test_extension_of_another_engine_is_flagged() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "qlever",
+                "url": "http://localhost/sparql",
+                "engine": "QLever",
+                "default": true,
+                "prefixMap": {}
+            }))
+            .await;
+
+        let query = indoc! {
+            "PREFIX bif: <http://www.openlinksw.com/schemas/bif#>
+             PREFIX geof: <http://www.opengis.net/def/function/geosparql/>
+             SELECT * WHERE {
+               ?s ?p ?o .
+               ?o bif:contains \"'word'\" .
+               BIND(geof:distance(?a, ?b) AS ?d)
+             }"
+        };
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        let diagnostics = diagnostics_with_code(&response, FOREIGN_EXTENSION_CODE);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 4);
+        assert_eq!(
+            diagnostics[0]["message"],
+            "\"bif:contains\" is in the `bif:` namespace of Virtuoso, but backend \"qlever\" runs QLever"
+        );
+    });
+}
+
+#[test]
+fn test_extensions_are_not_checked_without_engine() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "unknown",
+                "url": "http://localhost/sparql",
+                "default": true,
+                "prefixMap": {}
+            }))
+            .await;
+
+        let query = "PREFIX bif: <http://www.openlinksw.com/schemas/bif#>\nSELECT * WHERE { ?o bif:contains \"x\" }";
+        client.open_document("file:///test.sparql", query).await;
+
+        let id = client.diagnostics("file:///test.sparql").await;
+        let response = client.get_response(id).expect("Should receive response");
+
+        assert!(diagnostics_with_code(&response, FOREIGN_EXTENSION_CODE).is_empty());
+    });
+}
+
+// ========== undeclared-prefix ==========
+
+const UNDECLARED_PREFIX_CODE: &str = "undeclared-prefix";
+
+/// The text that the quickfix of the undeclared `prefix` inserts.
+async fn declare_prefix_quickfix(client: &TestClient, uri: &str, prefix: &str) -> String {
+    let id = client.diagnostics(uri).await;
+    let response = client.get_response(id).expect("Should receive response");
+    let diagnostics = diagnostics_with_code(&response, UNDECLARED_PREFIX_CODE);
+    let diagnostic = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["data"] == prefix)
+        .unwrap_or_else(|| panic!("Should flag \"{prefix}\", got: {diagnostics:?}"));
+
+    let id = client
+        .send_request(
+            "textDocument/codeAction",
+            json!({
+                "textDocument": { "uri": uri },
+                "range": diagnostic["range"],
+                "context": { "diagnostics": [diagnostic] }
+            }),
+        )
+        .await;
+    let response = client.get_response(id).expect("Should receive response");
+
+    let quickfix = response["result"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|action| action["title"] == format!("Declare prefix \"{prefix}\""))
+        .expect("Should offer to declare the prefix");
+    quickfix["edit"]["changes"][uri][0]["newText"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_undeclared_prefix_is_declared_from_catalog_without_backend() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+
+        let query = "SELECT * WHERE { ?s foaf:name ?name }";
+        client.open_document("file:///test.sparql", query).await;
+
+        assert_eq!(
+            declare_prefix_quickfix(&client, "file:///test.sparql", "foaf").await,
+            "PREFIX foaf: <http://xmlns.com/foaf/0.1/>\n"
+        );
+    });
+}
+
+#[test]
+fn test_undeclared_prefix_is_declared_from_catalog_missing_in_backend() {
+    run_lsp_test(|| async {
+        let client = TestClient::new();
+        client.initialize().await;
+        client
+            .add_backend_with(json!({
+                "name": "test",
+                "url": "http://localhost:0/sparql",
+                "default": true,
+                "prefixMap": {
+                    "ex": "http://ex.org/",
+                    "schema": "https://schema.org/"
+                }
+            }))
+            .await;
+
+        let query = "SELECT * WHERE { ?s foaf:name ?name ; schema:name ?name }";
+        client.open_document("file:///test.sparql", query).await;
+
+        assert_eq!(
+            declare_prefix_quickfix(&client, "file:///test.sparql", "foaf").await,
+            "PREFIX foaf: <http://xmlns.com/foaf/0.1/>\n"
+        );
+        // NOTE: the prefix map of the backend wins over the catalog
+        assert_eq!(
+            declare_prefix_quickfix(&client, "file:///test.sparql", "schema").await,
+            "PREFIX schema: <https://schema.org/>\n"
+        );
+    });
+}
@@ -192,6 +192,11 @@ impl TestClient {
         .await
     }
 
+    /// Request the details of a completion item.
+    pub async fn resolve_completion(&self, item: Value) -> u32 {
+        self.send_request("completionItem/resolve", item).await
+    }
+
     /// Request hover information at a position.
     pub async fn hover(&self, uri: &str, line: u32, character: u32) -> u32 {
         self.send_request(